@@ -0,0 +1,124 @@
+//! Compression
+//!
+//! Only decompression of DEFLATE ([RFC 1951]) streams is supported, either
+//! raw or wrapped in zlib ([RFC 1950]) or gzip ([RFC 1952]). Nothing here
+//! allocates, so it works in a bootloader.
+//!
+//! This module is available when the `compression` feature is enabled.
+//!
+//! [RFC 1950]: https://tools.ietf.org/html/rfc1950
+//! [RFC 1951]: https://tools.ietf.org/html/rfc1951
+//! [RFC 1952]: https://tools.ietf.org/html/rfc1952
+
+use core::fmt;
+
+use crate::error::{impl_foreign_error, Error};
+
+mod decoder;
+mod inflate;
+
+pub use self::decoder::DeflateDecoder;
+pub use self::inflate::Inflater;
+
+/// The container around a DEFLATE stream.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Format {
+    /// A bare DEFLATE stream, with no header or checksum.
+    Raw,
+    /// A zlib stream, checked with Adler-32.
+    Zlib,
+    /// A gzip member, checked with CRC-32 and the uncompressed size.
+    ///
+    /// Only the first member of a multi-member file is decoded.
+    Gzip,
+}
+
+/// An error in the compressed data.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum InflateError {
+    /// The zlib or gzip header is malformed or uses an unsupported feature,
+    /// such as a preset dictionary.
+    InvalidHeader,
+    /// The gzip header's CRC-16 doesn't match.
+    HeaderCrcMismatch,
+    /// A block has the reserved block type.
+    InvalidBlockType,
+    /// A stored block's length doesn't match its complement.
+    InvalidStoredLength,
+    /// A Huffman code is malformed, or the data uses a code that isn't in it.
+    InvalidHuffmanCode,
+    /// A match refers back past the start of the data.
+    InvalidDistance,
+    /// The stream ended early.
+    UnexpectedEof,
+    /// The zlib Adler-32 checksum doesn't match the data.
+    Adler32Mismatch,
+    /// The gzip CRC-32 doesn't match the data.
+    Crc32Mismatch,
+    /// The gzip uncompressed size doesn't match the data.
+    SizeMismatch,
+}
+
+impl fmt::Display for InflateError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            InflateError::InvalidHeader => "invalid or unsupported header",
+            InflateError::HeaderCrcMismatch => "header checksum mismatch",
+            InflateError::InvalidBlockType => "invalid block type",
+            InflateError::InvalidStoredLength => "invalid stored block length",
+            InflateError::InvalidHuffmanCode => "invalid Huffman code",
+            InflateError::InvalidDistance => "match distance too far back",
+            InflateError::UnexpectedEof => "unexpected end of compressed data",
+            InflateError::Adler32Mismatch => "Adler-32 checksum mismatch",
+            InflateError::Crc32Mismatch => "CRC-32 checksum mismatch",
+            InflateError::SizeMismatch => "uncompressed size mismatch",
+        })
+    }
+}
+
+impl Error for InflateError {}
+
+impl_foreign_error!(InflateError);
+
+/// The error type for [`DeflateDecoder`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DecompressError<E> {
+    /// Reading from the underlying reader failed.
+    Read(E),
+    /// The compressed data is invalid.
+    Inflate(InflateError),
+}
+
+impl<E> fmt::Display for DecompressError<E> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            DecompressError::Read(_) => "error reading compressed data",
+            DecompressError::Inflate(_) => "invalid compressed data",
+        })
+    }
+}
+
+impl<E: Error + 'static> Error for DecompressError<E> {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            DecompressError::Read(e) => Some(e),
+            DecompressError::Inflate(e) => Some(e),
+        }
+    }
+}
+
+impl_foreign_error! {
+    impl<E> for DecompressError<E>;
+    fn source(&self) {
+        match self {
+            DecompressError::Read(e) => Some(e),
+            DecompressError::Inflate(e) => Some(e),
+        }
+    }
+}
+
+impl<E> From<InflateError> for DecompressError<E> {
+    fn from(error: InflateError) -> Self {
+        DecompressError::Inflate(error)
+    }
+}
@@ -0,0 +1,171 @@
+use core::{
+    pin::Pin,
+    task::{Context, Poll},
+};
+
+use crate::{
+    compression::{DecompressError, Format, InflateError, Inflater},
+    io::{AsyncRead, Read},
+};
+
+const INPUT_SIZE: usize = 1024;
+
+/// Decompresses a DEFLATE, zlib or gzip stream read from the wrapped reader.
+///
+/// Works with both [`Read`] and [`AsyncRead`] readers. The trailer, if there
+/// is one, is checked before the end of the stream is reported, so a read
+/// returning zero means the data was intact.
+///
+/// Compressed data is read in chunks of up to 1 KiB, so the underlying reader
+/// may be read past the end of the stream. Together with the 32 KiB window,
+/// this makes the decoder about 34 KiB in size.
+///
+/// # Examples
+///
+/// ```
+/// use coreplus::compression::{DeflateDecoder, Format};
+/// use coreplus::io::Read;
+///
+/// // "hello, hello!" with gzip.
+/// let compressed = [
+///     0x1f, 0x8b, 0x08, 0x00, 0x00, 0x00, 0x00, 0x00, 0x02, 0x03, 0xcb, 0x48, 0xcd, 0xc9,
+///     0xc9, 0xd7, 0x51, 0xc8, 0x00, 0x51, 0x8a, 0x00, 0x9b, 0xdc, 0x9a, 0xb3, 0x0d, 0x00,
+///     0x00, 0x00,
+/// ];
+/// let mut decoder = DeflateDecoder::new(&compressed[..], Format::Gzip);
+///
+/// let mut buf = [0; 32];
+/// let mut len = 0;
+/// loop {
+///     match decoder.read(&mut buf[len..])? {
+///         0 => break,
+///         n => len += n,
+///     }
+/// }
+/// assert_eq!(&buf[..len], b"hello, hello!");
+/// # Ok::<(), coreplus::compression::DecompressError<core::convert::Infallible>>(())
+/// ```
+#[derive(Debug)]
+pub struct DeflateDecoder<R> {
+    inner: R,
+    state: Decoding,
+}
+
+#[derive(Debug)]
+struct Decoding {
+    inflater: Inflater,
+    input: [u8; INPUT_SIZE],
+    pos: usize,
+    len: usize,
+}
+
+impl Decoding {
+    /// Decodes buffered input into `buf`. Returns `None` if more input is
+    /// needed before anything can be produced.
+    fn inflate(&mut self, buf: &mut [u8]) -> Result<Option<usize>, InflateError> {
+        let (consumed, produced) = self
+            .inflater
+            .inflate(&self.input[self.pos..self.len], buf)?;
+        self.pos += consumed;
+        if produced != 0 || self.inflater.is_done() || buf.is_empty() {
+            Ok(Some(produced))
+        } else {
+            Ok(None)
+        }
+    }
+
+    /// Records that `n` bytes were read into `input`.
+    fn refilled(&mut self, n: usize) -> Result<(), InflateError> {
+        if n == 0 {
+            return Err(InflateError::UnexpectedEof);
+        }
+        self.pos = 0;
+        self.len = n;
+        Ok(())
+    }
+}
+
+impl<R> DeflateDecoder<R> {
+    /// Wraps `inner`, which holds data in the given format.
+    pub fn new(inner: R, format: Format) -> Self {
+        Self {
+            inner,
+            state: Decoding {
+                inflater: Inflater::new(format),
+                input: [0; INPUT_SIZE],
+                pos: 0,
+                len: 0,
+            },
+        }
+    }
+
+    /// Gets a reference to the underlying reader.
+    pub fn get_ref(&self) -> &R {
+        &self.inner
+    }
+
+    /// Gets a mutable reference to the underlying reader.
+    pub fn get_mut(&mut self) -> &mut R {
+        &mut self.inner
+    }
+
+    /// Gets a pinned mutable reference to the underlying reader.
+    pub fn get_pin_mut(self: Pin<&mut Self>) -> Pin<&mut R> {
+        self.project().0
+    }
+
+    /// Unwraps this `DeflateDecoder`, returning the underlying reader.
+    ///
+    /// Compressed data that was read but not yet decoded is lost.
+    pub fn into_inner(self) -> R {
+        self.inner
+    }
+
+    fn project(self: Pin<&mut Self>) -> (Pin<&mut R>, &mut Decoding) {
+        // SAFETY: `inner` is structurally pinned and never moved out of a
+        // pinned `DeflateDecoder`. Nothing else is pinned.
+        unsafe {
+            let this = self.get_unchecked_mut();
+            (Pin::new_unchecked(&mut this.inner), &mut this.state)
+        }
+    }
+}
+
+impl<R: Read> Read for DeflateDecoder<R> {
+    type Error = DecompressError<R::Error>;
+
+    fn read(&mut self, buf: &mut [u8]) -> Result<usize, Self::Error> {
+        loop {
+            if let Some(n) = self.state.inflate(buf)? {
+                return Ok(n);
+            }
+            let n = self
+                .inner
+                .read(&mut self.state.input)
+                .map_err(DecompressError::Read)?;
+            self.state.refilled(n)?;
+        }
+    }
+}
+
+impl<R: AsyncRead> AsyncRead for DeflateDecoder<R> {
+    type Error = DecompressError<R::Error>;
+
+    fn poll_read(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut [u8],
+    ) -> Poll<Result<usize, Self::Error>> {
+        let (mut inner, state) = self.project();
+        loop {
+            if let Some(n) = state.inflate(buf)? {
+                return Poll::Ready(Ok(n));
+            }
+            match inner.as_mut().poll_read(cx, &mut state.input) {
+                Poll::Ready(Ok(n)) => state.refilled(n)?,
+                Poll::Ready(Err(e)) => return Poll::Ready(Err(DecompressError::Read(e))),
+                Poll::Pending => return Poll::Pending,
+            }
+        }
+    }
+}
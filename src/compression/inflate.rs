@@ -0,0 +1,730 @@
+use core::cmp;
+
+use crate::{
+    compression::{Format, InflateError},
+    io::checksum::{Adler32, Checksum, Crc32},
+};
+
+/// The size of the sliding window, which is the furthest back a match can
+/// reach.
+const WINDOW_SIZE: usize = 32 * 1024;
+const WINDOW_MASK: usize = WINDOW_SIZE - 1;
+
+const MAX_BITS: usize = 15;
+const MAX_LIT_CODES: usize = 288;
+const MAX_DIST_CODES: usize = 32;
+const MAX_CL_CODES: usize = 19;
+
+const LEN_BASE: [u16; 29] = [
+    3, 4, 5, 6, 7, 8, 9, 10, 11, 13, 15, 17, 19, 23, 27, 31, 35, 43, 51, 59, 67, 83, 99, 115, 131,
+    163, 195, 227, 258,
+];
+const LEN_EXTRA: [u8; 29] = [
+    0, 0, 0, 0, 0, 0, 0, 0, 1, 1, 1, 1, 2, 2, 2, 2, 3, 3, 3, 3, 4, 4, 4, 4, 5, 5, 5, 5, 0,
+];
+const DIST_BASE: [u16; 30] = [
+    1, 2, 3, 4, 5, 7, 9, 13, 17, 25, 33, 49, 65, 97, 129, 193, 257, 385, 513, 769, 1025, 1537,
+    2049, 3073, 4097, 6145, 8193, 12289, 16385, 24577,
+];
+const DIST_EXTRA: [u8; 30] = [
+    0, 0, 0, 0, 1, 1, 2, 2, 3, 3, 4, 4, 5, 5, 6, 6, 7, 7, 8, 8, 9, 9, 10, 10, 11, 11, 12, 12, 13,
+    13,
+];
+/// The order in which code length code lengths are sent.
+const CL_ORDER: [usize; MAX_CL_CODES] = [
+    16, 17, 18, 0, 8, 7, 9, 6, 10, 5, 11, 4, 12, 3, 13, 2, 14, 1, 15,
+];
+
+const GZIP_FHCRC: u8 = 0x02;
+const GZIP_FEXTRA: u8 = 0x04;
+const GZIP_FNAME: u8 = 0x08;
+const GZIP_FCOMMENT: u8 = 0x10;
+const GZIP_RESERVED: u8 = 0xe0;
+
+/// A canonical Huffman code, decoded one bit at a time.
+///
+/// This is slower than a lookup table, but small and needs no allocation.
+struct Huffman<const N: usize> {
+    /// The number of codes of each length.
+    count: [u16; MAX_BITS + 1],
+    /// The symbols, ordered by code.
+    symbol: [u16; N],
+}
+
+impl<const N: usize> Huffman<N> {
+    const fn new() -> Self {
+        Self {
+            count: [0; MAX_BITS + 1],
+            symbol: [0; N],
+        }
+    }
+
+    /// Builds the code from the length of each symbol's code, where zero
+    /// means the symbol is unused.
+    ///
+    /// Incomplete codes are accepted; decoding an unused code is an error.
+    fn build(&mut self, lengths: &[u8]) -> Result<(), InflateError> {
+        self.count = [0; MAX_BITS + 1];
+        for &len in lengths {
+            self.count[len as usize] += 1;
+        }
+
+        let mut left: i32 = 1;
+        for len in 1..=MAX_BITS {
+            left <<= 1;
+            left -= self.count[len] as i32;
+            if left < 0 {
+                return Err(InflateError::InvalidHuffmanCode);
+            }
+        }
+
+        let mut offsets = [0; MAX_BITS + 1];
+        for len in 1..MAX_BITS {
+            offsets[len + 1] = offsets[len] + self.count[len];
+        }
+        for (symbol, &len) in lengths.iter().enumerate() {
+            if len != 0 {
+                self.symbol[offsets[len as usize] as usize] = symbol as u16;
+                offsets[len as usize] += 1;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Buffered input bits, least significant first.
+struct Bits {
+    bits: u64,
+    count: u32,
+}
+
+impl Bits {
+    /// Makes sure at least `n` bits are buffered, pulling bytes from `input`
+    /// as needed. Returns `false` if `input` ran out first.
+    fn need(&mut self, input: &mut &[u8], n: u32) -> bool {
+        while self.count < n {
+            match input.split_first() {
+                Some((&byte, rest)) => {
+                    self.bits |= (byte as u64) << self.count;
+                    self.count += 8;
+                    *input = rest;
+                }
+                None => return false,
+            }
+        }
+        true
+    }
+
+    fn peek(&self, n: u32) -> u32 {
+        (self.bits & ((1 << n) - 1)) as u32
+    }
+
+    fn consume(&mut self, n: u32) {
+        self.bits >>= n;
+        self.count -= n;
+    }
+
+    fn take(&mut self, n: u32) -> u32 {
+        let value = self.peek(n);
+        self.consume(n);
+        value
+    }
+
+    /// Discards bits up to the next byte boundary.
+    fn align(&mut self) {
+        let n = self.count % 8;
+        self.consume(n);
+    }
+
+    /// Decodes a symbol without consuming it, returning it along with the
+    /// length of its code. Returns `Ok(None)` if more input is needed.
+    fn peek_symbol<const N: usize>(
+        &mut self,
+        input: &mut &[u8],
+        huffman: &Huffman<N>,
+    ) -> Result<Option<(u16, u32)>, InflateError> {
+        let mut code: i32 = 0;
+        let mut first: i32 = 0;
+        let mut index: i32 = 0;
+        for len in 1..=MAX_BITS as u32 {
+            if !self.need(input, len) {
+                return Ok(None);
+            }
+            code |= ((self.bits >> (len - 1)) & 1) as i32;
+            let count = huffman.count[len as usize] as i32;
+            if code - count < first {
+                let symbol = huffman.symbol[(index + (code - first)) as usize];
+                return Ok(Some((symbol, len)));
+            }
+            index += count;
+            first += count;
+            first <<= 1;
+            code <<= 1;
+        }
+        Err(InflateError::InvalidHuffmanCode)
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum GzipField {
+    Fixed,
+    ExtraLen,
+    Extra(u16),
+    Name,
+    Comment,
+    HeaderCrc,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum State {
+    ZlibHeader,
+    GzipHeader(GzipField),
+    BlockHeader,
+    StoredHeader,
+    Stored(u16),
+    DynamicCounts,
+    CodeLengthLengths(usize, usize),
+    CodeLengths(usize),
+    Codes,
+    Distance(u16),
+    Copy(u16, u16),
+    Trailer,
+    Done,
+}
+
+enum Step {
+    Continue,
+    NeedInput,
+    OutputFull,
+}
+
+/// A streaming DEFLATE decompressor that you feed with input and output
+/// buffers.
+///
+/// This is the engine behind [`DeflateDecoder`](super::DeflateDecoder), for
+/// when the input arrives in chunks that you already have, such as network
+/// packets. It never allocates; it does hold the 32 KiB window, so it's
+/// rather large to keep on a small stack.
+///
+/// # Examples
+///
+/// ```
+/// use coreplus::compression::{Format, Inflater};
+///
+/// // "hello, hello!" with zlib, fed in two pieces.
+/// let compressed = [
+///     0x78, 0xda, 0xcb, 0x48, 0xcd, 0xc9, 0xc9, 0xd7, 0x51, 0xc8, 0x00, 0x51, 0x8a, 0x00,
+///     0x21, 0x70, 0x04, 0x96,
+/// ];
+/// let mut inflater = Inflater::new(Format::Zlib);
+/// let mut out = [0; 32];
+///
+/// let (consumed, produced) = inflater.inflate(&compressed[..7], &mut out).unwrap();
+/// assert_eq!(consumed, 7);
+/// let (_, rest) = inflater.inflate(&compressed[7..], &mut out[produced..]).unwrap();
+///
+/// assert!(inflater.is_done());
+/// assert_eq!(&out[..produced + rest], b"hello, hello!");
+/// ```
+pub struct Inflater {
+    format: Format,
+    state: State,
+    bits: Bits,
+    final_block: bool,
+
+    lit: Huffman<MAX_LIT_CODES>,
+    dist: Huffman<MAX_DIST_CODES>,
+    code_lengths: Huffman<MAX_CL_CODES>,
+    lengths: [u8; MAX_LIT_CODES + MAX_DIST_CODES],
+    num_lit: usize,
+    num_dist: usize,
+
+    window: [u8; WINDOW_SIZE],
+    window_pos: usize,
+    /// How much of the window has been written, up to its size.
+    window_filled: usize,
+
+    /// Bytes of a header or trailer field read so far.
+    field: [u8; 10],
+    field_len: usize,
+    gzip_flags: u8,
+    header_crc: Crc32,
+
+    crc: Crc32,
+    adler: Adler32,
+    total_out: u32,
+}
+
+impl core::fmt::Debug for Inflater {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("Inflater")
+            .field("format", &self.format)
+            .field("done", &self.is_done())
+            .finish()
+    }
+}
+
+impl Inflater {
+    /// Creates a decompressor for the given format.
+    pub fn new(format: Format) -> Self {
+        let state = match format {
+            Format::Raw => State::BlockHeader,
+            Format::Zlib => State::ZlibHeader,
+            Format::Gzip => State::GzipHeader(GzipField::Fixed),
+        };
+        Self {
+            format,
+            state,
+            bits: Bits { bits: 0, count: 0 },
+            final_block: false,
+            lit: Huffman::new(),
+            dist: Huffman::new(),
+            code_lengths: Huffman::new(),
+            lengths: [0; MAX_LIT_CODES + MAX_DIST_CODES],
+            num_lit: 0,
+            num_dist: 0,
+            window: [0; WINDOW_SIZE],
+            window_pos: 0,
+            window_filled: 0,
+            field: [0; 10],
+            field_len: 0,
+            gzip_flags: 0,
+            header_crc: Crc32::new(),
+            crc: Crc32::new(),
+            adler: Adler32::new(),
+            total_out: 0,
+        }
+    }
+
+    /// Returns the format this decompressor expects.
+    pub fn format(&self) -> Format {
+        self.format
+    }
+
+    /// Returns `true` once the end of the stream, including any trailer, has
+    /// been decoded and checked.
+    pub fn is_done(&self) -> bool {
+        self.state == State::Done
+    }
+
+    /// Decompresses as much of `input` into `output` as possible, returning
+    /// the number of bytes consumed and produced.
+    ///
+    /// Input is only consumed as far as it's needed, so nothing after the end
+    /// of the stream is touched. If less than all of `input` was consumed,
+    /// `output` is full or the stream is done. Otherwise, call this again
+    /// with more input.
+    pub fn inflate(
+        &mut self,
+        input: &[u8],
+        output: &mut [u8],
+    ) -> Result<(usize, usize), InflateError> {
+        let mut rest = input;
+        let mut produced = 0;
+        let result = loop {
+            match self.step(&mut rest, output, &mut produced) {
+                Ok(Step::Continue) => {}
+                Ok(Step::NeedInput) | Ok(Step::OutputFull) => break Ok(()),
+                Err(e) => break Err(e),
+            }
+        };
+
+        match self.format {
+            Format::Raw => {}
+            Format::Zlib => self.adler.update(&output[..produced]),
+            Format::Gzip => self.crc.update(&output[..produced]),
+        }
+        self.total_out = self.total_out.wrapping_add(produced as u32);
+        // The trailer is checked against the data produced by earlier calls,
+        // so it's only verified once that has been added up.
+        if result.is_ok() && self.state == State::Trailer {
+            self.check_trailer(&mut rest)?;
+        }
+        result.map(|()| (input.len() - rest.len(), produced))
+    }
+
+    fn emit(&mut self, output: &mut [u8], produced: &mut usize, byte: u8) {
+        output[*produced] = byte;
+        *produced += 1;
+        self.window[self.window_pos] = byte;
+        self.window_pos = (self.window_pos + 1) & WINDOW_MASK;
+        if self.window_filled < WINDOW_SIZE {
+            self.window_filled += 1;
+        }
+    }
+
+    /// Reads a byte of a header or trailer.
+    fn byte(&mut self, input: &mut &[u8]) -> Option<u8> {
+        if !self.bits.need(input, 8) {
+            return None;
+        }
+        Some(self.bits.take(8) as u8)
+    }
+
+    /// Collects a header or trailer field of `n` bytes into `self.field`.
+    fn field(&mut self, input: &mut &[u8], n: usize) -> bool {
+        while self.field_len < n {
+            match self.byte(input) {
+                Some(byte) => {
+                    self.field[self.field_len] = byte;
+                    self.field_len += 1;
+                }
+                None => return false,
+            }
+        }
+        self.field_len = 0;
+        true
+    }
+
+    fn step(
+        &mut self,
+        input: &mut &[u8],
+        output: &mut [u8],
+        produced: &mut usize,
+    ) -> Result<Step, InflateError> {
+        match self.state {
+            State::ZlibHeader => {
+                if !self.field(input, 2) {
+                    return Ok(Step::NeedInput);
+                }
+                let (cmf, flg) = (self.field[0], self.field[1]);
+                let method_ok = cmf & 0x0f == 8 && cmf >> 4 <= 7;
+                let check_ok = (u16::from(cmf) << 8 | u16::from(flg)) % 31 == 0;
+                let no_dict = flg & 0x20 == 0;
+                if !(method_ok && check_ok && no_dict) {
+                    return Err(InflateError::InvalidHeader);
+                }
+                self.state = State::BlockHeader;
+            }
+            State::GzipHeader(field) => return self.gzip_header(input, field),
+            State::BlockHeader => {
+                if !self.bits.need(input, 3) {
+                    return Ok(Step::NeedInput);
+                }
+                self.final_block = self.bits.take(1) == 1;
+                self.state = match self.bits.take(2) {
+                    0 => State::StoredHeader,
+                    1 => {
+                        self.fixed_codes();
+                        State::Codes
+                    }
+                    2 => State::DynamicCounts,
+                    _ => return Err(InflateError::InvalidBlockType),
+                };
+            }
+            State::StoredHeader => {
+                self.bits.align();
+                if !self.bits.need(input, 32) {
+                    return Ok(Step::NeedInput);
+                }
+                let len = self.bits.take(16) as u16;
+                let nlen = self.bits.take(16) as u16;
+                if len != !nlen {
+                    return Err(InflateError::InvalidStoredLength);
+                }
+                self.state = State::Stored(len);
+            }
+            State::Stored(0) => self.end_block(),
+            State::Stored(remaining) => {
+                if *produced == output.len() {
+                    return Ok(Step::OutputFull);
+                }
+                // The header left the bit buffer empty, so bytes come straight
+                // from the input.
+                debug_assert_eq!(self.bits.count, 0);
+                let n = cmp::min(
+                    remaining as usize,
+                    cmp::min(input.len(), output.len() - *produced),
+                );
+                if n == 0 {
+                    return Ok(Step::NeedInput);
+                }
+                for i in 0..n {
+                    self.emit(output, produced, input[i]);
+                }
+                *input = &input[n..];
+                self.state = State::Stored(remaining - n as u16);
+            }
+            State::DynamicCounts => {
+                if !self.bits.need(input, 14) {
+                    return Ok(Step::NeedInput);
+                }
+                self.num_lit = self.bits.take(5) as usize + 257;
+                self.num_dist = self.bits.take(5) as usize + 1;
+                let num_cl = self.bits.take(4) as usize + 4;
+                if self.num_lit > 286 || self.num_dist > 30 {
+                    return Err(InflateError::InvalidHuffmanCode);
+                }
+                self.lengths = [0; MAX_LIT_CODES + MAX_DIST_CODES];
+                self.state = State::CodeLengthLengths(0, num_cl);
+            }
+            State::CodeLengthLengths(i, n) if i == n => {
+                // The ones that weren't sent are still zero.
+                let mut cl_lengths = [0; MAX_CL_CODES];
+                cl_lengths.copy_from_slice(&self.lengths[..MAX_CL_CODES]);
+                self.code_lengths.build(&cl_lengths)?;
+                self.lengths = [0; MAX_LIT_CODES + MAX_DIST_CODES];
+                self.state = State::CodeLengths(0);
+            }
+            State::CodeLengthLengths(i, n) => {
+                if !self.bits.need(input, 3) {
+                    return Ok(Step::NeedInput);
+                }
+                self.lengths[CL_ORDER[i]] = self.bits.take(3) as u8;
+                self.state = State::CodeLengthLengths(i + 1, n);
+            }
+            State::CodeLengths(i) => return self.code_lengths(input, i),
+            State::Codes => {
+                if *produced == output.len() {
+                    return Ok(Step::OutputFull);
+                }
+                let (symbol, len) = match self.bits.peek_symbol(input, &self.lit)? {
+                    Some(decoded) => decoded,
+                    None => return Ok(Step::NeedInput),
+                };
+                match symbol {
+                    0..=255 => {
+                        self.bits.consume(len);
+                        self.emit(output, produced, symbol as u8);
+                    }
+                    256 => {
+                        self.bits.consume(len);
+                        self.end_block();
+                    }
+                    257..=285 => {
+                        let index = (symbol - 257) as usize;
+                        let extra = u32::from(LEN_EXTRA[index]);
+                        if !self.bits.need(input, len + extra) {
+                            return Ok(Step::NeedInput);
+                        }
+                        self.bits.consume(len);
+                        let length = LEN_BASE[index] + self.bits.take(extra) as u16;
+                        self.state = State::Distance(length);
+                    }
+                    _ => return Err(InflateError::InvalidHuffmanCode),
+                }
+            }
+            State::Distance(length) => {
+                let (symbol, len) = match self.bits.peek_symbol(input, &self.dist)? {
+                    Some(decoded) => decoded,
+                    None => return Ok(Step::NeedInput),
+                };
+                let index = symbol as usize;
+                if index >= DIST_BASE.len() {
+                    return Err(InflateError::InvalidHuffmanCode);
+                }
+                let extra = u32::from(DIST_EXTRA[index]);
+                if !self.bits.need(input, len + extra) {
+                    return Ok(Step::NeedInput);
+                }
+                self.bits.consume(len);
+                let distance = DIST_BASE[index] + self.bits.take(extra) as u16;
+                if distance as usize > self.window_filled {
+                    return Err(InflateError::InvalidDistance);
+                }
+                self.state = State::Copy(length, distance);
+            }
+            State::Copy(0, _) => self.state = State::Codes,
+            State::Copy(mut length, distance) => {
+                while length != 0 {
+                    if *produced == output.len() {
+                        self.state = State::Copy(length, distance);
+                        return Ok(Step::OutputFull);
+                    }
+                    let from = (self.window_pos + WINDOW_SIZE - distance as usize) & WINDOW_MASK;
+                    self.emit(output, produced, self.window[from]);
+                    length -= 1;
+                }
+                self.state = State::Codes;
+            }
+            // Checked in `inflate`, once the output has been summed.
+            State::Trailer => return Ok(Step::NeedInput),
+            State::Done => return Ok(Step::NeedInput),
+        }
+        Ok(Step::Continue)
+    }
+
+    fn gzip_header(&mut self, input: &mut &[u8], field: GzipField) -> Result<Step, InflateError> {
+        let next = match field {
+            GzipField::Fixed => {
+                if !self.field(input, 10) {
+                    return Ok(Step::NeedInput);
+                }
+                let flags = self.field[3];
+                if self.field[..3] != [0x1f, 0x8b, 8] || flags & GZIP_RESERVED != 0 {
+                    return Err(InflateError::InvalidHeader);
+                }
+                self.header_crc.update(&self.field);
+                self.gzip_flags = flags;
+                GzipField::ExtraLen
+            }
+            GzipField::ExtraLen => {
+                if self.gzip_flags & GZIP_FEXTRA == 0 {
+                    GzipField::Name
+                } else if self.field(input, 2) {
+                    self.header_crc.update(&self.field[..2]);
+                    GzipField::Extra(u16::from_le_bytes([self.field[0], self.field[1]]))
+                } else {
+                    return Ok(Step::NeedInput);
+                }
+            }
+            GzipField::Extra(0) => GzipField::Name,
+            GzipField::Extra(remaining) => match self.byte(input) {
+                Some(byte) => {
+                    self.header_crc.update(&[byte]);
+                    GzipField::Extra(remaining - 1)
+                }
+                None => return Ok(Step::NeedInput),
+            },
+            GzipField::Name | GzipField::Comment => {
+                let flag = if field == GzipField::Name {
+                    GZIP_FNAME
+                } else {
+                    GZIP_FCOMMENT
+                };
+                let after = if field == GzipField::Name {
+                    GzipField::Comment
+                } else {
+                    GzipField::HeaderCrc
+                };
+                if self.gzip_flags & flag != 0 {
+                    // Skip the zero-terminated string.
+                    loop {
+                        let byte = match self.byte(input) {
+                            Some(byte) => byte,
+                            None => return Ok(Step::NeedInput),
+                        };
+                        self.header_crc.update(&[byte]);
+                        if byte == 0 {
+                            break;
+                        }
+                    }
+                }
+                after
+            }
+            GzipField::HeaderCrc => {
+                if self.gzip_flags & GZIP_FHCRC != 0 {
+                    if !self.field(input, 2) {
+                        return Ok(Step::NeedInput);
+                    }
+                    let expected = self.header_crc.finish() as u16;
+                    if u16::from_le_bytes([self.field[0], self.field[1]]) != expected {
+                        return Err(InflateError::HeaderCrcMismatch);
+                    }
+                }
+                self.state = State::BlockHeader;
+                return Ok(Step::Continue);
+            }
+        };
+        self.state = State::GzipHeader(next);
+        Ok(Step::Continue)
+    }
+
+    fn code_lengths(&mut self, input: &mut &[u8], mut i: usize) -> Result<Step, InflateError> {
+        let total = self.num_lit + self.num_dist;
+        while i < total {
+            let (symbol, len) = match self.bits.peek_symbol(input, &self.code_lengths)? {
+                Some(decoded) => decoded,
+                None => {
+                    self.state = State::CodeLengths(i);
+                    return Ok(Step::NeedInput);
+                }
+            };
+            let (value, extra, base) = match symbol {
+                0..=15 => {
+                    self.bits.consume(len);
+                    self.lengths[i] = symbol as u8;
+                    i += 1;
+                    continue;
+                }
+                16 if i == 0 => return Err(InflateError::InvalidHuffmanCode),
+                16 => (self.lengths[i - 1], 2, 3),
+                17 => (0, 3, 3),
+                _ => (0, 7, 11),
+            };
+            if !self.bits.need(input, len + extra) {
+                self.state = State::CodeLengths(i);
+                return Ok(Step::NeedInput);
+            }
+            self.bits.consume(len);
+            let repeat = base + self.bits.take(extra) as usize;
+            if i + repeat > total {
+                return Err(InflateError::InvalidHuffmanCode);
+            }
+            for length in &mut self.lengths[i..i + repeat] {
+                *length = value;
+            }
+            i += repeat;
+        }
+
+        // Without an end-of-block code, the block could never finish.
+        if self.lengths[256] == 0 {
+            return Err(InflateError::InvalidHuffmanCode);
+        }
+        let (lit, dist) = self.lengths[..total].split_at(self.num_lit);
+        self.lit.build(lit)?;
+        self.dist.build(dist)?;
+        self.state = State::Codes;
+        Ok(Step::Continue)
+    }
+
+    fn fixed_codes(&mut self) {
+        let mut lengths = [0; MAX_LIT_CODES];
+        for (symbol, length) in lengths.iter_mut().enumerate() {
+            *length = match symbol {
+                0..=143 => 8,
+                144..=255 => 9,
+                256..=279 => 7,
+                _ => 8,
+            };
+        }
+        // These are complete codes, so building them can't fail.
+        let _ = self.lit.build(&lengths);
+        let _ = self.dist.build(&[5; 30]);
+    }
+
+    fn end_block(&mut self) {
+        self.state = if !self.final_block {
+            State::BlockHeader
+        } else if self.format == Format::Raw {
+            State::Done
+        } else {
+            self.bits.align();
+            State::Trailer
+        };
+    }
+
+    fn check_trailer(&mut self, input: &mut &[u8]) -> Result<(), InflateError> {
+        match self.format {
+            Format::Raw => {}
+            Format::Zlib => {
+                if !self.field(input, 4) {
+                    return Ok(());
+                }
+                let expected = u32::from_be_bytes([
+                    self.field[0],
+                    self.field[1],
+                    self.field[2],
+                    self.field[3],
+                ]);
+                if expected != self.adler.finish() {
+                    return Err(InflateError::Adler32Mismatch);
+                }
+            }
+            Format::Gzip => {
+                if !self.field(input, 8) {
+                    return Ok(());
+                }
+                let f = self.field;
+                if u32::from_le_bytes([f[0], f[1], f[2], f[3]]) != self.crc.finish() {
+                    return Err(InflateError::Crc32Mismatch);
+                }
+                if u32::from_le_bytes([f[4], f[5], f[6], f[7]]) != self.total_out {
+                    return Err(InflateError::SizeMismatch);
+                }
+            }
+        }
+        self.state = State::Done;
+        Ok(())
+    }
+}
@@ -0,0 +1,95 @@
+//! Errors
+//!
+//! [`std::error::Error`] isn't available without the standard library, so
+//! this module provides an equivalent [`Error`] trait that works everywhere.
+//! Every error type in this crate implements it.
+//!
+//! The error types in this crate also implement [`std::error::Error`] when the
+//! `std` feature is enabled, and `core::error::Error` when the `core-error`
+//! feature is enabled (this needs Rust 1.81 or newer). In both cases the
+//! [`source`](Error::source) chain is preserved as long as the wrapped errors
+//! implement that trait too.
+
+use core::{convert::Infallible, fmt};
+
+/// A no_std equivalent of [`std::error::Error`].
+///
+/// # Examples
+///
+/// ```
+/// use coreplus::error::Error;
+/// use coreplus::io::CopyError;
+/// use coreplus::net::Ipv4Addr;
+///
+/// let parse_error = "not an address".parse::<Ipv4Addr>().unwrap_err();
+/// let error: CopyError<_, core::convert::Infallible> = CopyError::Read(parse_error);
+///
+/// assert_eq!(error.to_string(), "error reading");
+/// assert_eq!(
+///     error.source().unwrap().to_string(),
+///     "invalid IP address syntax"
+/// );
+/// ```
+pub trait Error: fmt::Debug + fmt::Display {
+    /// Returns the lower-level source of this error, if any.
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        None
+    }
+}
+
+impl<E: Error + ?Sized> Error for &E {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        (**self).source()
+    }
+}
+
+impl Error for Infallible {}
+
+impl Error for fmt::Error {}
+
+#[cfg(feature = "alloc")]
+impl<E: Error + ?Sized> Error for alloc::boxed::Box<E> {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        (**self).source()
+    }
+}
+
+/// [`std::io::Error`] has no source that is a coreplus [`Error`], so the
+/// chain stops here.
+#[cfg(feature = "std")]
+impl Error for std::io::Error {}
+
+/// Implements [`std::error::Error`] or `core::error::Error`, whichever is
+/// available, for an error type in this crate.
+///
+/// The first form gives each generic parameter an `Error` bound and forwards
+/// `source` to the given block, once for each trait. The others are for
+/// errors without a source.
+macro_rules! impl_foreign_error {
+    (
+        impl<$($param:ident),*> for $ty:ty;
+        fn source(&$self:ident) $body:block
+    ) => {
+        #[cfg(feature = "std")]
+        impl<$($param: std::error::Error + 'static),*> std::error::Error for $ty {
+            fn source(&$self) -> Option<&(dyn std::error::Error + 'static)> $body
+        }
+
+        #[cfg(all(feature = "core-error", not(feature = "std")))]
+        impl<$($param: core::error::Error + 'static),*> core::error::Error for $ty {
+            fn source(&$self) -> Option<&(dyn core::error::Error + 'static)> $body
+        }
+    };
+    (impl<$($param:ident),*> for $ty:ty) => {
+        #[cfg(feature = "std")]
+        impl<$($param),*> std::error::Error for $ty {}
+
+        #[cfg(all(feature = "core-error", not(feature = "std")))]
+        impl<$($param),*> core::error::Error for $ty {}
+    };
+    ($ty:ty) => {
+        $crate::error::impl_foreign_error!(impl<> for $ty);
+    };
+}
+
+pub(crate) use impl_foreign_error;
@@ -0,0 +1,246 @@
+//! Filesystems
+
+use crate::io::{Read, Seek, Write};
+
+#[cfg(feature = "alloc")]
+mod memory;
+#[cfg(feature = "std")]
+mod std_impl;
+
+#[cfg(feature = "alloc")]
+pub use self::memory::*;
+#[cfg(feature = "std")]
+pub use self::std_impl::*;
+
+/// A filesystem.
+///
+/// Implement this for your storage stack. Paths are `/`-separated strings.
+///
+/// # Examples
+///
+/// ```
+/// # #[cfg(feature = "alloc")]
+/// # fn main() -> Result<(), coreplus::fs::MemoryError> {
+/// use coreplus::fs::{FileSystem, MemoryFileSystem, OpenOptions};
+/// use coreplus::io::{Read, Write};
+///
+/// let fs = MemoryFileSystem::new();
+/// fs.create("greeting.txt")?.write_all(b"hello")?;
+///
+/// let mut buf = [0; 8];
+/// let n = fs.open("greeting.txt", &OpenOptions::new().read(true))?.read(&mut buf)?;
+/// assert_eq!(&buf[..n], b"hello");
+/// assert_eq!(fs.metadata("greeting.txt")?.len(), 5);
+/// # Ok(())
+/// # }
+/// # #[cfg(not(feature = "alloc"))]
+/// # fn main() {}
+/// ```
+pub trait FileSystem {
+    /// The associated error type.
+    type Error;
+
+    /// A handle to an open file.
+    type File: Read<Error = Self::Error> + Write<Error = Self::Error> + Seek<Error = Self::Error>;
+
+    /// An entry returned by [`read_dir`](FileSystem::read_dir).
+    type DirEntry: DirEntry;
+
+    /// The iterator returned by [`read_dir`](FileSystem::read_dir).
+    type ReadDir: Iterator<Item = Result<Self::DirEntry, Self::Error>>;
+
+    /// Opens a file with the given options.
+    fn open(&self, path: &str, options: &OpenOptions) -> Result<Self::File, Self::Error>;
+
+    /// Opens a file in write-only mode, creating it if it doesn't exist and
+    /// truncating it if it does.
+    fn create(&self, path: &str) -> Result<Self::File, Self::Error> {
+        self.open(
+            path,
+            OpenOptions::new().write(true).create(true).truncate(true),
+        )
+    }
+
+    /// Creates a new, empty directory.
+    fn create_dir(&self, path: &str) -> Result<(), Self::Error>;
+
+    /// Removes a file.
+    fn remove_file(&self, path: &str) -> Result<(), Self::Error>;
+
+    /// Removes an empty directory.
+    fn remove_dir(&self, path: &str) -> Result<(), Self::Error>;
+
+    /// Renames a file or directory, replacing `to` if it is an existing file.
+    fn rename(&self, from: &str, to: &str) -> Result<(), Self::Error>;
+
+    /// Queries metadata about a file or directory.
+    fn metadata(&self, path: &str) -> Result<Metadata, Self::Error>;
+
+    /// Returns an iterator over the entries within a directory.
+    fn read_dir(&self, path: &str) -> Result<Self::ReadDir, Self::Error>;
+}
+
+/// An entry inside a directory.
+pub trait DirEntry {
+    /// Returns the name of this entry, without the leading path.
+    fn file_name(&self) -> &str;
+
+    /// Returns the type of this entry.
+    fn file_type(&self) -> FileType;
+}
+
+/// The type of a filesystem entry.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum FileType {
+    /// A regular file.
+    File,
+    /// A directory.
+    Dir,
+    /// A symbolic link.
+    Symlink,
+    /// Anything else, such as a device or a socket.
+    Other,
+}
+
+impl FileType {
+    /// Returns `true` if this is a regular file.
+    pub fn is_file(&self) -> bool {
+        *self == FileType::File
+    }
+
+    /// Returns `true` if this is a directory.
+    pub fn is_dir(&self) -> bool {
+        *self == FileType::Dir
+    }
+
+    /// Returns `true` if this is a symbolic link.
+    pub fn is_symlink(&self) -> bool {
+        *self == FileType::Symlink
+    }
+}
+
+/// Metadata about a file or directory.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Metadata {
+    file_type: FileType,
+    len: u64,
+}
+
+impl Metadata {
+    /// Creates metadata for an entry of the given type and size in bytes.
+    pub fn new(file_type: FileType, len: u64) -> Self {
+        Self { file_type, len }
+    }
+
+    /// Returns the type of the entry.
+    pub fn file_type(&self) -> FileType {
+        self.file_type
+    }
+
+    /// Returns `true` if this metadata is for a regular file.
+    pub fn is_file(&self) -> bool {
+        self.file_type.is_file()
+    }
+
+    /// Returns `true` if this metadata is for a directory.
+    pub fn is_dir(&self) -> bool {
+        self.file_type.is_dir()
+    }
+
+    /// Returns the size of the file, in bytes.
+    pub fn len(&self) -> u64 {
+        self.len
+    }
+
+    /// Returns `true` if the file is empty.
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+}
+
+/// Options for opening a file with [`FileSystem::open`].
+///
+/// This mirrors [`std::fs::OpenOptions`]. All options start out `false`.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct OpenOptions {
+    read: bool,
+    write: bool,
+    append: bool,
+    truncate: bool,
+    create: bool,
+    create_new: bool,
+}
+
+impl OpenOptions {
+    /// Creates a blank set of options.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the option for read access.
+    pub fn read(&mut self, read: bool) -> &mut Self {
+        self.read = read;
+        self
+    }
+
+    /// Sets the option for write access.
+    pub fn write(&mut self, write: bool) -> &mut Self {
+        self.write = write;
+        self
+    }
+
+    /// Sets the option for append mode, which implies write access.
+    pub fn append(&mut self, append: bool) -> &mut Self {
+        self.append = append;
+        self
+    }
+
+    /// Sets the option for truncating an existing file to zero length.
+    pub fn truncate(&mut self, truncate: bool) -> &mut Self {
+        self.truncate = truncate;
+        self
+    }
+
+    /// Sets the option to create the file if it doesn't exist.
+    pub fn create(&mut self, create: bool) -> &mut Self {
+        self.create = create;
+        self
+    }
+
+    /// Sets the option to create the file, failing if it already exists.
+    pub fn create_new(&mut self, create_new: bool) -> &mut Self {
+        self.create_new = create_new;
+        self
+    }
+
+    /// Returns whether read access was requested.
+    pub fn is_read(&self) -> bool {
+        self.read
+    }
+
+    /// Returns whether write access was requested, either directly or through
+    /// append mode.
+    pub fn is_write(&self) -> bool {
+        self.write || self.append
+    }
+
+    /// Returns whether append mode was requested.
+    pub fn is_append(&self) -> bool {
+        self.append
+    }
+
+    /// Returns whether truncation was requested.
+    pub fn is_truncate(&self) -> bool {
+        self.truncate
+    }
+
+    /// Returns whether the file should be created if it doesn't exist.
+    pub fn is_create(&self) -> bool {
+        self.create
+    }
+
+    /// Returns whether the file must be newly created.
+    pub fn is_create_new(&self) -> bool {
+        self.create_new
+    }
+}
@@ -0,0 +1,433 @@
+use alloc::{
+    collections::BTreeMap,
+    rc::Rc,
+    string::{String, ToString},
+    vec::Vec,
+};
+use core::{cell::RefCell, cmp, convert::TryFrom, fmt};
+
+use crate::{
+    fs::{DirEntry, FileSystem, FileType, Metadata, OpenOptions},
+    io::{Read, Seek, SeekFrom, Write},
+};
+
+/// The error type for [`MemoryFileSystem`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum MemoryError {
+    /// The path doesn't exist.
+    NotFound,
+    /// The path already exists.
+    AlreadyExists,
+    /// A directory was expected, but the path is a file.
+    NotADirectory,
+    /// A file was expected, but the path is a directory.
+    IsADirectory,
+    /// The directory isn't empty.
+    DirectoryNotEmpty,
+    /// The path can't be used for this operation, such as removing the root.
+    InvalidPath,
+    /// The file was opened without read or write access.
+    InvalidOptions,
+    /// The file wasn't opened with the access needed for this operation.
+    PermissionDenied,
+    /// A seek to a negative or overflowing position.
+    InvalidSeek,
+    /// A write would make the file larger than can be addressed in memory.
+    FileTooLarge,
+}
+
+impl fmt::Display for MemoryError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            MemoryError::NotFound => "entity not found",
+            MemoryError::AlreadyExists => "entity already exists",
+            MemoryError::NotADirectory => "not a directory",
+            MemoryError::IsADirectory => "is a directory",
+            MemoryError::DirectoryNotEmpty => "directory not empty",
+            MemoryError::InvalidPath => "invalid path",
+            MemoryError::InvalidOptions => "file must be opened for reading or writing",
+            MemoryError::PermissionDenied => "file not opened with the required access",
+            MemoryError::InvalidSeek => "invalid seek to a negative or overflowing position",
+            MemoryError::FileTooLarge => "file too large",
+        })
+    }
+}
+
+impl crate::error::Error for MemoryError {}
+
+crate::error::impl_foreign_error!(MemoryError);
+
+type Contents = Rc<RefCell<Vec<u8>>>;
+
+enum Node {
+    File(Contents),
+    Dir,
+}
+
+/// A filesystem that lives entirely in memory, for tests.
+///
+/// Open files share their contents with the filesystem, so writes are visible
+/// to everyone as soon as they are made. Like on Unix, a file that is removed
+/// while open stays readable through the open handle.
+///
+/// This type is available when the `alloc` feature is enabled.
+#[derive(Default)]
+pub struct MemoryFileSystem {
+    // Keyed by normalized path. The root directory is implicit.
+    nodes: RefCell<BTreeMap<String, Node>>,
+}
+
+impl MemoryFileSystem {
+    /// Creates an empty filesystem.
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl fmt::Debug for MemoryFileSystem {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_set().entries(self.nodes.borrow().keys()).finish()
+    }
+}
+
+/// Turns `path` into a list of components joined by `/`, without a leading
+/// slash, and with `.` and `..` resolved. The root is the empty string.
+fn normalize(path: &str) -> String {
+    let mut components: Vec<&str> = Vec::new();
+    for component in path.split('/') {
+        match component {
+            "" | "." => {}
+            ".." => {
+                components.pop();
+            }
+            c => components.push(c),
+        }
+    }
+    components.join("/")
+}
+
+fn parent(path: &str) -> &str {
+    path.rsplit_once('/').map_or("", |(parent, _)| parent)
+}
+
+/// Returns the prefix shared by every path inside `dir`.
+fn child_prefix(dir: &str) -> String {
+    if dir.is_empty() {
+        String::new()
+    } else {
+        let mut prefix = dir.to_string();
+        prefix.push('/');
+        prefix
+    }
+}
+
+fn is_dir(nodes: &BTreeMap<String, Node>, path: &str) -> Result<(), MemoryError> {
+    if path.is_empty() {
+        return Ok(());
+    }
+    match nodes.get(path) {
+        Some(Node::Dir) => Ok(()),
+        Some(Node::File(_)) => Err(MemoryError::NotADirectory),
+        None => Err(MemoryError::NotFound),
+    }
+}
+
+fn has_children(nodes: &BTreeMap<String, Node>, dir: &str) -> bool {
+    let prefix = child_prefix(dir);
+    nodes
+        .range(prefix.clone()..)
+        .next()
+        .map_or(false, |(k, _)| k.starts_with(&prefix))
+}
+
+impl FileSystem for MemoryFileSystem {
+    type Error = MemoryError;
+    type File = MemoryFile;
+    type DirEntry = MemoryDirEntry;
+    type ReadDir = MemoryReadDir;
+
+    fn open(&self, path: &str, options: &OpenOptions) -> Result<Self::File, Self::Error> {
+        let (read, write) = (options.is_read(), options.is_write());
+        if !read && !write {
+            return Err(MemoryError::InvalidOptions);
+        }
+        let creating = options.is_create() || options.is_create_new();
+        if (creating || options.is_truncate()) && !write {
+            return Err(MemoryError::InvalidOptions);
+        }
+
+        let path = normalize(path);
+        let mut nodes = self.nodes.borrow_mut();
+        let contents = match nodes.get(&path) {
+            _ if path.is_empty() => return Err(MemoryError::IsADirectory),
+            Some(Node::Dir) => return Err(MemoryError::IsADirectory),
+            Some(Node::File(_)) if options.is_create_new() => {
+                return Err(MemoryError::AlreadyExists)
+            }
+            Some(Node::File(contents)) => {
+                if options.is_truncate() {
+                    contents.borrow_mut().clear();
+                }
+                contents.clone()
+            }
+            None if creating => {
+                is_dir(&nodes, parent(&path))?;
+                let contents = Contents::default();
+                nodes.insert(path, Node::File(contents.clone()));
+                contents
+            }
+            None => return Err(MemoryError::NotFound),
+        };
+
+        Ok(MemoryFile {
+            contents,
+            pos: 0,
+            read,
+            write,
+            append: options.is_append(),
+        })
+    }
+
+    fn create_dir(&self, path: &str) -> Result<(), Self::Error> {
+        let path = normalize(path);
+        let mut nodes = self.nodes.borrow_mut();
+        if path.is_empty() || nodes.contains_key(&path) {
+            return Err(MemoryError::AlreadyExists);
+        }
+        is_dir(&nodes, parent(&path))?;
+        nodes.insert(path, Node::Dir);
+        Ok(())
+    }
+
+    fn remove_file(&self, path: &str) -> Result<(), Self::Error> {
+        let path = normalize(path);
+        let mut nodes = self.nodes.borrow_mut();
+        match nodes.get(&path) {
+            _ if path.is_empty() => Err(MemoryError::IsADirectory),
+            Some(Node::File(_)) => {
+                nodes.remove(&path);
+                Ok(())
+            }
+            Some(Node::Dir) => Err(MemoryError::IsADirectory),
+            None => Err(MemoryError::NotFound),
+        }
+    }
+
+    fn remove_dir(&self, path: &str) -> Result<(), Self::Error> {
+        let path = normalize(path);
+        let mut nodes = self.nodes.borrow_mut();
+        if path.is_empty() {
+            return Err(MemoryError::InvalidPath);
+        }
+        is_dir(&nodes, &path)?;
+        if has_children(&nodes, &path) {
+            return Err(MemoryError::DirectoryNotEmpty);
+        }
+        nodes.remove(&path);
+        Ok(())
+    }
+
+    fn rename(&self, from: &str, to: &str) -> Result<(), Self::Error> {
+        let (from, to) = (normalize(from), normalize(to));
+        let mut nodes = self.nodes.borrow_mut();
+        if from.is_empty() || to.is_empty() {
+            return Err(MemoryError::InvalidPath);
+        }
+        if from == to {
+            return nodes.get(&from).map(|_| ()).ok_or(MemoryError::NotFound);
+        }
+        let from_is_dir = match nodes.get(&from) {
+            Some(node) => matches!(node, Node::Dir),
+            None => return Err(MemoryError::NotFound),
+        };
+        is_dir(&nodes, parent(&to))?;
+
+        match nodes.get(&to) {
+            Some(Node::Dir) if !from_is_dir => return Err(MemoryError::IsADirectory),
+            Some(Node::File(_)) if from_is_dir => return Err(MemoryError::NotADirectory),
+            Some(Node::Dir) if has_children(&nodes, &to) => {
+                return Err(MemoryError::DirectoryNotEmpty)
+            }
+            _ => {}
+        }
+
+        if from_is_dir {
+            let from_prefix = child_prefix(&from);
+            if to.starts_with(&from_prefix) {
+                // Can't move a directory inside itself.
+                return Err(MemoryError::InvalidPath);
+            }
+            let to_prefix = child_prefix(&to);
+            let children: Vec<String> = nodes
+                .range(from_prefix.clone()..)
+                .map(|(k, _)| k)
+                .take_while(|k| k.starts_with(&from_prefix))
+                .cloned()
+                .collect();
+            for old in children {
+                let node = nodes.remove(&old).expect("child exists");
+                let mut new = to_prefix.clone();
+                new.push_str(&old[from_prefix.len()..]);
+                nodes.insert(new, node);
+            }
+        }
+
+        let node = nodes.remove(&from).expect("source exists");
+        nodes.insert(to, node);
+        Ok(())
+    }
+
+    fn metadata(&self, path: &str) -> Result<Metadata, Self::Error> {
+        let path = normalize(path);
+        let nodes = self.nodes.borrow();
+        match nodes.get(&path) {
+            _ if path.is_empty() => Ok(Metadata::new(FileType::Dir, 0)),
+            Some(Node::File(contents)) => Ok(Metadata::new(
+                FileType::File,
+                contents.borrow().len() as u64,
+            )),
+            Some(Node::Dir) => Ok(Metadata::new(FileType::Dir, 0)),
+            None => Err(MemoryError::NotFound),
+        }
+    }
+
+    fn read_dir(&self, path: &str) -> Result<Self::ReadDir, Self::Error> {
+        let path = normalize(path);
+        let nodes = self.nodes.borrow();
+        is_dir(&nodes, &path)?;
+
+        let prefix = child_prefix(&path);
+        let entries = nodes
+            .range(prefix.clone()..)
+            .take_while(|(k, _)| k.starts_with(&prefix))
+            .filter(|(k, _)| !k[prefix.len()..].contains('/'))
+            .map(|(k, node)| {
+                Ok(MemoryDirEntry {
+                    name: k[prefix.len()..].to_string(),
+                    file_type: match node {
+                        Node::File(_) => FileType::File,
+                        Node::Dir => FileType::Dir,
+                    },
+                })
+            })
+            .collect::<Vec<_>>();
+        Ok(MemoryReadDir(entries.into_iter()))
+    }
+}
+
+/// A handle to a file in a [`MemoryFileSystem`].
+///
+/// This type is available when the `alloc` feature is enabled.
+#[derive(Debug)]
+pub struct MemoryFile {
+    contents: Contents,
+    pos: u64,
+    read: bool,
+    write: bool,
+    append: bool,
+}
+
+impl Read for MemoryFile {
+    type Error = MemoryError;
+
+    fn read(&mut self, buf: &mut [u8]) -> Result<usize, Self::Error> {
+        if !self.read {
+            return Err(MemoryError::PermissionDenied);
+        }
+        let contents = self.contents.borrow();
+        let start = cmp::min(self.pos, contents.len() as u64) as usize;
+        let n = match (&contents[start..]).read(buf) {
+            Ok(n) => n,
+            Err(never) => match never {},
+        };
+        self.pos += n as u64;
+        Ok(n)
+    }
+}
+
+impl Write for MemoryFile {
+    type Error = MemoryError;
+
+    fn write(&mut self, buf: &[u8]) -> Result<usize, Self::Error> {
+        if !self.write {
+            return Err(MemoryError::PermissionDenied);
+        }
+        let mut contents = self.contents.borrow_mut();
+        if self.append {
+            self.pos = contents.len() as u64;
+        }
+        // An empty write past the end mustn't extend the file.
+        if buf.is_empty() {
+            return Ok(0);
+        }
+        let start = usize::try_from(self.pos).map_err(|_| MemoryError::FileTooLarge)?;
+        let end = start
+            .checked_add(buf.len())
+            .ok_or(MemoryError::FileTooLarge)?;
+        if contents.len() < end {
+            contents.resize(end, 0);
+        }
+        contents[start..end].copy_from_slice(buf);
+        self.pos = end as u64;
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> Result<(), Self::Error> {
+        Ok(())
+    }
+}
+
+impl Seek for MemoryFile {
+    type Error = MemoryError;
+
+    fn seek(&mut self, pos: SeekFrom) -> Result<u64, Self::Error> {
+        let (base, offset) = match pos {
+            SeekFrom::Start(n) => {
+                self.pos = n;
+                return Ok(n);
+            }
+            SeekFrom::End(n) => (self.contents.borrow().len() as u64, n),
+            SeekFrom::Current(n) => (self.pos, n),
+        };
+        let new = if offset >= 0 {
+            base.checked_add(offset as u64)
+        } else {
+            base.checked_sub(offset.unsigned_abs())
+        };
+        self.pos = new.ok_or(MemoryError::InvalidSeek)?;
+        Ok(self.pos)
+    }
+}
+
+/// An entry returned by [`MemoryFileSystem::read_dir`](FileSystem::read_dir).
+///
+/// This type is available when the `alloc` feature is enabled.
+#[derive(Clone, Debug)]
+pub struct MemoryDirEntry {
+    name: String,
+    file_type: FileType,
+}
+
+impl DirEntry for MemoryDirEntry {
+    fn file_name(&self) -> &str {
+        &self.name
+    }
+
+    fn file_type(&self) -> FileType {
+        self.file_type
+    }
+}
+
+/// The iterator returned by [`MemoryFileSystem::read_dir`](FileSystem::read_dir).
+///
+/// This type is available when the `alloc` feature is enabled.
+#[derive(Debug)]
+pub struct MemoryReadDir(alloc::vec::IntoIter<Result<MemoryDirEntry, MemoryError>>);
+
+impl Iterator for MemoryReadDir {
+    type Item = Result<MemoryDirEntry, MemoryError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.0.next()
+    }
+}
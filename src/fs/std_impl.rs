@@ -0,0 +1,124 @@
+use crate::{
+    fs::{DirEntry, FileSystem, FileType, Metadata, OpenOptions},
+    io::CoreIO,
+};
+
+/// Zero-sized struct that implements [`FileSystem`] with [`std::fs`].
+///
+/// This type is available when the `std` feature is enabled.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct StdFileSystem;
+
+/// An entry returned by [`StdFileSystem::read_dir`](FileSystem::read_dir).
+///
+/// This type is available when the `std` feature is enabled.
+#[derive(Clone, Debug)]
+pub struct StdDirEntry {
+    name: String,
+    file_type: FileType,
+}
+
+impl DirEntry for StdDirEntry {
+    fn file_name(&self) -> &str {
+        &self.name
+    }
+
+    fn file_type(&self) -> FileType {
+        self.file_type
+    }
+}
+
+/// The iterator returned by [`StdFileSystem::read_dir`](FileSystem::read_dir).
+///
+/// This type is available when the `std` feature is enabled.
+#[derive(Debug)]
+pub struct StdReadDir(std::fs::ReadDir);
+
+impl Iterator for StdReadDir {
+    type Item = Result<StdDirEntry, std::io::Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.0.next().map(|entry| {
+            let entry = entry?;
+            let name = entry.file_name().into_string().map_err(|_| {
+                std::io::Error::new(
+                    std::io::ErrorKind::InvalidData,
+                    "file name is not valid UTF-8",
+                )
+            })?;
+            Ok(StdDirEntry {
+                name,
+                file_type: entry.file_type()?.into(),
+            })
+        })
+    }
+}
+
+impl From<std::fs::FileType> for FileType {
+    fn from(file_type: std::fs::FileType) -> Self {
+        if file_type.is_file() {
+            FileType::File
+        } else if file_type.is_dir() {
+            FileType::Dir
+        } else if file_type.is_symlink() {
+            FileType::Symlink
+        } else {
+            FileType::Other
+        }
+    }
+}
+
+impl From<std::fs::Metadata> for Metadata {
+    fn from(metadata: std::fs::Metadata) -> Self {
+        Metadata::new(metadata.file_type().into(), metadata.len())
+    }
+}
+
+impl From<&OpenOptions> for std::fs::OpenOptions {
+    fn from(options: &OpenOptions) -> Self {
+        let mut std_options = std::fs::OpenOptions::new();
+        std_options
+            .read(options.is_read())
+            .write(options.is_write())
+            .append(options.is_append())
+            .truncate(options.is_truncate())
+            .create(options.is_create())
+            .create_new(options.is_create_new());
+        std_options
+    }
+}
+
+impl FileSystem for StdFileSystem {
+    type Error = std::io::Error;
+    type File = CoreIO<std::fs::File>;
+    type DirEntry = StdDirEntry;
+    type ReadDir = StdReadDir;
+
+    fn open(&self, path: &str, options: &OpenOptions) -> Result<Self::File, Self::Error> {
+        std::fs::OpenOptions::from(options).open(path).map(CoreIO)
+    }
+
+    fn create_dir(&self, path: &str) -> Result<(), Self::Error> {
+        std::fs::create_dir(path)
+    }
+
+    fn remove_file(&self, path: &str) -> Result<(), Self::Error> {
+        std::fs::remove_file(path)
+    }
+
+    fn remove_dir(&self, path: &str) -> Result<(), Self::Error> {
+        std::fs::remove_dir(path)
+    }
+
+    fn rename(&self, from: &str, to: &str) -> Result<(), Self::Error> {
+        std::fs::rename(from, to)
+    }
+
+    fn metadata(&self, path: &str) -> Result<Metadata, Self::Error> {
+        std::fs::metadata(path).map(Into::into)
+    }
+
+    fn read_dir(&self, path: &str) -> Result<Self::ReadDir, Self::Error> {
+        std::fs::read_dir(path).map(StdReadDir)
+    }
+}
@@ -8,11 +8,27 @@ use core::{
     task::{Context, Poll},
 };
 
+pub mod checksum;
+mod counting;
+pub mod encoding;
+mod peekable;
+mod positional;
+mod read_buf;
+mod split;
 #[cfg(feature = "std")]
 mod std_impl;
-
+mod throttle;
+mod util;
+
+pub use self::counting::{Counting, Progress};
+pub use self::peekable::{Peekable, UnreadError};
+pub use self::positional::*;
+pub use self::read_buf::ReadBuf;
+pub use self::split::*;
 #[cfg(feature = "std")]
 pub use self::std_impl::*;
+pub use self::throttle::Throttle;
+pub use self::util::*;
 
 /// Read bytes asynchronously.
 pub trait AsyncRead {
@@ -23,6 +39,27 @@ pub trait AsyncRead {
         cx: &mut Context<'_>,
         buf: &mut [u8],
     ) -> Poll<Result<usize, Self::Error>>;
+
+    /// Reads into a [`ReadBuf`], which may not be initialized.
+    ///
+    /// The default implementation initializes the unfilled part of `buf`
+    /// (only the first time, since `ReadBuf` keeps track) and calls
+    /// [`poll_read`](AsyncRead::poll_read). Override it if the reader can
+    /// write into uninitialized memory.
+    fn poll_read_buf(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut ReadBuf<'_>,
+    ) -> Poll<Result<(), Self::Error>> {
+        match self.poll_read(cx, buf.initialize_unfilled()) {
+            Poll::Ready(Ok(n)) => {
+                buf.advance(n);
+                Poll::Ready(Ok(()))
+            }
+            Poll::Ready(Err(e)) => Poll::Ready(Err(e)),
+            Poll::Pending => Poll::Pending,
+        }
+    }
 }
 
 /// Write bytes asynchronously.
@@ -48,6 +85,18 @@ pub trait Read {
     type Error;
 
     fn read(&mut self, buf: &mut [u8]) -> Result<usize, Self::Error>;
+
+    /// Reads into a [`ReadBuf`], which may not be initialized.
+    ///
+    /// The default implementation initializes the unfilled part of `buf`
+    /// (only the first time, since `ReadBuf` keeps track) and calls
+    /// [`read`](Read::read). Override it if the reader can write into
+    /// uninitialized memory.
+    fn read_buf(&mut self, buf: &mut ReadBuf<'_>) -> Result<(), Self::Error> {
+        let n = self.read(buf.initialize_unfilled())?;
+        buf.advance(n);
+        Ok(())
+    }
 }
 
 /// Write bytes.
@@ -110,6 +159,48 @@ pub trait Write {
     }
 }
 
+/// Enumeration of possible methods to seek within an I/O object.
+///
+/// It is used by the [`Seek`] trait.
+#[derive(Copy, PartialEq, Eq, Clone, Debug)]
+pub enum SeekFrom {
+    /// Sets the offset to the provided number of bytes.
+    Start(u64),
+
+    /// Sets the offset to the size of this object plus the specified number of
+    /// bytes.
+    ///
+    /// It is possible to seek beyond the end of an object, but it's an error to
+    /// seek before byte 0.
+    End(i64),
+
+    /// Sets the offset to the current position plus the specified number of
+    /// bytes.
+    ///
+    /// It is possible to seek beyond the end of an object, but it's an error to
+    /// seek before byte 0.
+    Current(i64),
+}
+
+/// Seek within a stream of bytes.
+///
+/// When the `std` feature is enabled (by default), this trait is automatically
+/// implemented for [`CoreIO`] wrapping any type that implements [`std::io::Seek`].
+pub trait Seek {
+    type Error;
+
+    /// Seek to an offset, in bytes, in a stream.
+    ///
+    /// If the seek operation completed successfully, this method returns the
+    /// new position from the start of the stream.
+    fn seek(&mut self, pos: SeekFrom) -> Result<u64, Self::Error>;
+
+    /// Returns the current seek position from the start of the stream.
+    fn stream_position(&mut self) -> Result<u64, Self::Error> {
+        self.seek(SeekFrom::Current(0))
+    }
+}
+
 impl Read for &[u8] {
     type Error = Infallible;
 
@@ -129,6 +220,14 @@ impl Read for &[u8] {
         *self = b;
         Ok(amt)
     }
+
+    fn read_buf(&mut self, buf: &mut ReadBuf<'_>) -> Result<(), Self::Error> {
+        let amt = cmp::min(buf.remaining(), self.len());
+        let (a, b) = self.split_at(amt);
+        buf.put_slice(a);
+        *self = b;
+        Ok(())
+    }
 }
 
 impl Write for &mut [u8] {
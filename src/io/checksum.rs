@@ -0,0 +1,510 @@
+//! Running checksums over streamed data.
+//!
+//! [`ChecksumReader`] and [`ChecksumWriter`] wrap a [`Read`] or [`Write`] and
+//! feed every byte that passes through them into a [`Checksum`].
+//!
+//! # Examples
+//!
+//! ```
+//! use coreplus::io::{Read, checksum::{Checksum, ChecksumReader, Crc32}};
+//!
+//! let mut reader = ChecksumReader::new(&b"123456789"[..], Crc32::new());
+//! let mut buf = [0; 4];
+//! while reader.read(&mut buf).unwrap() != 0 {}
+//!
+//! assert_eq!(reader.checksum().finish(), 0xcbf4_3926);
+//! ```
+
+use crate::io::{Read, Write};
+
+/// A checksum or hash that can be updated incrementally.
+pub trait Checksum {
+    /// The type of the finished checksum value.
+    type Output;
+
+    /// Feeds `bytes` into the checksum.
+    fn update(&mut self, bytes: &[u8]);
+
+    /// Returns the checksum of all bytes fed in so far.
+    ///
+    /// This does not reset the state, so more bytes can be fed in afterwards.
+    fn finish(&self) -> Self::Output;
+
+    /// Resets the checksum to its initial state.
+    fn reset(&mut self);
+}
+
+impl<C: Checksum + ?Sized> Checksum for &mut C {
+    type Output = C::Output;
+
+    fn update(&mut self, bytes: &[u8]) {
+        (**self).update(bytes)
+    }
+
+    fn finish(&self) -> Self::Output {
+        (**self).finish()
+    }
+
+    fn reset(&mut self) {
+        (**self).reset()
+    }
+}
+
+const fn crc32_table(poly: u32) -> [u32; 256] {
+    let mut table = [0; 256];
+    let mut i = 0;
+    while i < 256 {
+        let mut crc = i as u32;
+        let mut bit = 0;
+        while bit < 8 {
+            crc = if crc & 1 != 0 { (crc >> 1) ^ poly } else { crc >> 1 };
+            bit += 1;
+        }
+        table[i] = crc;
+        i += 1;
+    }
+    table
+}
+
+/// Builds a table for a CRC-16 that processes the least significant bit first.
+///
+/// `poly` must already be bit-reversed.
+const fn crc16_table_reflected(poly: u16) -> [u16; 256] {
+    let mut table = [0; 256];
+    let mut i = 0;
+    while i < 256 {
+        let mut crc = i as u16;
+        let mut bit = 0;
+        while bit < 8 {
+            crc = if crc & 1 != 0 { (crc >> 1) ^ poly } else { crc >> 1 };
+            bit += 1;
+        }
+        table[i] = crc;
+        i += 1;
+    }
+    table
+}
+
+/// Builds a table for a CRC-16 that processes the most significant bit first.
+const fn crc16_table(poly: u16) -> [u16; 256] {
+    let mut table = [0; 256];
+    let mut i = 0;
+    while i < 256 {
+        let mut crc = (i as u16) << 8;
+        let mut bit = 0;
+        while bit < 8 {
+            crc = if crc & 0x8000 != 0 {
+                (crc << 1) ^ poly
+            } else {
+                crc << 1
+            };
+            bit += 1;
+        }
+        table[i] = crc;
+        i += 1;
+    }
+    table
+}
+
+static CRC32_TABLE: [u32; 256] = crc32_table(0xedb8_8320);
+// The CRC-16 tables are consts rather than statics so the `const fn`
+// constructors can borrow them; `&TABLE` is promoted to a `'static` borrow.
+const CRC16_8005_REFLECTED_TABLE: [u16; 256] = crc16_table_reflected(0xa001);
+const CRC16_1021_REFLECTED_TABLE: [u16; 256] = crc16_table_reflected(0x8408);
+const CRC16_1021_TABLE: [u16; 256] = crc16_table(0x1021);
+
+/// The CRC-32 used by Ethernet, zlib, gzip and PNG (CRC-32/ISO-HDLC).
+#[derive(Clone, Copy, Debug)]
+pub struct Crc32 {
+    state: u32,
+}
+
+impl Crc32 {
+    /// Creates a new CRC-32 in its initial state.
+    pub const fn new() -> Self {
+        Self { state: !0 }
+    }
+}
+
+impl Default for Crc32 {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Checksum for Crc32 {
+    type Output = u32;
+
+    fn update(&mut self, bytes: &[u8]) {
+        let mut crc = self.state;
+        for &b in bytes {
+            crc = CRC32_TABLE[((crc ^ b as u32) & 0xff) as usize] ^ (crc >> 8);
+        }
+        self.state = crc;
+    }
+
+    fn finish(&self) -> u32 {
+        !self.state
+    }
+
+    fn reset(&mut self) {
+        *self = Self::new();
+    }
+}
+
+/// A CRC-16.
+///
+/// There are many CRC-16 variants in common use; pick one with the matching
+/// constructor.
+///
+/// # Examples
+///
+/// ```
+/// use coreplus::io::checksum::{Checksum, Crc16};
+///
+/// let mut crc = Crc16::modbus();
+/// crc.update(b"123456789");
+/// assert_eq!(crc.finish(), 0x4b37);
+/// ```
+#[derive(Clone, Copy)]
+pub struct Crc16 {
+    table: &'static [u16; 256],
+    reflected: bool,
+    init: u16,
+    xorout: u16,
+    state: u16,
+}
+
+impl Crc16 {
+    const fn new(table: &'static [u16; 256], reflected: bool, init: u16, xorout: u16) -> Self {
+        Self {
+            table,
+            reflected,
+            init,
+            xorout,
+            state: init,
+        }
+    }
+
+    /// CRC-16/ARC, also known as CRC-16/IBM or plain "CRC-16".
+    pub const fn arc() -> Self {
+        Self::new(&CRC16_8005_REFLECTED_TABLE, true, 0x0000, 0x0000)
+    }
+
+    /// CRC-16/MODBUS.
+    pub const fn modbus() -> Self {
+        Self::new(&CRC16_8005_REFLECTED_TABLE, true, 0xffff, 0x0000)
+    }
+
+    /// CRC-16/IBM-3740, commonly (and incorrectly) called CRC-16/CCITT-FALSE.
+    pub const fn ccitt_false() -> Self {
+        Self::new(&CRC16_1021_TABLE, false, 0xffff, 0x0000)
+    }
+
+    /// CRC-16/XMODEM.
+    pub const fn xmodem() -> Self {
+        Self::new(&CRC16_1021_TABLE, false, 0x0000, 0x0000)
+    }
+
+    /// CRC-16/KERMIT, also known as CRC-16/CCITT.
+    pub const fn kermit() -> Self {
+        Self::new(&CRC16_1021_REFLECTED_TABLE, true, 0x0000, 0x0000)
+    }
+
+    /// CRC-16/X-25, used by HDLC and PPP.
+    pub const fn x25() -> Self {
+        Self::new(&CRC16_1021_REFLECTED_TABLE, true, 0xffff, 0xffff)
+    }
+}
+
+impl core::fmt::Debug for Crc16 {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("Crc16")
+            .field("reflected", &self.reflected)
+            .field("init", &self.init)
+            .field("xorout", &self.xorout)
+            .field("state", &self.state)
+            .finish()
+    }
+}
+
+impl Checksum for Crc16 {
+    type Output = u16;
+
+    fn update(&mut self, bytes: &[u8]) {
+        let mut crc = self.state;
+        if self.reflected {
+            for &b in bytes {
+                crc = self.table[((crc ^ b as u16) & 0xff) as usize] ^ (crc >> 8);
+            }
+        } else {
+            for &b in bytes {
+                crc = self.table[((crc >> 8) ^ b as u16) as usize] ^ (crc << 8);
+            }
+        }
+        self.state = crc;
+    }
+
+    fn finish(&self) -> u16 {
+        self.state ^ self.xorout
+    }
+
+    fn reset(&mut self) {
+        self.state = self.init;
+    }
+}
+
+/// The Adler-32 checksum used by zlib.
+///
+/// # Examples
+///
+/// ```
+/// use coreplus::io::checksum::{Adler32, Checksum};
+///
+/// let mut adler = Adler32::new();
+/// adler.update(b"Wiki");
+/// adler.update(b"pedia");
+/// assert_eq!(adler.finish(), 0x11e6_0398);
+/// ```
+#[derive(Clone, Copy, Debug)]
+pub struct Adler32 {
+    a: u32,
+    b: u32,
+}
+
+impl Adler32 {
+    const MOD: u32 = 65521;
+    /// The largest number of bytes that can be summed before `b` may overflow.
+    const NMAX: usize = 5552;
+
+    /// Creates a new Adler-32 in its initial state.
+    pub const fn new() -> Self {
+        Self { a: 1, b: 0 }
+    }
+}
+
+impl Default for Adler32 {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Checksum for Adler32 {
+    type Output = u32;
+
+    fn update(&mut self, bytes: &[u8]) {
+        for chunk in bytes.chunks(Self::NMAX) {
+            for &byte in chunk {
+                self.a += byte as u32;
+                self.b += self.a;
+            }
+            self.a %= Self::MOD;
+            self.b %= Self::MOD;
+        }
+    }
+
+    fn finish(&self) -> u32 {
+        (self.b << 16) | self.a
+    }
+
+    fn reset(&mut self) {
+        *self = Self::new();
+    }
+}
+
+/// The ones'-complement sum used by IPv4, TCP, UDP and ICMP headers ([RFC 1071]).
+///
+/// Bytes are summed as big-endian 16-bit words. A word may be split across
+/// calls to [`update`](Checksum::update).
+///
+/// [RFC 1071]: https://tools.ietf.org/html/rfc1071
+///
+/// # Examples
+///
+/// ```
+/// use coreplus::io::checksum::{Checksum, InternetChecksum};
+///
+/// let mut sum = InternetChecksum::new();
+/// sum.update(&[0x00, 0x01, 0xf2]);
+/// sum.update(&[0x03, 0xf4, 0xf5, 0xf6, 0xf7]);
+/// assert_eq!(sum.finish(), !0xddf2);
+///
+/// // Long inputs can't overflow the sum, even with an odd byte left over.
+/// let mut sum = InternetChecksum::new();
+/// sum.update(&[0xff; 131_075]);
+/// assert_eq!(sum.finish(), 0x00ff);
+/// ```
+#[derive(Clone, Copy, Debug, Default)]
+pub struct InternetChecksum {
+    sum: u32,
+    odd: Option<u8>,
+}
+
+impl InternetChecksum {
+    /// Creates a new checksum in its initial state.
+    pub const fn new() -> Self {
+        Self { sum: 0, odd: None }
+    }
+
+    fn add_word(&mut self, word: u16) {
+        self.sum = add_carry(self.sum, word);
+    }
+}
+
+/// Adds `word` to `sum`, folding the carry back in immediately so the sum
+/// never overflows.
+fn add_carry(sum: u32, word: u16) -> u32 {
+    let (sum, carry) = sum.overflowing_add(word as u32);
+    sum + carry as u32
+}
+
+impl Checksum for InternetChecksum {
+    type Output = u16;
+
+    fn update(&mut self, mut bytes: &[u8]) {
+        if let Some(hi) = self.odd.take() {
+            match bytes.split_first() {
+                Some((&lo, rest)) => {
+                    self.add_word(u16::from_be_bytes([hi, lo]));
+                    bytes = rest;
+                }
+                None => {
+                    self.odd = Some(hi);
+                    return;
+                }
+            }
+        }
+
+        let mut words = bytes.chunks_exact(2);
+        for word in &mut words {
+            self.add_word(u16::from_be_bytes([word[0], word[1]]));
+        }
+        if let [last] = *words.remainder() {
+            self.odd = Some(last);
+        }
+    }
+
+    fn finish(&self) -> u16 {
+        let mut sum = self.sum;
+        if let Some(hi) = self.odd {
+            // An odd byte is padded with a zero to make the last word.
+            sum = add_carry(sum, u16::from_be_bytes([hi, 0]));
+        }
+        while sum >> 16 != 0 {
+            sum = (sum & 0xffff) + (sum >> 16);
+        }
+        !(sum as u16)
+    }
+
+    fn reset(&mut self) {
+        *self = Self::new();
+    }
+}
+
+/// A reader that updates a [`Checksum`] with every byte read through it.
+#[derive(Debug)]
+pub struct ChecksumReader<R, C> {
+    inner: R,
+    checksum: C,
+}
+
+impl<R, C> ChecksumReader<R, C> {
+    /// Wraps `inner`, feeding everything read from it into `checksum`.
+    pub fn new(inner: R, checksum: C) -> Self {
+        Self { inner, checksum }
+    }
+
+    /// Returns a reference to the checksum.
+    pub fn checksum(&self) -> &C {
+        &self.checksum
+    }
+
+    /// Returns a mutable reference to the checksum.
+    pub fn checksum_mut(&mut self) -> &mut C {
+        &mut self.checksum
+    }
+
+    /// Gets a reference to the underlying reader.
+    pub fn get_ref(&self) -> &R {
+        &self.inner
+    }
+
+    /// Gets a mutable reference to the underlying reader.
+    ///
+    /// Bytes read directly from the underlying reader are not checksummed.
+    pub fn get_mut(&mut self) -> &mut R {
+        &mut self.inner
+    }
+
+    /// Unwraps this `ChecksumReader`, returning the underlying reader and the checksum.
+    pub fn into_parts(self) -> (R, C) {
+        (self.inner, self.checksum)
+    }
+}
+
+impl<R: Read, C: Checksum> Read for ChecksumReader<R, C> {
+    type Error = R::Error;
+
+    fn read(&mut self, buf: &mut [u8]) -> Result<usize, Self::Error> {
+        let n = self.inner.read(buf)?;
+        self.checksum.update(&buf[..n]);
+        Ok(n)
+    }
+}
+
+/// A writer that updates a [`Checksum`] with every byte written through it.
+///
+/// Only the bytes the underlying writer reports as written are checksummed.
+#[derive(Debug)]
+pub struct ChecksumWriter<W, C> {
+    inner: W,
+    checksum: C,
+}
+
+impl<W, C> ChecksumWriter<W, C> {
+    /// Wraps `inner`, feeding everything written to it into `checksum`.
+    pub fn new(inner: W, checksum: C) -> Self {
+        Self { inner, checksum }
+    }
+
+    /// Returns a reference to the checksum.
+    pub fn checksum(&self) -> &C {
+        &self.checksum
+    }
+
+    /// Returns a mutable reference to the checksum.
+    pub fn checksum_mut(&mut self) -> &mut C {
+        &mut self.checksum
+    }
+
+    /// Gets a reference to the underlying writer.
+    pub fn get_ref(&self) -> &W {
+        &self.inner
+    }
+
+    /// Gets a mutable reference to the underlying writer.
+    ///
+    /// Bytes written directly to the underlying writer are not checksummed.
+    pub fn get_mut(&mut self) -> &mut W {
+        &mut self.inner
+    }
+
+    /// Unwraps this `ChecksumWriter`, returning the underlying writer and the checksum.
+    pub fn into_parts(self) -> (W, C) {
+        (self.inner, self.checksum)
+    }
+}
+
+impl<W: Write, C: Checksum> Write for ChecksumWriter<W, C> {
+    type Error = W::Error;
+
+    fn write(&mut self, buf: &[u8]) -> Result<usize, Self::Error> {
+        let n = self.inner.write(buf)?;
+        self.checksum.update(&buf[..n]);
+        Ok(n)
+    }
+
+    fn flush(&mut self) -> Result<(), Self::Error> {
+        self.inner.flush()
+    }
+}
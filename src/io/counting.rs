@@ -0,0 +1,348 @@
+use core::{
+    pin::Pin,
+    task::{Context, Poll},
+};
+
+use crate::io::{AsyncRead, AsyncWrite, Read, ReadBuf, Write};
+
+/// Counts the bytes read from and written to the wrapped object.
+///
+/// # Examples
+///
+/// ```
+/// use coreplus::io::{Counting, Read};
+///
+/// let mut reader = Counting::new(&b"hello world"[..]);
+/// let mut buf = [0; 5];
+/// reader.read(&mut buf).unwrap();
+///
+/// assert_eq!(reader.bytes_read(), 5);
+/// assert_eq!(reader.bytes_written(), 0);
+/// ```
+#[derive(Debug, Default)]
+pub struct Counting<S> {
+    inner: S,
+    read: u64,
+    written: u64,
+}
+
+impl<S> Counting<S> {
+    /// Wraps `inner` with both counters at zero.
+    pub fn new(inner: S) -> Self {
+        Self {
+            inner,
+            read: 0,
+            written: 0,
+        }
+    }
+
+    /// Returns the number of bytes read so far.
+    pub fn bytes_read(&self) -> u64 {
+        self.read
+    }
+
+    /// Returns the number of bytes written so far.
+    pub fn bytes_written(&self) -> u64 {
+        self.written
+    }
+
+    /// Resets both counters to zero.
+    pub fn reset(&mut self) {
+        self.read = 0;
+        self.written = 0;
+    }
+
+    /// Gets a reference to the underlying object.
+    pub fn get_ref(&self) -> &S {
+        &self.inner
+    }
+
+    /// Gets a mutable reference to the underlying object.
+    pub fn get_mut(&mut self) -> &mut S {
+        &mut self.inner
+    }
+
+    /// Gets a pinned mutable reference to the underlying object.
+    pub fn get_pin_mut(self: Pin<&mut Self>) -> Pin<&mut S> {
+        self.project().0
+    }
+
+    /// Unwraps this `Counting`, returning the underlying object.
+    pub fn into_inner(self) -> S {
+        self.inner
+    }
+
+    fn project(self: Pin<&mut Self>) -> (Pin<&mut S>, &mut u64, &mut u64) {
+        // SAFETY: `inner` is structurally pinned and never moved out of a
+        // pinned `Counting`. The counters are not pinned.
+        unsafe {
+            let this = self.get_unchecked_mut();
+            (
+                Pin::new_unchecked(&mut this.inner),
+                &mut this.read,
+                &mut this.written,
+            )
+        }
+    }
+}
+
+impl<S: Read> Read for Counting<S> {
+    type Error = S::Error;
+
+    fn read(&mut self, buf: &mut [u8]) -> Result<usize, Self::Error> {
+        let n = self.inner.read(buf)?;
+        self.read += n as u64;
+        Ok(n)
+    }
+
+    fn read_buf(&mut self, buf: &mut ReadBuf<'_>) -> Result<(), Self::Error> {
+        let before = buf.filled().len();
+        self.inner.read_buf(buf)?;
+        self.read += (buf.filled().len() - before) as u64;
+        Ok(())
+    }
+}
+
+impl<S: Write> Write for Counting<S> {
+    type Error = S::Error;
+
+    fn write(&mut self, buf: &[u8]) -> Result<usize, Self::Error> {
+        let n = self.inner.write(buf)?;
+        self.written += n as u64;
+        Ok(n)
+    }
+
+    fn flush(&mut self) -> Result<(), Self::Error> {
+        self.inner.flush()
+    }
+}
+
+impl<S: AsyncRead> AsyncRead for Counting<S> {
+    type Error = S::Error;
+
+    fn poll_read(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut [u8],
+    ) -> Poll<Result<usize, Self::Error>> {
+        let (inner, read, _) = self.project();
+        let poll = inner.poll_read(cx, buf);
+        if let Poll::Ready(Ok(n)) = poll {
+            *read += n as u64;
+        }
+        poll
+    }
+
+    fn poll_read_buf(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut ReadBuf<'_>,
+    ) -> Poll<Result<(), Self::Error>> {
+        let (inner, read, _) = self.project();
+        let before = buf.filled().len();
+        let poll = inner.poll_read_buf(cx, buf);
+        if let Poll::Ready(Ok(())) = poll {
+            *read += (buf.filled().len() - before) as u64;
+        }
+        poll
+    }
+}
+
+impl<S: AsyncWrite> AsyncWrite for Counting<S> {
+    type Error = S::Error;
+
+    fn poll_write(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<Result<usize, Self::Error>> {
+        let (inner, _, written) = self.project();
+        let poll = inner.poll_write(cx, buf);
+        if let Poll::Ready(Ok(n)) = poll {
+            *written += n as u64;
+        }
+        poll
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.project().0.poll_flush(cx)
+    }
+
+    fn poll_close(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.project().0.poll_close(cx)
+    }
+}
+
+/// Calls a function with the running total every time bytes pass through the
+/// wrapped object.
+///
+/// Reads and writes are added to the same total, so wrap each direction
+/// separately if both need reporting.
+///
+/// # Examples
+///
+/// ```
+/// use coreplus::io::{Progress, Write};
+///
+/// let mut buf = [0; 16];
+/// let mut last = 0;
+/// let mut writer = Progress::new(&mut buf[..], |total| last = total);
+/// writer.write_all(b"hello").unwrap();
+/// writer.write_all(b" world").unwrap();
+/// drop(writer);
+///
+/// assert_eq!(last, 11);
+/// ```
+pub struct Progress<S, F> {
+    inner: S,
+    callback: F,
+    total: u64,
+}
+
+impl<S, F: FnMut(u64)> Progress<S, F> {
+    /// Wraps `inner`, calling `callback` with the total number of bytes
+    /// transferred after every read or write that transfers at least one byte.
+    pub fn new(inner: S, callback: F) -> Self {
+        Self {
+            inner,
+            callback,
+            total: 0,
+        }
+    }
+
+    /// Returns the number of bytes transferred so far.
+    pub fn total(&self) -> u64 {
+        self.total
+    }
+
+    /// Gets a reference to the underlying object.
+    pub fn get_ref(&self) -> &S {
+        &self.inner
+    }
+
+    /// Gets a mutable reference to the underlying object.
+    pub fn get_mut(&mut self) -> &mut S {
+        &mut self.inner
+    }
+
+    /// Gets a pinned mutable reference to the underlying object.
+    pub fn get_pin_mut(self: Pin<&mut Self>) -> Pin<&mut S> {
+        self.project().0
+    }
+
+    /// Unwraps this `Progress`, returning the underlying object.
+    pub fn into_inner(self) -> S {
+        self.inner
+    }
+
+    fn project(self: Pin<&mut Self>) -> (Pin<&mut S>, &mut F, &mut u64) {
+        // SAFETY: `inner` is structurally pinned and never moved out of a
+        // pinned `Progress`. The callback and total are not pinned.
+        unsafe {
+            let this = self.get_unchecked_mut();
+            (
+                Pin::new_unchecked(&mut this.inner),
+                &mut this.callback,
+                &mut this.total,
+            )
+        }
+    }
+}
+
+fn advance<F: FnMut(u64)>(callback: &mut F, total: &mut u64, n: usize) {
+    if n != 0 {
+        *total += n as u64;
+        callback(*total);
+    }
+}
+
+impl<S: Read, F: FnMut(u64)> Read for Progress<S, F> {
+    type Error = S::Error;
+
+    fn read(&mut self, buf: &mut [u8]) -> Result<usize, Self::Error> {
+        let n = self.inner.read(buf)?;
+        advance(&mut self.callback, &mut self.total, n);
+        Ok(n)
+    }
+
+    fn read_buf(&mut self, buf: &mut ReadBuf<'_>) -> Result<(), Self::Error> {
+        let before = buf.filled().len();
+        self.inner.read_buf(buf)?;
+        advance(
+            &mut self.callback,
+            &mut self.total,
+            buf.filled().len() - before,
+        );
+        Ok(())
+    }
+}
+
+impl<S: Write, F: FnMut(u64)> Write for Progress<S, F> {
+    type Error = S::Error;
+
+    fn write(&mut self, buf: &[u8]) -> Result<usize, Self::Error> {
+        let n = self.inner.write(buf)?;
+        advance(&mut self.callback, &mut self.total, n);
+        Ok(n)
+    }
+
+    fn flush(&mut self) -> Result<(), Self::Error> {
+        self.inner.flush()
+    }
+}
+
+impl<S: AsyncRead, F: FnMut(u64)> AsyncRead for Progress<S, F> {
+    type Error = S::Error;
+
+    fn poll_read(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut [u8],
+    ) -> Poll<Result<usize, Self::Error>> {
+        let (inner, callback, total) = self.project();
+        let poll = inner.poll_read(cx, buf);
+        if let Poll::Ready(Ok(n)) = poll {
+            advance(callback, total, n);
+        }
+        poll
+    }
+
+    fn poll_read_buf(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut ReadBuf<'_>,
+    ) -> Poll<Result<(), Self::Error>> {
+        let (inner, callback, total) = self.project();
+        let before = buf.filled().len();
+        let poll = inner.poll_read_buf(cx, buf);
+        if let Poll::Ready(Ok(())) = poll {
+            advance(callback, total, buf.filled().len() - before);
+        }
+        poll
+    }
+}
+
+impl<S: AsyncWrite, F: FnMut(u64)> AsyncWrite for Progress<S, F> {
+    type Error = S::Error;
+
+    fn poll_write(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<Result<usize, Self::Error>> {
+        let (inner, callback, total) = self.project();
+        let poll = inner.poll_write(cx, buf);
+        if let Poll::Ready(Ok(n)) = poll {
+            advance(callback, total, n);
+        }
+        poll
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.project().0.poll_flush(cx)
+    }
+
+    fn poll_close(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.project().0.poll_close(cx)
+    }
+}
@@ -0,0 +1,510 @@
+//! Streaming text encodings of binary data.
+//!
+//! The encoders wrap a [`Write`] and the decoders wrap a [`Read`]; both also
+//! work with [`AsyncWrite`] and [`AsyncRead`]. Input may be split across calls
+//! however you like.
+//!
+//! Some encodings can't be completed until the end of the data is known, so
+//! call [`finish`](Base64Encoder::finish) (or close an async encoder) when you're
+//! done writing. Flushing writes out everything that can be written so far.
+//!
+//! # Examples
+//!
+//! ```
+//! use coreplus::io::{Read, Write};
+//! use coreplus::io::encoding::{Base64Config, Base64Decoder, Base64Encoder};
+//!
+//! let mut buf = [0; 16];
+//! let mut encoder = Base64Encoder::new(&mut buf[..], Base64Config::STANDARD);
+//! encoder.write_all(b"hel").unwrap();
+//! encoder.write_all(b"lo").unwrap();
+//! encoder.finish().unwrap();
+//! assert_eq!(&buf[..8], b"aGVsbG8=");
+//!
+//! let mut decoder = Base64Decoder::new(&buf[..8], Base64Config::STANDARD);
+//! let mut decoded = [0; 8];
+//! let n = decoder.read(&mut decoded).unwrap();
+//! assert_eq!(&decoded[..n], b"hello");
+//! ```
+
+use core::{
+    cmp, fmt,
+    pin::Pin,
+    task::{Context, Poll},
+};
+
+use crate::{
+    error::{impl_foreign_error, Error},
+    io::{AsyncRead, AsyncWrite, Read, Write, WriteAllError},
+};
+
+mod base64;
+mod hex;
+
+pub use self::base64::*;
+pub use self::hex::*;
+
+/// The error type for the decoders in this module.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DecodeError<E> {
+    /// Reading from the underlying reader failed.
+    Read(E),
+    /// A byte that isn't part of the encoding's alphabet.
+    InvalidByte(u8),
+    /// Padding is missing, misplaced or not allowed.
+    InvalidPadding,
+    /// The input ended in the middle of an encoded group.
+    Truncated,
+}
+
+impl DecodeError<()> {
+    /// Converts an error that didn't come from the reader.
+    fn cast<E>(self) -> DecodeError<E> {
+        match self {
+            DecodeError::Read(()) => unreachable!(),
+            DecodeError::InvalidByte(b) => DecodeError::InvalidByte(b),
+            DecodeError::InvalidPadding => DecodeError::InvalidPadding,
+            DecodeError::Truncated => DecodeError::Truncated,
+        }
+    }
+}
+
+impl<E> fmt::Display for DecodeError<E> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DecodeError::Read(_) => f.write_str("error reading encoded data"),
+            DecodeError::InvalidByte(b) => write!(f, "invalid byte {:#04x}", b),
+            DecodeError::InvalidPadding => f.write_str("invalid padding"),
+            DecodeError::Truncated => f.write_str("encoded data ended early"),
+        }
+    }
+}
+
+impl<E: Error + 'static> Error for DecodeError<E> {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            DecodeError::Read(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl_foreign_error! {
+    impl<E> for DecodeError<E>;
+    fn source(&self) {
+        match self {
+            DecodeError::Read(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+/// Encoded output that hasn't been written yet.
+struct Output {
+    buf: [u8; 128],
+    pos: usize,
+    len: usize,
+}
+
+impl Output {
+    const fn new() -> Self {
+        Self {
+            buf: [0; 128],
+            pos: 0,
+            len: 0,
+        }
+    }
+
+    /// Returns the free space, which is only ever needed once the buffer has
+    /// been drained.
+    fn spare(&mut self) -> &mut [u8] {
+        if self.pos == self.len {
+            self.pos = 0;
+            self.len = 0;
+        }
+        &mut self.buf[self.len..]
+    }
+
+    fn push(&mut self, bytes: &[u8]) {
+        self.spare()[..bytes.len()].copy_from_slice(bytes);
+        self.len += bytes.len();
+    }
+
+    fn drain<W: Write>(&mut self, inner: &mut W) -> Result<(), WriteAllError<W::Error>> {
+        while self.pos != self.len {
+            match inner.write(&self.buf[self.pos..self.len]) {
+                Ok(0) => return Err(WriteAllError::WriteZero),
+                Ok(n) => self.pos += n,
+                Err(e) => return Err(WriteAllError::Other(e)),
+            }
+        }
+        Ok(())
+    }
+
+    fn poll_drain<W: AsyncWrite>(
+        &mut self,
+        mut inner: Pin<&mut W>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Result<(), WriteAllError<W::Error>>> {
+        while self.pos != self.len {
+            match inner.as_mut().poll_write(cx, &self.buf[self.pos..self.len]) {
+                Poll::Ready(Ok(0)) => return Poll::Ready(Err(WriteAllError::WriteZero)),
+                Poll::Ready(Ok(n)) => self.pos += n,
+                Poll::Ready(Err(e)) => return Poll::Ready(Err(WriteAllError::Other(e))),
+                Poll::Pending => return Poll::Pending,
+            }
+        }
+        Poll::Ready(Ok(()))
+    }
+}
+
+/// The state of an encoding, separate from the writer it writes to.
+trait Encode {
+    fn output(&mut self) -> &mut Output;
+
+    /// Encodes as much of `input` as fits in the (empty) output buffer,
+    /// returning how much was consumed.
+    fn encode(&mut self, input: &[u8]) -> usize;
+
+    /// Encodes whatever is left over at the end of the data.
+    fn end(&mut self);
+}
+
+/// Implements the accessors, `finish`, [`Write`] and [`AsyncWrite`] for an
+/// encoder with `inner`, `state` (an [`Encode`]) and `ended` fields.
+macro_rules! impl_encoder {
+    ($name:ident) => {
+        impl<W> $name<W> {
+            /// Gets a reference to the underlying writer.
+            pub fn get_ref(&self) -> &W {
+                &self.inner
+            }
+
+            /// Gets a mutable reference to the underlying writer.
+            pub fn get_mut(&mut self) -> &mut W {
+                &mut self.inner
+            }
+
+            /// Gets a pinned mutable reference to the underlying writer.
+            pub fn get_pin_mut(self: ::core::pin::Pin<&mut Self>) -> ::core::pin::Pin<&mut W> {
+                self.project().0
+            }
+
+            fn project(
+                self: ::core::pin::Pin<&mut Self>,
+            ) -> (
+                ::core::pin::Pin<&mut W>,
+                &mut dyn $crate::io::encoding::Encode,
+                &mut bool,
+            ) {
+                // SAFETY: `inner` is structurally pinned and never moved out
+                // of a pinned encoder. Nothing else is pinned.
+                unsafe {
+                    let this = self.get_unchecked_mut();
+                    (
+                        ::core::pin::Pin::new_unchecked(&mut this.inner),
+                        &mut this.state,
+                        &mut this.ended,
+                    )
+                }
+            }
+        }
+
+        impl<W: $crate::io::Write> $name<W> {
+            /// Writes out the end of the encoding, flushes, and returns the
+            /// underlying writer.
+            pub fn finish(mut self) -> Result<W, $crate::io::WriteAllError<W::Error>> {
+                $crate::io::encoding::finish(&mut self.inner, &mut self.state, &mut self.ended)?;
+                Ok(self.inner)
+            }
+        }
+
+        impl<W: $crate::io::Write> $crate::io::Write for $name<W> {
+            type Error = $crate::io::WriteAllError<W::Error>;
+
+            fn write(&mut self, buf: &[u8]) -> Result<usize, Self::Error> {
+                self.state.output().drain(&mut self.inner)?;
+                Ok(self.state.encode(buf))
+            }
+
+            fn flush(&mut self) -> Result<(), Self::Error> {
+                self.state.output().drain(&mut self.inner)?;
+                self.inner.flush().map_err($crate::io::WriteAllError::Other)
+            }
+        }
+
+        impl<W: $crate::io::AsyncWrite> $crate::io::AsyncWrite for $name<W> {
+            type Error = $crate::io::WriteAllError<W::Error>;
+
+            fn poll_write(
+                self: ::core::pin::Pin<&mut Self>,
+                cx: &mut ::core::task::Context<'_>,
+                buf: &[u8],
+            ) -> ::core::task::Poll<Result<usize, Self::Error>> {
+                let (inner, state, _) = self.project();
+                $crate::io::encoding::poll_write(inner, state, cx, buf)
+            }
+
+            fn poll_flush(
+                self: ::core::pin::Pin<&mut Self>,
+                cx: &mut ::core::task::Context<'_>,
+            ) -> ::core::task::Poll<Result<(), Self::Error>> {
+                let (inner, state, _) = self.project();
+                $crate::io::encoding::poll_flush(inner, state, cx)
+            }
+
+            /// Writes out the end of the encoding and closes the underlying
+            /// writer.
+            fn poll_close(
+                self: ::core::pin::Pin<&mut Self>,
+                cx: &mut ::core::task::Context<'_>,
+            ) -> ::core::task::Poll<Result<(), Self::Error>> {
+                let (inner, state, ended) = self.project();
+                $crate::io::encoding::poll_close(inner, state, ended, cx)
+            }
+        }
+    };
+}
+
+use impl_encoder;
+
+fn finish<W: Write>(
+    inner: &mut W,
+    state: &mut dyn Encode,
+    ended: &mut bool,
+) -> Result<(), WriteAllError<W::Error>> {
+    if !*ended {
+        state.output().drain(inner)?;
+        state.end();
+        *ended = true;
+    }
+    state.output().drain(inner)?;
+    inner.flush().map_err(WriteAllError::Other)
+}
+
+fn poll_write<W: AsyncWrite>(
+    inner: Pin<&mut W>,
+    state: &mut dyn Encode,
+    cx: &mut Context<'_>,
+    buf: &[u8],
+) -> Poll<Result<usize, WriteAllError<W::Error>>> {
+    match state.output().poll_drain(inner, cx) {
+        Poll::Ready(Ok(())) => Poll::Ready(Ok(state.encode(buf))),
+        Poll::Ready(Err(e)) => Poll::Ready(Err(e)),
+        Poll::Pending => Poll::Pending,
+    }
+}
+
+fn poll_flush<W: AsyncWrite>(
+    mut inner: Pin<&mut W>,
+    state: &mut dyn Encode,
+    cx: &mut Context<'_>,
+) -> Poll<Result<(), WriteAllError<W::Error>>> {
+    match state.output().poll_drain(inner.as_mut(), cx) {
+        Poll::Ready(Ok(())) => inner.poll_flush(cx).map_err(WriteAllError::Other),
+        Poll::Ready(Err(e)) => Poll::Ready(Err(e)),
+        Poll::Pending => Poll::Pending,
+    }
+}
+
+fn poll_close<W: AsyncWrite>(
+    mut inner: Pin<&mut W>,
+    state: &mut dyn Encode,
+    ended: &mut bool,
+    cx: &mut Context<'_>,
+) -> Poll<Result<(), WriteAllError<W::Error>>> {
+    if !*ended {
+        match state.output().poll_drain(inner.as_mut(), cx) {
+            Poll::Ready(Ok(())) => {}
+            Poll::Ready(Err(e)) => return Poll::Ready(Err(e)),
+            Poll::Pending => return Poll::Pending,
+        }
+        state.end();
+        *ended = true;
+    }
+    match state.output().poll_drain(inner.as_mut(), cx) {
+        Poll::Ready(Ok(())) => inner.poll_close(cx).map_err(WriteAllError::Other),
+        Poll::Ready(Err(e)) => Poll::Ready(Err(e)),
+        Poll::Pending => Poll::Pending,
+    }
+}
+
+/// Encoded input that hasn't been decoded yet.
+struct Input {
+    buf: [u8; 128],
+    pos: usize,
+    len: usize,
+    eof: bool,
+}
+
+impl Input {
+    const fn new() -> Self {
+        Self {
+            buf: [0; 128],
+            pos: 0,
+            len: 0,
+            eof: false,
+        }
+    }
+
+    fn next(&mut self) -> Option<u8> {
+        let byte = *self.buf[self.pos..self.len].first()?;
+        self.pos += 1;
+        Some(byte)
+    }
+
+    fn filled(&mut self, n: usize) {
+        self.pos = 0;
+        self.len = n;
+        self.eof = n == 0;
+    }
+}
+
+/// The state of a decoding, separate from the reader it reads from.
+trait Decode {
+    fn input(&mut self) -> &mut Input;
+
+    /// Decodes buffered input into `buf`, returning how much was produced.
+    ///
+    /// Errors are never [`DecodeError::Read`].
+    fn decode(&mut self, buf: &mut [u8]) -> Result<usize, DecodeError<()>>;
+
+    /// Checks that the data didn't end in the middle of a group, and
+    /// decodes whatever is left over.
+    fn end(&mut self) -> Result<(), DecodeError<()>>;
+}
+
+/// Implements the accessors, [`Read`] and [`AsyncRead`] for a decoder with
+/// `inner` and `state` (a [`Decode`]) fields.
+macro_rules! impl_decoder {
+    ($name:ident) => {
+        impl<R> $name<R> {
+            /// Gets a reference to the underlying reader.
+            pub fn get_ref(&self) -> &R {
+                &self.inner
+            }
+
+            /// Gets a mutable reference to the underlying reader.
+            pub fn get_mut(&mut self) -> &mut R {
+                &mut self.inner
+            }
+
+            /// Gets a pinned mutable reference to the underlying reader.
+            pub fn get_pin_mut(self: ::core::pin::Pin<&mut Self>) -> ::core::pin::Pin<&mut R> {
+                self.project().0
+            }
+
+            /// Unwraps this decoder, returning the underlying reader.
+            ///
+            /// Encoded data that was read but not yet decoded is lost.
+            pub fn into_inner(self) -> R {
+                self.inner
+            }
+
+            fn project(
+                self: ::core::pin::Pin<&mut Self>,
+            ) -> (
+                ::core::pin::Pin<&mut R>,
+                &mut dyn $crate::io::encoding::Decode,
+            ) {
+                // SAFETY: `inner` is structurally pinned and never moved out
+                // of a pinned decoder. Nothing else is pinned.
+                unsafe {
+                    let this = self.get_unchecked_mut();
+                    (
+                        ::core::pin::Pin::new_unchecked(&mut this.inner),
+                        &mut this.state,
+                    )
+                }
+            }
+        }
+
+        impl<R: $crate::io::Read> $crate::io::Read for $name<R> {
+            type Error = $crate::io::encoding::DecodeError<R::Error>;
+
+            fn read(&mut self, buf: &mut [u8]) -> Result<usize, Self::Error> {
+                $crate::io::encoding::read(&mut self.inner, &mut self.state, buf)
+            }
+        }
+
+        impl<R: $crate::io::AsyncRead> $crate::io::AsyncRead for $name<R> {
+            type Error = $crate::io::encoding::DecodeError<R::Error>;
+
+            fn poll_read(
+                self: ::core::pin::Pin<&mut Self>,
+                cx: &mut ::core::task::Context<'_>,
+                buf: &mut [u8],
+            ) -> ::core::task::Poll<Result<usize, Self::Error>> {
+                let (inner, state) = self.project();
+                $crate::io::encoding::poll_read(inner, state, cx, buf)
+            }
+        }
+    };
+}
+
+use impl_decoder;
+
+/// Decodes what's buffered, returning `None` if more input is needed first.
+fn decode_buffered(
+    state: &mut dyn Decode,
+    buf: &mut [u8],
+) -> Result<Option<usize>, DecodeError<()>> {
+    let n = state.decode(buf)?;
+    if n != 0 || buf.is_empty() || state.input().eof {
+        Ok(Some(n))
+    } else {
+        Ok(None)
+    }
+}
+
+/// Records that `n` bytes were read into the input buffer.
+fn decode_filled(state: &mut dyn Decode, n: usize) -> Result<(), DecodeError<()>> {
+    state.input().filled(n);
+    if n == 0 {
+        state.end()?;
+    }
+    Ok(())
+}
+
+fn read<R: Read>(
+    inner: &mut R,
+    state: &mut dyn Decode,
+    buf: &mut [u8],
+) -> Result<usize, DecodeError<R::Error>> {
+    loop {
+        if let Some(n) = decode_buffered(state, buf).map_err(DecodeError::cast)? {
+            return Ok(n);
+        }
+        let n = inner
+            .read(&mut state.input().buf)
+            .map_err(DecodeError::Read)?;
+        decode_filled(state, n).map_err(DecodeError::cast)?;
+    }
+}
+
+fn poll_read<R: AsyncRead>(
+    mut inner: Pin<&mut R>,
+    state: &mut dyn Decode,
+    cx: &mut Context<'_>,
+    buf: &mut [u8],
+) -> Poll<Result<usize, DecodeError<R::Error>>> {
+    loop {
+        if let Some(n) = decode_buffered(state, buf).map_err(DecodeError::cast)? {
+            return Poll::Ready(Ok(n));
+        }
+        match inner.as_mut().poll_read(cx, &mut state.input().buf) {
+            Poll::Ready(Ok(n)) => decode_filled(state, n).map_err(DecodeError::cast)?,
+            Poll::Ready(Err(e)) => return Poll::Ready(Err(DecodeError::Read(e))),
+            Poll::Pending => return Poll::Pending,
+        }
+    }
+}
+
+/// Copies bytes decoded earlier, which didn't fit in the caller's buffer,
+/// into `buf`.
+fn take_leftover(leftover: &mut [u8], pos: &mut usize, len: usize, buf: &mut [u8]) -> usize {
+    let n = cmp::min(len - *pos, buf.len());
+    buf[..n].copy_from_slice(&leftover[*pos..*pos + n]);
+    *pos += n;
+    n
+}
@@ -0,0 +1,339 @@
+use core::fmt;
+
+use super::{
+    impl_decoder, impl_encoder, take_leftover, Decode, DecodeError, Encode, Input, Output,
+};
+
+const STANDARD_ALPHABET: &[u8; 64] =
+    b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+const URL_SAFE_ALPHABET: &[u8; 64] =
+    b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789-_";
+
+/// Which Base64 alphabet to use, and whether the output is padded with `=`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Base64Config {
+    url_safe: bool,
+    padding: bool,
+}
+
+impl Base64Config {
+    /// The standard alphabet from RFC 4648, with padding.
+    pub const STANDARD: Self = Self {
+        url_safe: false,
+        padding: true,
+    };
+    /// The standard alphabet from RFC 4648, without padding.
+    pub const STANDARD_NO_PAD: Self = Self {
+        url_safe: false,
+        padding: false,
+    };
+    /// The URL and filename safe alphabet from RFC 4648, with padding.
+    pub const URL_SAFE: Self = Self {
+        url_safe: true,
+        padding: true,
+    };
+    /// The URL and filename safe alphabet from RFC 4648, without padding.
+    pub const URL_SAFE_NO_PAD: Self = Self {
+        url_safe: true,
+        padding: false,
+    };
+
+    /// Returns whether this uses the URL and filename safe alphabet.
+    pub fn is_url_safe(&self) -> bool {
+        self.url_safe
+    }
+
+    /// Returns whether encoded data is padded to a multiple of four
+    /// characters.
+    pub fn has_padding(&self) -> bool {
+        self.padding
+    }
+
+    fn alphabet(&self) -> &'static [u8; 64] {
+        if self.url_safe {
+            URL_SAFE_ALPHABET
+        } else {
+            STANDARD_ALPHABET
+        }
+    }
+
+    fn value(&self, byte: u8) -> Option<u8> {
+        match byte {
+            b'A'..=b'Z' => Some(byte - b'A'),
+            b'a'..=b'z' => Some(byte - b'a' + 26),
+            b'0'..=b'9' => Some(byte - b'0' + 52),
+            b'+' if !self.url_safe => Some(62),
+            b'/' if !self.url_safe => Some(63),
+            b'-' if self.url_safe => Some(62),
+            b'_' if self.url_safe => Some(63),
+            _ => None,
+        }
+    }
+}
+
+/// Writes the Base64 encoding of the data written to it.
+///
+/// Call [`finish`](Self::finish), or close an async encoder, to write out the
+/// last group.
+///
+/// # Examples
+///
+/// ```
+/// use coreplus::io::Write;
+/// use coreplus::io::encoding::{Base64Config, Base64Encoder};
+///
+/// let mut buf = [0; 16];
+/// let mut encoder = Base64Encoder::new(&mut buf[..], Base64Config::URL_SAFE_NO_PAD);
+/// encoder.write_all(&[0xfb, 0xff]).unwrap();
+/// encoder.finish().unwrap();
+/// assert_eq!(&buf[..3], b"-_8");
+/// ```
+pub struct Base64Encoder<W> {
+    inner: W,
+    state: EncodeState,
+    ended: bool,
+}
+
+struct EncodeState {
+    config: Base64Config,
+    partial: [u8; 3],
+    npartial: usize,
+    output: Output,
+}
+
+impl<W> Base64Encoder<W> {
+    /// Wraps `inner`, encoding with the given alphabet and padding.
+    pub fn new(inner: W, config: Base64Config) -> Self {
+        Self {
+            inner,
+            state: EncodeState {
+                config,
+                partial: [0; 3],
+                npartial: 0,
+                output: Output::new(),
+            },
+            ended: false,
+        }
+    }
+
+    /// Returns the configuration this encoder was created with.
+    pub fn config(&self) -> Base64Config {
+        self.state.config
+    }
+}
+
+impl_encoder!(Base64Encoder);
+
+impl EncodeState {
+    fn push_group(&mut self) {
+        let alphabet = self.config.alphabet();
+        let [a, b, c] = self.partial;
+        let chars = [
+            alphabet[(a >> 2) as usize],
+            alphabet[((a & 0x03) << 4 | b >> 4) as usize],
+            alphabet[((b & 0x0f) << 2 | c >> 6) as usize],
+            alphabet[(c & 0x3f) as usize],
+        ];
+
+        let len = if self.npartial == 3 {
+            4
+        } else {
+            self.npartial + 1
+        };
+        self.output.push(&chars[..len]);
+        if len < 4 && self.config.padding {
+            self.output.push(&b"=="[..4 - len]);
+        }
+        self.partial = [0; 3];
+        self.npartial = 0;
+    }
+}
+
+impl Encode for EncodeState {
+    fn output(&mut self) -> &mut Output {
+        &mut self.output
+    }
+
+    fn encode(&mut self, input: &[u8]) -> usize {
+        let mut consumed = 0;
+        while consumed < input.len() && self.output.spare().len() >= 4 {
+            self.partial[self.npartial] = input[consumed];
+            self.npartial += 1;
+            consumed += 1;
+            if self.npartial == 3 {
+                self.push_group();
+            }
+        }
+        consumed
+    }
+
+    fn end(&mut self) {
+        if self.npartial != 0 {
+            self.push_group();
+        }
+    }
+}
+
+impl<W: fmt::Debug> fmt::Debug for Base64Encoder<W> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Base64Encoder")
+            .field("inner", &self.inner)
+            .field("config", &self.state.config)
+            .finish()
+    }
+}
+
+/// Reads Base64 from the wrapped reader and returns the decoded data.
+///
+/// With a padded configuration the input must be padded; without one, any
+/// `=` is an error. Whitespace is not skipped.
+///
+/// # Examples
+///
+/// ```
+/// use coreplus::io::Read;
+/// use coreplus::io::encoding::{Base64Config, Base64Decoder, DecodeError};
+///
+/// let mut decoder = Base64Decoder::new(&b"aGk"[..], Base64Config::STANDARD_NO_PAD);
+/// let mut buf = [0; 4];
+/// assert_eq!(decoder.read(&mut buf), Ok(2));
+/// assert_eq!(&buf[..2], b"hi");
+///
+/// let mut decoder = Base64Decoder::new(&b"aGk"[..], Base64Config::STANDARD);
+/// assert_eq!(decoder.read(&mut buf), Err(DecodeError::Truncated));
+/// ```
+pub struct Base64Decoder<R> {
+    inner: R,
+    state: DecodeState,
+}
+
+struct DecodeState {
+    config: Base64Config,
+    input: Input,
+    group: [u8; 4],
+    ngroup: usize,
+    /// How many `=` have been seen in the current group.
+    npad: usize,
+    /// Whether a padded group has ended the data.
+    padded: bool,
+    leftover: [u8; 3],
+    leftover_pos: usize,
+    leftover_len: usize,
+}
+
+impl<R> Base64Decoder<R> {
+    /// Wraps `inner`, decoding with the given alphabet and padding.
+    pub fn new(inner: R, config: Base64Config) -> Self {
+        Self {
+            inner,
+            state: DecodeState {
+                config,
+                input: Input::new(),
+                group: [0; 4],
+                ngroup: 0,
+                npad: 0,
+                padded: false,
+                leftover: [0; 3],
+                leftover_pos: 0,
+                leftover_len: 0,
+            },
+        }
+    }
+
+    /// Returns the configuration this decoder was created with.
+    pub fn config(&self) -> Base64Config {
+        self.state.config
+    }
+}
+
+impl_decoder!(Base64Decoder);
+
+impl DecodeState {
+    /// Decodes the current group, which has `ngroup` characters, into the
+    /// leftover buffer.
+    fn decode_group(&mut self) {
+        let [a, b, c, d] = self.group;
+        let bits = u32::from(a) << 18 | u32::from(b) << 12 | u32::from(c) << 6 | u32::from(d);
+        self.leftover = [(bits >> 16) as u8, (bits >> 8) as u8, bits as u8];
+        self.leftover_pos = 0;
+        self.leftover_len = self.ngroup - 1;
+        self.group = [0; 4];
+        self.ngroup = 0;
+    }
+}
+
+impl Decode for DecodeState {
+    fn input(&mut self) -> &mut Input {
+        &mut self.input
+    }
+
+    fn decode(&mut self, buf: &mut [u8]) -> Result<usize, DecodeError<()>> {
+        let mut written = take_leftover(
+            &mut self.leftover,
+            &mut self.leftover_pos,
+            self.leftover_len,
+            buf,
+        );
+        while written < buf.len() {
+            let byte = match self.input.next() {
+                Some(byte) => byte,
+                None => break,
+            };
+
+            if byte == b'=' {
+                if !self.config.padding || self.padded || self.ngroup < 2 {
+                    return Err(DecodeError::InvalidPadding);
+                }
+                self.npad += 1;
+                if self.ngroup + self.npad == 4 {
+                    self.npad = 0;
+                    self.padded = true;
+                } else {
+                    continue;
+                }
+            } else {
+                let value = self
+                    .config
+                    .value(byte)
+                    .ok_or(DecodeError::InvalidByte(byte))?;
+                if self.npad != 0 || self.padded {
+                    return Err(DecodeError::InvalidPadding);
+                }
+                self.group[self.ngroup] = value;
+                self.ngroup += 1;
+                if self.ngroup != 4 {
+                    continue;
+                }
+            }
+
+            self.decode_group();
+            written += take_leftover(
+                &mut self.leftover,
+                &mut self.leftover_pos,
+                self.leftover_len,
+                &mut buf[written..],
+            );
+        }
+        Ok(written)
+    }
+
+    fn end(&mut self) -> Result<(), DecodeError<()>> {
+        match self.ngroup + self.npad {
+            0 => Ok(()),
+            2 | 3 if !self.config.padding => {
+                self.decode_group();
+                Ok(())
+            }
+            _ => Err(DecodeError::Truncated),
+        }
+    }
+}
+
+impl<R: fmt::Debug> fmt::Debug for Base64Decoder<R> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Base64Decoder")
+            .field("inner", &self.inner)
+            .field("config", &self.state.config)
+            .finish()
+    }
+}
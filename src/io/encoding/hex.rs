@@ -0,0 +1,311 @@
+use core::fmt;
+
+use super::{impl_decoder, impl_encoder, Decode, DecodeError, Encode, Input, Output};
+
+const LOWER_DIGITS: &[u8; 16] = b"0123456789abcdef";
+const UPPER_DIGITS: &[u8; 16] = b"0123456789ABCDEF";
+
+/// Writes each byte written to it as two hex digits.
+///
+/// # Examples
+///
+/// ```
+/// use coreplus::io::Write;
+/// use coreplus::io::encoding::HexEncoder;
+///
+/// let mut buf = [0; 8];
+/// let mut encoder = HexEncoder::new(&mut buf[..]);
+/// encoder.write_all(&[0xde, 0xad]).unwrap();
+/// encoder.finish().unwrap();
+/// assert_eq!(&buf[..4], b"dead");
+/// ```
+pub struct HexEncoder<W> {
+    inner: W,
+    state: EncodeState,
+    ended: bool,
+}
+
+struct EncodeState {
+    digits: &'static [u8; 16],
+    output: Output,
+}
+
+impl<W> HexEncoder<W> {
+    /// Wraps `inner`, writing lowercase digits.
+    pub fn new(inner: W) -> Self {
+        Self::with_digits(inner, LOWER_DIGITS)
+    }
+
+    /// Wraps `inner`, writing uppercase digits.
+    pub fn new_upper(inner: W) -> Self {
+        Self::with_digits(inner, UPPER_DIGITS)
+    }
+
+    fn with_digits(inner: W, digits: &'static [u8; 16]) -> Self {
+        Self {
+            inner,
+            state: EncodeState {
+                digits,
+                output: Output::new(),
+            },
+            ended: false,
+        }
+    }
+}
+
+impl_encoder!(HexEncoder);
+
+impl Encode for EncodeState {
+    fn output(&mut self) -> &mut Output {
+        &mut self.output
+    }
+
+    fn encode(&mut self, input: &[u8]) -> usize {
+        let mut consumed = 0;
+        while consumed < input.len() && self.output.spare().len() >= 2 {
+            let byte = input[consumed];
+            self.output.push(&[
+                self.digits[(byte >> 4) as usize],
+                self.digits[(byte & 0xf) as usize],
+            ]);
+            consumed += 1;
+        }
+        consumed
+    }
+
+    fn end(&mut self) {}
+}
+
+impl<W: fmt::Debug> fmt::Debug for HexEncoder<W> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("HexEncoder")
+            .field("inner", &self.inner)
+            .finish()
+    }
+}
+
+/// Reads hex digits from the wrapped reader and returns the bytes they
+/// encode.
+///
+/// Both uppercase and lowercase digits are accepted.
+///
+/// # Examples
+///
+/// ```
+/// use coreplus::io::Read;
+/// use coreplus::io::encoding::HexDecoder;
+///
+/// let mut decoder = HexDecoder::new(&b"DEad"[..]);
+/// let mut buf = [0; 4];
+/// assert_eq!(decoder.read(&mut buf), Ok(2));
+/// assert_eq!(&buf[..2], [0xde, 0xad]);
+/// ```
+pub struct HexDecoder<R> {
+    inner: R,
+    state: DecodeState,
+}
+
+struct DecodeState {
+    input: Input,
+    /// The first digit of a byte whose second digit hasn't been read yet.
+    high: Option<u8>,
+}
+
+impl<R> HexDecoder<R> {
+    /// Wraps `inner`.
+    pub fn new(inner: R) -> Self {
+        Self {
+            inner,
+            state: DecodeState {
+                input: Input::new(),
+                high: None,
+            },
+        }
+    }
+}
+
+impl_decoder!(HexDecoder);
+
+impl Decode for DecodeState {
+    fn input(&mut self) -> &mut Input {
+        &mut self.input
+    }
+
+    fn decode(&mut self, buf: &mut [u8]) -> Result<usize, DecodeError<()>> {
+        let mut written = 0;
+        while written < buf.len() {
+            let byte = match self.input.next() {
+                Some(byte) => byte,
+                None => break,
+            };
+            let value = match byte {
+                b'0'..=b'9' => byte - b'0',
+                b'a'..=b'f' => byte - b'a' + 10,
+                b'A'..=b'F' => byte - b'A' + 10,
+                _ => return Err(DecodeError::InvalidByte(byte)),
+            };
+            match self.high.take() {
+                Some(high) => {
+                    buf[written] = high << 4 | value;
+                    written += 1;
+                }
+                None => self.high = Some(value),
+            }
+        }
+        Ok(written)
+    }
+
+    fn end(&mut self) -> Result<(), DecodeError<()>> {
+        match self.high {
+            Some(_) => Err(DecodeError::Truncated),
+            None => Ok(()),
+        }
+    }
+}
+
+impl<R: fmt::Debug> fmt::Debug for HexDecoder<R> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("HexDecoder")
+            .field("inner", &self.inner)
+            .finish()
+    }
+}
+
+/// The length of a full line of a hex dump.
+const LINE_LEN: usize = 79;
+
+/// Writes a hex dump of the data written to it, in the same format as
+/// `hexdump -C`, for debugging.
+///
+/// Each line shows the offset, sixteen bytes in hex and the same bytes as
+/// ASCII, with `.` for anything that isn't printable. Unlike `hexdump`,
+/// repeated lines aren't collapsed. Call [`finish`](Self::finish), or close
+/// an async dump, to write out the last line and the final offset.
+///
+/// # Examples
+///
+/// ```
+/// use coreplus::io::Write;
+/// use coreplus::io::encoding::HexDump;
+///
+/// let mut buf = [0; 128];
+/// let mut dump = HexDump::new(&mut buf[..]);
+/// dump.write_all(b"hello\n").unwrap();
+/// let len = 128 - dump.finish().unwrap().len();
+///
+/// assert_eq!(
+///     core::str::from_utf8(&buf[..len]).unwrap(),
+///     "00000000  68 65 6c 6c 6f 0a                                 |hello.|\n\
+///      00000006\n",
+/// );
+/// ```
+pub struct HexDump<W> {
+    inner: W,
+    state: DumpState,
+    ended: bool,
+}
+
+struct DumpState {
+    offset: u64,
+    line: [u8; 16],
+    nline: usize,
+    output: Output,
+}
+
+impl<W> HexDump<W> {
+    /// Wraps `inner`, starting at offset zero.
+    pub fn new(inner: W) -> Self {
+        Self {
+            inner,
+            state: DumpState {
+                offset: 0,
+                line: [0; 16],
+                nline: 0,
+                output: Output::new(),
+            },
+            ended: false,
+        }
+    }
+}
+
+impl_encoder!(HexDump);
+
+impl DumpState {
+    fn push_offset(&mut self) {
+        let mut digits = [0; 8];
+        for (i, digit) in digits.iter_mut().enumerate() {
+            *digit = LOWER_DIGITS[(self.offset >> (28 - 4 * i) & 0xf) as usize];
+        }
+        self.output.push(&digits);
+    }
+
+    fn push_line(&mut self) {
+        self.push_offset();
+        self.output.push(b"  ");
+        for i in 0..16 {
+            match self.line[..self.nline].get(i) {
+                Some(&byte) => self.output.push(&[
+                    LOWER_DIGITS[(byte >> 4) as usize],
+                    LOWER_DIGITS[(byte & 0xf) as usize],
+                    b' ',
+                ]),
+                None => self.output.push(b"   "),
+            }
+            if i == 7 {
+                self.output.push(b" ");
+            }
+        }
+        self.output.push(b" |");
+        for i in 0..self.nline {
+            let byte = self.line[i];
+            let shown = if byte.is_ascii_graphic() || byte == b' ' {
+                byte
+            } else {
+                b'.'
+            };
+            self.output.push(&[shown]);
+        }
+        self.output.push(b"|\n");
+
+        self.offset += self.nline as u64;
+        self.nline = 0;
+    }
+}
+
+impl Encode for DumpState {
+    fn output(&mut self) -> &mut Output {
+        &mut self.output
+    }
+
+    fn encode(&mut self, input: &[u8]) -> usize {
+        let mut consumed = 0;
+        while consumed < input.len() && self.output.spare().len() >= LINE_LEN {
+            self.line[self.nline] = input[consumed];
+            self.nline += 1;
+            consumed += 1;
+            if self.nline == 16 {
+                self.push_line();
+            }
+        }
+        consumed
+    }
+
+    fn end(&mut self) {
+        if self.nline != 0 {
+            self.push_line();
+        }
+        if self.offset != 0 {
+            self.push_offset();
+            self.output.push(b"\n");
+        }
+    }
+}
+
+impl<W: fmt::Debug> fmt::Debug for HexDump<W> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("HexDump")
+            .field("inner", &self.inner)
+            .field("offset", &self.state.offset)
+            .finish()
+    }
+}
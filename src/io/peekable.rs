@@ -0,0 +1,295 @@
+use core::{
+    cmp, fmt,
+    pin::Pin,
+    task::{Context, Poll},
+};
+
+use crate::{
+    error::{impl_foreign_error, Error},
+    io::{AsyncRead, Read},
+};
+
+/// The error returned by [`Peekable::unread`] when the bytes don't fit in the
+/// buffer.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct UnreadError;
+
+impl fmt::Display for UnreadError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("not enough room in the peek buffer")
+    }
+}
+
+impl Error for UnreadError {}
+
+impl_foreign_error!(UnreadError);
+
+/// A reader that can look at upcoming bytes without consuming them, and put
+/// bytes back.
+///
+/// Up to `N` bytes are held in a buffer inside the wrapper. Reads are served
+/// from the buffer first, so the stream comes out unchanged no matter how
+/// much was peeked.
+///
+/// # Examples
+///
+/// ```
+/// use coreplus::io::{Peekable, Read};
+///
+/// let mut reader = Peekable::<_, 8>::new(&b"\x16\x03\x01hello"[..]);
+///
+/// // Looks like a TLS handshake.
+/// assert_eq!(reader.peek(1).unwrap(), b"\x16");
+///
+/// let mut buf = [0; 8];
+/// let n = reader.read(&mut buf).unwrap();
+/// assert_eq!(&buf[..n], b"\x16\x03\x01hello");
+/// ```
+#[derive(Debug)]
+pub struct Peekable<R, const N: usize> {
+    inner: R,
+    buffer: Buffer<N>,
+}
+
+#[derive(Debug)]
+struct Buffer<const N: usize> {
+    buf: [u8; N],
+    start: usize,
+    end: usize,
+}
+
+impl<const N: usize> Buffer<N> {
+    fn len(&self) -> usize {
+        self.end - self.start
+    }
+
+    fn bytes(&self, n: usize) -> &[u8] {
+        let len = cmp::min(n, self.len());
+        &self.buf[self.start..self.start + len]
+    }
+
+    /// Returns the free space after the buffered bytes, first moving them to
+    /// the front if there wouldn't be room for `n` bytes in total.
+    fn spare(&mut self, n: usize) -> &mut [u8] {
+        if self.start + n > N {
+            self.buf.copy_within(self.start..self.end, 0);
+            self.end -= self.start;
+            self.start = 0;
+        }
+        &mut self.buf[self.end..]
+    }
+
+    /// Moves buffered bytes into `buf`.
+    fn take(&mut self, buf: &mut [u8]) -> usize {
+        let n = cmp::min(buf.len(), self.len());
+        buf[..n].copy_from_slice(&self.buf[self.start..self.start + n]);
+        self.start += n;
+        n
+    }
+
+    fn unread(&mut self, data: &[u8]) -> Result<(), UnreadError> {
+        let len = self.len();
+        if data.len() > N - len {
+            return Err(UnreadError);
+        }
+
+        if data.len() > self.start {
+            self.buf.copy_within(self.start..self.end, data.len());
+            self.start = data.len();
+            self.end = data.len() + len;
+        }
+        self.start -= data.len();
+        self.buf[self.start..self.start + data.len()].copy_from_slice(data);
+        Ok(())
+    }
+}
+
+impl<R, const N: usize> Peekable<R, N> {
+    /// Wraps `inner` with an empty buffer.
+    pub fn new(inner: R) -> Self {
+        Self {
+            inner,
+            buffer: Buffer {
+                buf: [0; N],
+                start: 0,
+                end: 0,
+            },
+        }
+    }
+
+    /// Returns the bytes that have been peeked or unread but not yet read.
+    pub fn buffer(&self) -> &[u8] {
+        self.buffer.bytes(N)
+    }
+
+    /// Puts `data` back in front of the stream, so it is read before anything
+    /// else.
+    ///
+    /// The data doesn't have to be something that was read from this stream.
+    /// Fails if the buffer doesn't have room for it, in which case nothing is
+    /// changed.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use coreplus::io::{Peekable, Read};
+    ///
+    /// let mut reader = Peekable::<_, 4>::new(&b"llo"[..]);
+    /// reader.unread(b"he").unwrap();
+    ///
+    /// let mut buf = [0; 8];
+    /// let n = reader.read(&mut buf).unwrap();
+    /// assert_eq!(&buf[..n], b"he");
+    /// let n = reader.read(&mut buf).unwrap();
+    /// assert_eq!(&buf[..n], b"llo");
+    /// ```
+    pub fn unread(&mut self, data: &[u8]) -> Result<(), UnreadError> {
+        self.buffer.unread(data)
+    }
+
+    /// Gets a reference to the underlying reader.
+    pub fn get_ref(&self) -> &R {
+        &self.inner
+    }
+
+    /// Gets a mutable reference to the underlying reader.
+    ///
+    /// Reading from it directly skips over any buffered bytes.
+    pub fn get_mut(&mut self) -> &mut R {
+        &mut self.inner
+    }
+
+    /// Gets a pinned mutable reference to the underlying reader.
+    ///
+    /// Reading from it directly skips over any buffered bytes.
+    pub fn get_pin_mut(self: Pin<&mut Self>) -> Pin<&mut R> {
+        self.project().0
+    }
+
+    /// Unwraps this `Peekable`, returning the underlying reader.
+    ///
+    /// Any buffered bytes are lost.
+    pub fn into_inner(self) -> R {
+        self.inner
+    }
+
+    fn project(self: Pin<&mut Self>) -> (Pin<&mut R>, &mut Buffer<N>) {
+        // SAFETY: `inner` is structurally pinned and never moved out of a
+        // pinned `Peekable`. The buffer is not pinned.
+        unsafe {
+            let this = self.get_unchecked_mut();
+            (Pin::new_unchecked(&mut this.inner), &mut this.buffer)
+        }
+    }
+}
+
+impl<R: Read, const N: usize> Peekable<R, N> {
+    /// Returns the next `n` bytes without consuming them, reading from the
+    /// underlying reader as many times as needed.
+    ///
+    /// Fewer than `n` bytes are returned only if the end of the stream was
+    /// reached.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `n` is larger than the buffer size `N`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use coreplus::io::{Peekable, Read};
+    ///
+    /// // A reader that returns one byte per call.
+    /// struct Trickle(&'static [u8]);
+    ///
+    /// impl Read for Trickle {
+    ///     type Error = core::convert::Infallible;
+    ///     fn read(&mut self, buf: &mut [u8]) -> Result<usize, Self::Error> {
+    ///         let n = self.0.len().min(buf.len()).min(1);
+    ///         buf[..n].copy_from_slice(&self.0[..n]);
+    ///         self.0 = &self.0[n..];
+    ///         Ok(n)
+    ///     }
+    /// }
+    ///
+    /// let mut reader = Peekable::<_, 8>::new(Trickle(b"hello"));
+    /// assert_eq!(reader.peek(3), Ok(&b"hel"[..]));
+    /// assert_eq!(reader.get_ref().0, b"lo");
+    ///
+    /// // Peeking past the end returns what's left.
+    /// assert_eq!(reader.peek(8), Ok(&b"hello"[..]));
+    ///
+    /// // Nothing was consumed.
+    /// let mut buf = [0; 8];
+    /// let n = reader.read(&mut buf).unwrap();
+    /// assert_eq!(&buf[..n], b"hello");
+    /// ```
+    pub fn peek(&mut self, n: usize) -> Result<&[u8], R::Error> {
+        assert!(n <= N, "cannot peek more bytes than the buffer holds");
+        while self.buffer.len() < n {
+            let read = self.inner.read(self.buffer.spare(n))?;
+            if read == 0 {
+                break;
+            }
+            self.buffer.end += read;
+        }
+        Ok(self.buffer.bytes(n))
+    }
+}
+
+impl<R: AsyncRead, const N: usize> Peekable<R, N> {
+    /// Returns the next `n` bytes without consuming them, reading from the
+    /// underlying reader as many times as needed.
+    ///
+    /// Fewer than `n` bytes are returned only if the end of the stream was
+    /// reached. Bytes that arrive before the reader returns
+    /// [`Poll::Pending`] stay buffered.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `n` is larger than the buffer size `N`.
+    pub fn poll_peek(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        n: usize,
+    ) -> Poll<Result<&[u8], R::Error>> {
+        assert!(n <= N, "cannot peek more bytes than the buffer holds");
+        let (mut inner, buffer) = self.project();
+        while buffer.len() < n {
+            match inner.as_mut().poll_read(cx, buffer.spare(n)) {
+                Poll::Ready(Ok(0)) => break,
+                Poll::Ready(Ok(read)) => buffer.end += read,
+                Poll::Ready(Err(e)) => return Poll::Ready(Err(e)),
+                Poll::Pending => return Poll::Pending,
+            }
+        }
+        Poll::Ready(Ok(buffer.bytes(n)))
+    }
+}
+
+impl<R: Read, const N: usize> Read for Peekable<R, N> {
+    type Error = R::Error;
+
+    fn read(&mut self, buf: &mut [u8]) -> Result<usize, Self::Error> {
+        if self.buffer.len() == 0 {
+            return self.inner.read(buf);
+        }
+        Ok(self.buffer.take(buf))
+    }
+}
+
+impl<R: AsyncRead, const N: usize> AsyncRead for Peekable<R, N> {
+    type Error = R::Error;
+
+    fn poll_read(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut [u8],
+    ) -> Poll<Result<usize, Self::Error>> {
+        let (inner, buffer) = self.project();
+        if buffer.len() == 0 {
+            return inner.poll_read(cx, buf);
+        }
+        Poll::Ready(Ok(buffer.take(buf)))
+    }
+}
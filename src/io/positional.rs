@@ -0,0 +1,553 @@
+use core::{
+    cmp, fmt,
+    pin::Pin,
+    task::{Context, Poll},
+};
+
+use crate::{
+    error::{impl_foreign_error, Error},
+    io::{AsyncRead, AsyncWrite, Read, Seek, SeekFrom, Write},
+};
+
+/// The error returned by [`ReadAt::read_exact_at`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ReadExactError<E> {
+    /// The end of the device was reached before the buffer was filled.
+    UnexpectedEof,
+    /// The underlying device returned an error.
+    Other(E),
+}
+
+impl<E: fmt::Display> fmt::Display for ReadExactError<E> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ReadExactError::UnexpectedEof => f.write_str("failed to fill whole buffer"),
+            ReadExactError::Other(e) => e.fmt(f),
+        }
+    }
+}
+
+impl<E: Error> Error for ReadExactError<E> {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            ReadExactError::UnexpectedEof => None,
+            ReadExactError::Other(e) => e.source(),
+        }
+    }
+}
+
+impl_foreign_error! {
+    impl<E> for ReadExactError<E>;
+    fn source(&self) {
+        match self {
+            ReadExactError::UnexpectedEof => None,
+            ReadExactError::Other(e) => e.source(),
+        }
+    }
+}
+
+/// The error returned by [`WriteAt::write_all_at`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum WriteAllError<E> {
+    /// The device accepted zero bytes before the whole buffer was written.
+    WriteZero,
+    /// The underlying device returned an error.
+    Other(E),
+}
+
+impl<E: fmt::Display> fmt::Display for WriteAllError<E> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            WriteAllError::WriteZero => f.write_str("failed to write whole buffer"),
+            WriteAllError::Other(e) => e.fmt(f),
+        }
+    }
+}
+
+impl<E: Error> Error for WriteAllError<E> {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            WriteAllError::WriteZero => None,
+            WriteAllError::Other(e) => e.source(),
+        }
+    }
+}
+
+impl_foreign_error! {
+    impl<E> for WriteAllError<E>;
+    fn source(&self) {
+        match self {
+            WriteAllError::WriteZero => None,
+            WriteAllError::Other(e) => e.source(),
+        }
+    }
+}
+
+/// Read bytes at an offset.
+///
+/// Unlike [`Read`] with [`Seek`], there's no shared position, so a device
+/// can be read from several places at once.
+///
+/// When the `std` feature is enabled on a Unix platform, this trait is
+/// implemented for [`CoreIO<std::fs::File>`](crate::io::CoreIO).
+pub trait ReadAt {
+    type Error;
+
+    /// Reads bytes starting at `offset`, returning how many were read.
+    ///
+    /// A return value of zero means `offset` is at or past the end of the
+    /// device, or `buf` is empty.
+    fn read_at(&self, buf: &mut [u8], offset: u64) -> Result<usize, Self::Error>;
+
+    /// Reads exactly enough bytes to fill `buf`, starting at `offset`.
+    fn read_exact_at(
+        &self,
+        mut buf: &mut [u8],
+        mut offset: u64,
+    ) -> Result<(), ReadExactError<Self::Error>> {
+        while !buf.is_empty() {
+            match self.read_at(buf, offset) {
+                Ok(0) => return Err(ReadExactError::UnexpectedEof),
+                Ok(n) => {
+                    buf = &mut buf[n..];
+                    offset += n as u64;
+                }
+                Err(e) => return Err(ReadExactError::Other(e)),
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Write bytes at an offset.
+///
+/// Like [`ReadAt`], this takes `&self` because there's no shared position,
+/// so a device can be written at several places at once.
+///
+/// When the `std` feature is enabled on a Unix platform, this trait is
+/// implemented for [`CoreIO<std::fs::File>`](crate::io::CoreIO).
+pub trait WriteAt {
+    type Error;
+
+    /// Writes bytes starting at `offset`, returning how many were written.
+    fn write_at(&self, buf: &[u8], offset: u64) -> Result<usize, Self::Error>;
+
+    /// Flushes any buffered writes to the device.
+    fn flush(&self) -> Result<(), Self::Error>;
+
+    /// Writes all of `buf`, starting at `offset`.
+    fn write_all_at(
+        &self,
+        mut buf: &[u8],
+        mut offset: u64,
+    ) -> Result<(), WriteAllError<Self::Error>> {
+        while !buf.is_empty() {
+            match self.write_at(buf, offset) {
+                Ok(0) => return Err(WriteAllError::WriteZero),
+                Ok(n) => {
+                    buf = &buf[n..];
+                    offset += n as u64;
+                }
+                Err(e) => return Err(WriteAllError::Other(e)),
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Read bytes at an offset asynchronously.
+///
+/// This is the async version of [`ReadAt`]. [`OffsetCursor`] turns it into
+/// an [`AsyncRead`] stream.
+pub trait AsyncReadAt {
+    /// The associated error type.
+    type Error;
+
+    /// Attempts to read bytes starting at `offset`, returning how many were
+    /// read.
+    ///
+    /// A return value of zero means `offset` is at or past the end of the
+    /// device, or `buf` is empty. If the device isn't ready, this returns
+    /// [`Poll::Pending`] and wakes the task when it should be polled again.
+    fn poll_read_at(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut [u8],
+        offset: u64,
+    ) -> Poll<Result<usize, Self::Error>>;
+}
+
+/// Write bytes at an offset asynchronously.
+///
+/// This is the async version of [`WriteAt`]. [`OffsetCursor`] turns it into
+/// an [`AsyncWrite`] stream.
+pub trait AsyncWriteAt {
+    /// The associated error type.
+    type Error;
+
+    /// Attempts to write bytes starting at `offset`, returning how many were
+    /// written.
+    ///
+    /// If the device isn't ready, this returns [`Poll::Pending`] and wakes
+    /// the task when it should be polled again.
+    fn poll_write_at(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &[u8],
+        offset: u64,
+    ) -> Poll<Result<usize, Self::Error>>;
+
+    /// Attempts to flush any buffered writes to the device.
+    fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>>;
+}
+
+impl<T: ReadAt + ?Sized> ReadAt for &T {
+    type Error = T::Error;
+
+    fn read_at(&self, buf: &mut [u8], offset: u64) -> Result<usize, Self::Error> {
+        (**self).read_at(buf, offset)
+    }
+}
+
+impl<T: WriteAt + ?Sized> WriteAt for &T {
+    type Error = T::Error;
+
+    fn write_at(&self, buf: &[u8], offset: u64) -> Result<usize, Self::Error> {
+        (**self).write_at(buf, offset)
+    }
+
+    fn flush(&self) -> Result<(), Self::Error> {
+        (**self).flush()
+    }
+}
+
+impl ReadAt for [u8] {
+    type Error = core::convert::Infallible;
+
+    fn read_at(&self, buf: &mut [u8], offset: u64) -> Result<usize, Self::Error> {
+        let start = cmp::min(offset, self.len() as u64) as usize;
+        let mut rest = &self[start..];
+        rest.read(buf)
+    }
+}
+
+/// The error returned when seeking an [`OffsetCursor`] to a negative or
+/// overflowing position.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct InvalidSeek;
+
+impl fmt::Display for InvalidSeek {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("invalid seek to a negative or overflowing position")
+    }
+}
+
+impl Error for InvalidSeek {}
+
+impl_foreign_error!(InvalidSeek);
+
+/// The error returned by reads and writes through an [`OffsetCursor`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum OffsetCursorError<E> {
+    /// Moving the position past the bytes transferred would overflow a
+    /// `u64`.
+    Overflow,
+    /// The underlying device returned an error.
+    Other(E),
+}
+
+impl<E: fmt::Display> fmt::Display for OffsetCursorError<E> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            OffsetCursorError::Overflow => f.write_str("cursor position overflowed"),
+            OffsetCursorError::Other(e) => e.fmt(f),
+        }
+    }
+}
+
+impl<E: Error> Error for OffsetCursorError<E> {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            OffsetCursorError::Overflow => None,
+            OffsetCursorError::Other(e) => e.source(),
+        }
+    }
+}
+
+impl_foreign_error! {
+    impl<E> for OffsetCursorError<E>;
+    fn source(&self) {
+        match self {
+            OffsetCursorError::Overflow => None,
+            OffsetCursorError::Other(e) => e.source(),
+        }
+    }
+}
+
+/// Moves `pos` past `n` transferred bytes.
+fn advance<E>(pos: &mut u64, n: usize) -> Result<usize, OffsetCursorError<E>> {
+    *pos = pos
+        .checked_add(n as u64)
+        .ok_or(OffsetCursorError::Overflow)?;
+    Ok(n)
+}
+
+/// Turns a [`ReadAt`] (and [`WriteAt`]) device into a [`Read`] (and [`Write`])
+/// stream that implements [`Seek`].
+///
+/// The cursor keeps its own position, so several cursors can read the same
+/// device independently. An [`AsyncReadAt`] (and [`AsyncWriteAt`]) device
+/// becomes an [`AsyncRead`] (and [`AsyncWrite`]) stream in the same way.
+///
+/// A read or write that would move the position past [`u64::MAX`] returns
+/// [`OffsetCursorError::Overflow`] and leaves the position where it was.
+///
+/// # Examples
+///
+/// ```
+/// use coreplus::io::{OffsetCursor, Read, Seek, SeekFrom};
+///
+/// let data = b"hello world";
+/// let mut cursor = OffsetCursor::new(&data[..], data.len() as u64);
+///
+/// cursor.seek(SeekFrom::End(-5)).unwrap();
+/// let mut buf = [0; 8];
+/// assert_eq!(cursor.read(&mut buf), Ok(5));
+/// assert_eq!(&buf[..5], b"world");
+/// ```
+///
+/// With an async device:
+///
+/// ```
+/// use core::{cmp, convert::Infallible, pin::Pin, task::{Context, Poll}};
+/// use coreplus::io::{
+///     AsyncRead, AsyncReadAt, AsyncWrite, AsyncWriteAt, OffsetCursor, ReadAt, Seek, SeekFrom,
+///     Write,
+/// };
+/// use coreplus::task::{block_on, poll_fn};
+///
+/// // A device that's always ready.
+/// struct Disk([u8; 16]);
+///
+/// impl AsyncReadAt for Disk {
+///     type Error = Infallible;
+///     fn poll_read_at(
+///         self: Pin<&mut Self>,
+///         _cx: &mut Context<'_>,
+///         buf: &mut [u8],
+///         offset: u64,
+///     ) -> Poll<Result<usize, Infallible>> {
+///         Poll::Ready(self.0.read_at(buf, offset))
+///     }
+/// }
+///
+/// impl AsyncWriteAt for Disk {
+///     type Error = Infallible;
+///     fn poll_write_at(
+///         mut self: Pin<&mut Self>,
+///         _cx: &mut Context<'_>,
+///         buf: &[u8],
+///         offset: u64,
+///     ) -> Poll<Result<usize, Infallible>> {
+///         let start = cmp::min(offset, 16) as usize;
+///         Poll::Ready((&mut self.0[start..]).write(buf))
+///     }
+///     fn poll_flush(self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<Result<(), Infallible>> {
+///         Poll::Ready(Ok(()))
+///     }
+/// }
+///
+/// let mut cursor = OffsetCursor::new(Disk([0; 16]), 0);
+/// let n = block_on(poll_fn(|cx| Pin::new(&mut cursor).poll_write(cx, b"hello")));
+/// assert_eq!(n, Ok(5));
+/// assert_eq!(cursor.len(), 5);
+///
+/// cursor.seek(SeekFrom::Start(1)).unwrap();
+/// let mut buf = [0; 8];
+/// let n = block_on(poll_fn(|cx| Pin::new(&mut cursor).poll_read(cx, &mut buf)));
+/// assert_eq!(n, Ok(4));
+/// assert_eq!(&buf[..4], b"ello");
+/// ```
+#[derive(Clone, Debug)]
+pub struct OffsetCursor<T> {
+    inner: T,
+    pos: u64,
+    len: u64,
+}
+
+impl<T> OffsetCursor<T> {
+    /// Creates a cursor at offset zero over a device of `len` bytes.
+    ///
+    /// The length is used for [`SeekFrom::End`] and to stop reads at the end
+    /// of the device.
+    pub fn new(inner: T, len: u64) -> Self {
+        Self { inner, pos: 0, len }
+    }
+
+    /// Returns the current position of this cursor.
+    pub fn position(&self) -> u64 {
+        self.pos
+    }
+
+    /// Sets the position of this cursor.
+    pub fn set_position(&mut self, pos: u64) {
+        self.pos = pos;
+    }
+
+    /// Returns the length of the underlying device, as far as this cursor knows.
+    ///
+    /// Writes past the end grow it.
+    pub fn len(&self) -> u64 {
+        self.len
+    }
+
+    /// Returns `true` if the underlying device is empty.
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Gets a reference to the underlying device.
+    pub fn get_ref(&self) -> &T {
+        &self.inner
+    }
+
+    /// Gets a mutable reference to the underlying device.
+    pub fn get_mut(&mut self) -> &mut T {
+        &mut self.inner
+    }
+
+    /// Gets a pinned mutable reference to the underlying device.
+    pub fn get_pin_mut(self: Pin<&mut Self>) -> Pin<&mut T> {
+        self.project().0
+    }
+
+    /// Unwraps this `OffsetCursor`, returning the underlying device.
+    pub fn into_inner(self) -> T {
+        self.inner
+    }
+
+    fn project(self: Pin<&mut Self>) -> (Pin<&mut T>, &mut u64, &mut u64) {
+        // SAFETY: `inner` is structurally pinned and never moved out of a
+        // pinned `OffsetCursor`. The position and length are not pinned.
+        unsafe {
+            let this = self.get_unchecked_mut();
+            (
+                Pin::new_unchecked(&mut this.inner),
+                &mut this.pos,
+                &mut this.len,
+            )
+        }
+    }
+}
+
+impl<T: ReadAt> Read for OffsetCursor<T> {
+    type Error = OffsetCursorError<T::Error>;
+
+    fn read(&mut self, buf: &mut [u8]) -> Result<usize, Self::Error> {
+        let left = self.len.saturating_sub(self.pos);
+        let amt = cmp::min(buf.len() as u64, left) as usize;
+        let n = self
+            .inner
+            .read_at(&mut buf[..amt], self.pos)
+            .map_err(OffsetCursorError::Other)?;
+        advance(&mut self.pos, n)
+    }
+}
+
+impl<T: WriteAt> Write for OffsetCursor<T> {
+    type Error = OffsetCursorError<T::Error>;
+
+    fn write(&mut self, buf: &[u8]) -> Result<usize, Self::Error> {
+        let n = self
+            .inner
+            .write_at(buf, self.pos)
+            .map_err(OffsetCursorError::Other)?;
+        advance(&mut self.pos, n)?;
+        self.len = cmp::max(self.len, self.pos);
+        Ok(n)
+    }
+
+    fn flush(&mut self) -> Result<(), Self::Error> {
+        self.inner.flush().map_err(OffsetCursorError::Other)
+    }
+}
+
+impl<T: AsyncReadAt> AsyncRead for OffsetCursor<T> {
+    type Error = OffsetCursorError<T::Error>;
+
+    fn poll_read(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut [u8],
+    ) -> Poll<Result<usize, Self::Error>> {
+        let (inner, pos, len) = self.project();
+        let left = len.saturating_sub(*pos);
+        let amt = cmp::min(buf.len() as u64, left) as usize;
+        match inner.poll_read_at(cx, &mut buf[..amt], *pos) {
+            Poll::Ready(Ok(n)) => Poll::Ready(advance(pos, n)),
+            Poll::Ready(Err(e)) => Poll::Ready(Err(OffsetCursorError::Other(e))),
+            Poll::Pending => Poll::Pending,
+        }
+    }
+}
+
+impl<T: AsyncWriteAt> AsyncWrite for OffsetCursor<T> {
+    type Error = OffsetCursorError<T::Error>;
+
+    fn poll_write(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<Result<usize, Self::Error>> {
+        let (inner, pos, len) = self.project();
+        match inner.poll_write_at(cx, buf, *pos) {
+            Poll::Ready(Ok(n)) => {
+                let n = advance(pos, n);
+                *len = cmp::max(*len, *pos);
+                Poll::Ready(n)
+            }
+            Poll::Ready(Err(e)) => Poll::Ready(Err(OffsetCursorError::Other(e))),
+            Poll::Pending => Poll::Pending,
+        }
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.project()
+            .0
+            .poll_flush(cx)
+            .map_err(OffsetCursorError::Other)
+    }
+
+    /// A positional device has nothing to shut down, so this only flushes
+    /// it.
+    fn poll_close(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.poll_flush(cx)
+    }
+}
+
+impl<T> Seek for OffsetCursor<T> {
+    type Error = InvalidSeek;
+
+    fn seek(&mut self, pos: SeekFrom) -> Result<u64, Self::Error> {
+        let (base, offset) = match pos {
+            SeekFrom::Start(n) => {
+                self.pos = n;
+                return Ok(n);
+            }
+            SeekFrom::End(n) => (self.len, n),
+            SeekFrom::Current(n) => (self.pos, n),
+        };
+        let new = if offset >= 0 {
+            base.checked_add(offset as u64)
+        } else {
+            base.checked_sub(offset.unsigned_abs())
+        };
+        match new {
+            Some(n) => {
+                self.pos = n;
+                Ok(n)
+            }
+            None => Err(InvalidSeek),
+        }
+    }
+}
@@ -0,0 +1,271 @@
+use core::{cmp, fmt, mem::MaybeUninit};
+
+/// A wrapper around a byte buffer that is incrementally filled and initialized.
+///
+/// This type is a sort of "double cursor". It tracks three regions in the
+/// buffer: a region at the beginning of the buffer that has been logically
+/// filled with data, a region that has been initialized at some point but not
+/// yet logically filled, and a region at the end that may be uninitialized.
+/// The filled region is guaranteed to be a subset of the initialized region.
+///
+/// In summary, the contents of the buffer can be visualized as:
+///
+/// ```not_rust
+/// [             capacity              ]
+/// [ filled |         unfilled         ]
+/// [    initialized    | uninitialized ]
+/// ```
+///
+/// Reading into a `ReadBuf` with [`Read::read_buf`](crate::io::Read::read_buf)
+/// or [`AsyncRead::poll_read_buf`](crate::io::AsyncRead::poll_read_buf) avoids
+/// zeroing memory that has already been initialized. Reuse a `ReadBuf` with
+/// [`clear`](ReadBuf::clear) to keep what is already initialized.
+///
+/// # Examples
+///
+/// ```
+/// use core::mem::MaybeUninit;
+/// use coreplus::io::{Read, ReadBuf};
+///
+/// let mut storage = [MaybeUninit::uninit(); 16];
+/// let mut buf = ReadBuf::uninit(&mut storage);
+///
+/// let mut reader: &[u8] = b"hello";
+/// reader.read_buf(&mut buf).unwrap();
+///
+/// assert_eq!(buf.filled(), b"hello");
+/// assert_eq!(buf.remaining(), 11);
+/// ```
+pub struct ReadBuf<'a> {
+    buf: &'a mut [MaybeUninit<u8>],
+    filled: usize,
+    initialized: usize,
+}
+
+impl<'a> ReadBuf<'a> {
+    /// Creates a new `ReadBuf` from a fully initialized buffer.
+    #[inline]
+    pub fn new(buf: &'a mut [u8]) -> Self {
+        let initialized = buf.len();
+        // SAFETY: `u8` and `MaybeUninit<u8>` have the same layout, and the
+        // initialized tracking ensures the bytes are never de-initialized.
+        let buf = unsafe { &mut *(buf as *mut [u8] as *mut [MaybeUninit<u8>]) };
+        Self {
+            buf,
+            filled: 0,
+            initialized,
+        }
+    }
+
+    /// Creates a new `ReadBuf` from a buffer that may be uninitialized.
+    #[inline]
+    pub fn uninit(buf: &'a mut [MaybeUninit<u8>]) -> Self {
+        Self {
+            buf,
+            filled: 0,
+            initialized: 0,
+        }
+    }
+
+    /// Returns the total capacity of the buffer.
+    #[inline]
+    pub fn capacity(&self) -> usize {
+        self.buf.len()
+    }
+
+    /// Returns a shared reference to the filled portion of the buffer.
+    #[inline]
+    pub fn filled(&self) -> &[u8] {
+        // SAFETY: the filled region is always initialized.
+        unsafe { slice_assume_init(&self.buf[..self.filled]) }
+    }
+
+    /// Returns a mutable reference to the filled portion of the buffer.
+    #[inline]
+    pub fn filled_mut(&mut self) -> &mut [u8] {
+        // SAFETY: the filled region is always initialized.
+        unsafe { slice_assume_init_mut(&mut self.buf[..self.filled]) }
+    }
+
+    /// Returns a shared reference to the initialized portion of the buffer.
+    ///
+    /// This includes the filled portion.
+    #[inline]
+    pub fn initialized(&self) -> &[u8] {
+        // SAFETY: `initialized` only ever covers initialized bytes.
+        unsafe { slice_assume_init(&self.buf[..self.initialized]) }
+    }
+
+    /// Returns a mutable reference to the unfilled part of the buffer without
+    /// ensuring that it has been fully initialized.
+    ///
+    /// # Safety
+    ///
+    /// The caller must not de-initialize portions of the buffer that have
+    /// already been initialized, for example by writing
+    /// [`MaybeUninit::uninit()`] into them.
+    #[inline]
+    pub unsafe fn unfilled_mut(&mut self) -> &mut [MaybeUninit<u8>] {
+        &mut self.buf[self.filled..]
+    }
+
+    /// Returns a mutable reference to the unfilled part of the buffer, ensuring
+    /// it is fully initialized.
+    ///
+    /// Since `ReadBuf` tracks the region of the buffer that has been
+    /// initialized, this is effectively "free" after the first use.
+    #[inline]
+    pub fn initialize_unfilled(&mut self) -> &mut [u8] {
+        self.initialize_unfilled_to(self.remaining())
+    }
+
+    /// Returns a mutable reference to the first `n` bytes of the unfilled part
+    /// of the buffer, ensuring it is fully initialized.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `self.remaining()` is less than `n`.
+    #[inline]
+    pub fn initialize_unfilled_to(&mut self, n: usize) -> &mut [u8] {
+        assert!(self.remaining() >= n, "n overflows remaining");
+
+        let end = self.filled + n;
+        if self.initialized < end {
+            for byte in &mut self.buf[self.initialized..end] {
+                *byte = MaybeUninit::new(0);
+            }
+            self.initialized = end;
+        }
+
+        // SAFETY: everything up to `end` was just initialized.
+        unsafe { slice_assume_init_mut(&mut self.buf[self.filled..end]) }
+    }
+
+    /// Returns the number of bytes at the end of the slice that have not yet
+    /// been filled.
+    #[inline]
+    pub fn remaining(&self) -> usize {
+        self.capacity() - self.filled
+    }
+
+    /// Clears the buffer, resetting the filled region to empty.
+    ///
+    /// The number of initialized bytes is not changed, and the contents of the
+    /// buffer are not modified.
+    #[inline]
+    pub fn clear(&mut self) {
+        self.filled = 0;
+    }
+
+    /// Advances the size of the filled region of the buffer.
+    ///
+    /// The number of initialized bytes is not changed.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the filled region of the buffer would become larger than the
+    /// initialized region.
+    #[inline]
+    pub fn advance(&mut self, n: usize) {
+        let new = self.filled.checked_add(n).expect("filled overflow");
+        self.set_filled(new);
+    }
+
+    /// Sets the size of the filled region of the buffer.
+    ///
+    /// The number of initialized bytes is not changed.
+    ///
+    /// Note that this can be used to *shrink* the filled region of the buffer
+    /// in addition to growing it (for example, by a `Read` implementation that
+    /// compresses data in-place).
+    ///
+    /// # Panics
+    ///
+    /// Panics if the filled region of the buffer would become larger than the
+    /// initialized region.
+    #[inline]
+    pub fn set_filled(&mut self, n: usize) {
+        assert!(
+            n <= self.initialized,
+            "filled must not become larger than initialized"
+        );
+        self.filled = n;
+    }
+
+    /// Asserts that the first `n` unfilled bytes of the buffer are initialized.
+    ///
+    /// `ReadBuf` assumes that bytes are never de-initialized, so this method
+    /// does nothing when called with fewer bytes than are already known to be
+    /// initialized.
+    ///
+    /// # Safety
+    ///
+    /// The caller must ensure that `n` unfilled bytes of the buffer have
+    /// already been initialized.
+    #[inline]
+    pub unsafe fn assume_init(&mut self, n: usize) {
+        let new = self.filled + n;
+        if new > self.initialized {
+            self.initialized = new;
+        }
+    }
+
+    /// Appends data to the buffer, advancing the written position and possibly
+    /// also the initialized position.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `self.remaining()` is less than `buf.len()`.
+    #[inline]
+    pub fn put_slice(&mut self, buf: &[u8]) {
+        assert!(
+            self.remaining() >= buf.len(),
+            "buf.len() must fit in remaining()"
+        );
+
+        let amt = buf.len();
+        let end = self.filled + amt;
+
+        // SAFETY: `&[u8]` and `&[MaybeUninit<u8>]` have the same layout.
+        let src = unsafe { &*(buf as *const [u8] as *const [MaybeUninit<u8>]) };
+        self.buf[self.filled..end].copy_from_slice(src);
+
+        if self.initialized < end {
+            self.initialized = end;
+        }
+        self.filled = end;
+    }
+
+    /// Returns a new `ReadBuf` comprised of the unfilled section, up to `n` bytes.
+    ///
+    /// Bytes filled into the returned buffer are not reflected in `self`; use
+    /// [`advance`](ReadBuf::advance) afterwards.
+    #[inline]
+    pub fn take(&mut self, n: usize) -> ReadBuf<'_> {
+        let max = cmp::min(self.remaining(), n);
+        let initialized = self.initialized.saturating_sub(self.filled);
+        ReadBuf {
+            buf: &mut self.buf[self.filled..self.filled + max],
+            filled: 0,
+            initialized: cmp::min(initialized, max),
+        }
+    }
+}
+
+impl fmt::Debug for ReadBuf<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ReadBuf")
+            .field("filled", &self.filled)
+            .field("initialized", &self.initialized)
+            .field("capacity", &self.capacity())
+            .finish()
+    }
+}
+
+unsafe fn slice_assume_init(slice: &[MaybeUninit<u8>]) -> &[u8] {
+    &*(slice as *const [MaybeUninit<u8>] as *const [u8])
+}
+
+unsafe fn slice_assume_init_mut(slice: &mut [MaybeUninit<u8>]) -> &mut [u8] {
+    &mut *(slice as *mut [MaybeUninit<u8>] as *mut [u8])
+}
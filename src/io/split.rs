@@ -0,0 +1,432 @@
+use core::{
+    fmt,
+    marker::PhantomData,
+    pin::Pin,
+    ptr::NonNull,
+    task::{Context, Poll},
+};
+
+use crate::io::{AsyncRead, AsyncWrite, Read, ReadBuf, Write};
+
+/// Splits a pinned stream into a read half and a write half that borrow it.
+///
+/// Unlike the halves from [`split`], these don't own the stream, so no
+/// allocation or locking is needed. In exchange, they can't outlive the
+/// borrow, can't be sent to or shared with other threads, and must be used
+/// from the thread (or interrupt priority level) that split the stream. This
+/// makes them suitable for single-core `no_std` executors.
+///
+/// The halves implement [`AsyncRead`] and [`AsyncWrite`] for any stream, but
+/// [`Read`] and [`Write`] only for streams that are [`Unpin`], since those
+/// traits take the stream by plain reference.
+///
+/// # Examples
+///
+/// ```
+/// use core::pin::Pin;
+/// use coreplus::io::{split_local, Read, Write};
+///
+/// struct Loopback([u8; 4], usize);
+///
+/// impl Read for Loopback {
+///     type Error = core::convert::Infallible;
+///     fn read(&mut self, buf: &mut [u8]) -> Result<usize, Self::Error> {
+///         let n = self.1.min(buf.len());
+///         buf[..n].copy_from_slice(&self.0[..n]);
+///         Ok(n)
+///     }
+/// }
+///
+/// impl Write for Loopback {
+///     type Error = core::convert::Infallible;
+///     fn write(&mut self, buf: &[u8]) -> Result<usize, Self::Error> {
+///         let n = buf.len().min(4);
+///         self.0[..n].copy_from_slice(&buf[..n]);
+///         self.1 = n;
+///         Ok(n)
+///     }
+///     fn flush(&mut self) -> Result<(), Self::Error> { Ok(()) }
+/// }
+///
+/// let mut stream = Loopback([0; 4], 0);
+/// let (mut rx, mut tx) = split_local(Pin::new(&mut stream));
+/// tx.write_all(b"ping").unwrap();
+///
+/// let mut buf = [0; 4];
+/// assert_eq!(rx.read(&mut buf), Ok(4));
+/// assert_eq!(&buf, b"ping");
+///
+/// assert!(rx.reunite(tx).is_ok());
+/// ```
+pub fn split_local<T: ?Sized>(
+    stream: Pin<&mut T>,
+) -> (LocalReadHalf<'_, T>, LocalWriteHalf<'_, T>) {
+    // SAFETY: the stream is never moved through this pointer; each half
+    // re-pins it before use.
+    let ptr = NonNull::from(unsafe { stream.get_unchecked_mut() });
+    (
+        LocalReadHalf {
+            ptr,
+            _marker: PhantomData,
+        },
+        LocalWriteHalf {
+            ptr,
+            _marker: PhantomData,
+        },
+    )
+}
+
+// `*mut ()` keeps the halves `!Send` and `!Sync`, which is what makes sharing
+// the stream between them without a lock sound.
+type HalfMarker<'a, T> = PhantomData<(&'a mut T, *mut ())>;
+
+/// The readable half of a stream, created by [`split_local`].
+pub struct LocalReadHalf<'a, T: ?Sized> {
+    ptr: NonNull<T>,
+    _marker: HalfMarker<'a, T>,
+}
+
+/// The writable half of a stream, created by [`split_local`].
+pub struct LocalWriteHalf<'a, T: ?Sized> {
+    ptr: NonNull<T>,
+    _marker: HalfMarker<'a, T>,
+}
+
+impl<'a, T: ?Sized> LocalReadHalf<'a, T> {
+    /// Returns `true` if `other` was split from the same stream as `self`.
+    pub fn is_pair_of(&self, other: &LocalWriteHalf<'a, T>) -> bool {
+        self.ptr.cast::<u8>() == other.ptr.cast::<u8>()
+    }
+
+    /// Reunites this half with its write half, returning the original stream.
+    ///
+    /// Fails if the halves weren't split from the same stream.
+    pub fn reunite(
+        self,
+        other: LocalWriteHalf<'a, T>,
+    ) -> Result<Pin<&'a mut T>, ReuniteError<Self, LocalWriteHalf<'a, T>>> {
+        if self.is_pair_of(&other) {
+            // SAFETY: both halves are consumed, so this is the only
+            // reference left, and the stream was pinned when it was split.
+            Ok(unsafe { Pin::new_unchecked(&mut *self.ptr.as_ptr()) })
+        } else {
+            Err(ReuniteError(self, other))
+        }
+    }
+
+    fn stream(&mut self) -> Pin<&mut T> {
+        // SAFETY: the halves can't leave this thread and neither hands out a
+        // reference that outlives a single call, so this never aliases.
+        unsafe { Pin::new_unchecked(&mut *self.ptr.as_ptr()) }
+    }
+}
+
+impl<'a, T: ?Sized> LocalWriteHalf<'a, T> {
+    /// Returns `true` if `other` was split from the same stream as `self`.
+    pub fn is_pair_of(&self, other: &LocalReadHalf<'a, T>) -> bool {
+        other.is_pair_of(self)
+    }
+
+    /// Reunites this half with its read half, returning the original stream.
+    ///
+    /// Fails if the halves weren't split from the same stream.
+    pub fn reunite(
+        self,
+        other: LocalReadHalf<'a, T>,
+    ) -> Result<Pin<&'a mut T>, ReuniteError<LocalReadHalf<'a, T>, Self>> {
+        other.reunite(self)
+    }
+
+    fn stream(&mut self) -> Pin<&mut T> {
+        // SAFETY: see `LocalReadHalf::stream`.
+        unsafe { Pin::new_unchecked(&mut *self.ptr.as_ptr()) }
+    }
+}
+
+impl<T: ?Sized> fmt::Debug for LocalReadHalf<'_, T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("LocalReadHalf").finish()
+    }
+}
+
+impl<T: ?Sized> fmt::Debug for LocalWriteHalf<'_, T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("LocalWriteHalf").finish()
+    }
+}
+
+impl<T: AsyncRead + ?Sized> AsyncRead for LocalReadHalf<'_, T> {
+    type Error = T::Error;
+
+    fn poll_read(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut [u8],
+    ) -> Poll<Result<usize, Self::Error>> {
+        self.stream().poll_read(cx, buf)
+    }
+
+    fn poll_read_buf(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut ReadBuf<'_>,
+    ) -> Poll<Result<(), Self::Error>> {
+        self.stream().poll_read_buf(cx, buf)
+    }
+}
+
+impl<T: AsyncWrite + ?Sized> AsyncWrite for LocalWriteHalf<'_, T> {
+    type Error = T::Error;
+
+    fn poll_write(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<Result<usize, Self::Error>> {
+        self.stream().poll_write(cx, buf)
+    }
+
+    fn poll_flush(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.stream().poll_flush(cx)
+    }
+
+    fn poll_close(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.stream().poll_close(cx)
+    }
+}
+
+impl<T: Read + Unpin + ?Sized> Read for LocalReadHalf<'_, T> {
+    type Error = T::Error;
+
+    fn read(&mut self, buf: &mut [u8]) -> Result<usize, Self::Error> {
+        self.stream().get_mut().read(buf)
+    }
+
+    fn read_buf(&mut self, buf: &mut ReadBuf<'_>) -> Result<(), Self::Error> {
+        self.stream().get_mut().read_buf(buf)
+    }
+}
+
+impl<T: Write + Unpin + ?Sized> Write for LocalWriteHalf<'_, T> {
+    type Error = T::Error;
+
+    fn write(&mut self, buf: &[u8]) -> Result<usize, Self::Error> {
+        self.stream().get_mut().write(buf)
+    }
+
+    fn flush(&mut self) -> Result<(), Self::Error> {
+        self.stream().get_mut().flush()
+    }
+}
+
+/// Error returned when trying to reunite two halves that weren't split from
+/// the same stream.
+///
+/// The halves are handed back unchanged.
+pub struct ReuniteError<R, W>(pub R, pub W);
+
+impl<R, W> fmt::Debug for ReuniteError<R, W> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("ReuniteError").field(&"..").finish()
+    }
+}
+
+impl<R, W> fmt::Display for ReuniteError<R, W> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("tried to reunite halves that are not from the same stream")
+    }
+}
+
+impl<R, W> crate::error::Error for ReuniteError<R, W> {}
+
+crate::error::impl_foreign_error!(impl<R, W> for ReuniteError<R, W>);
+
+#[cfg(feature = "std")]
+mod sync {
+    use std::sync::{Arc, Mutex, MutexGuard};
+
+    use super::*;
+
+    /// Splits a stream into a read half and a write half that each own a
+    /// share of it.
+    ///
+    /// The halves can be moved to different tasks or threads, and
+    /// [`reunite`](ReadHalf::reunite) gives the stream back once both are
+    /// done. Each operation locks a mutex around the stream, so a blocking
+    /// read holds up the write half until it returns. Without `std`, or on a
+    /// single core, the lock-free [`split_local`] splits a pinned borrow of
+    /// the stream instead.
+    ///
+    /// This function is available when the `std` feature is enabled.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use coreplus::io::{split, Read, Write};
+    /// use std::thread;
+    /// # struct Loopback([u8; 4], usize);
+    /// # impl Read for Loopback {
+    /// #     type Error = core::convert::Infallible;
+    /// #     fn read(&mut self, buf: &mut [u8]) -> Result<usize, Self::Error> {
+    /// #         let n = self.1.min(buf.len());
+    /// #         buf[..n].copy_from_slice(&self.0[..n]);
+    /// #         Ok(n)
+    /// #     }
+    /// # }
+    /// # impl Write for Loopback {
+    /// #     type Error = core::convert::Infallible;
+    /// #     fn write(&mut self, buf: &[u8]) -> Result<usize, Self::Error> {
+    /// #         let n = buf.len().min(4);
+    /// #         self.0[..n].copy_from_slice(&buf[..n]);
+    /// #         self.1 = n;
+    /// #         Ok(n)
+    /// #     }
+    /// #     fn flush(&mut self) -> Result<(), Self::Error> { Ok(()) }
+    /// # }
+    ///
+    /// // `Loopback` reads back whatever was last written to it, as in the
+    /// // `split_local` example.
+    /// let (mut rx, mut tx) = split(Loopback([0; 4], 0));
+    /// let writer = thread::spawn(move || {
+    ///     tx.write_all(b"ping").unwrap();
+    ///     tx
+    /// });
+    /// let tx = writer.join().unwrap();
+    ///
+    /// let mut buf = [0; 4];
+    /// assert_eq!(rx.read(&mut buf), Ok(4));
+    /// assert_eq!(&buf, b"ping");
+    ///
+    /// assert!(rx.reunite(tx).is_ok());
+    /// ```
+    pub fn split<T: Unpin>(stream: T) -> (ReadHalf<T>, WriteHalf<T>) {
+        let shared = Arc::new(Mutex::new(stream));
+        (ReadHalf(shared.clone()), WriteHalf(shared))
+    }
+
+    /// The readable half of a stream, created by [`split`].
+    pub struct ReadHalf<T>(Arc<Mutex<T>>);
+
+    /// The writable half of a stream, created by [`split`].
+    pub struct WriteHalf<T>(Arc<Mutex<T>>);
+
+    fn lock<T>(shared: &Mutex<T>) -> MutexGuard<'_, T> {
+        // A panic in the other half doesn't leave the stream itself in a
+        // state we could make worse.
+        shared.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    impl<T> ReadHalf<T> {
+        /// Returns `true` if `other` was split from the same stream as `self`.
+        pub fn is_pair_of(&self, other: &WriteHalf<T>) -> bool {
+            Arc::ptr_eq(&self.0, &other.0)
+        }
+
+        /// Reunites this half with its write half, returning the original stream.
+        ///
+        /// Fails if the halves weren't split from the same stream.
+        pub fn reunite(self, other: WriteHalf<T>) -> Result<T, ReuniteError<Self, WriteHalf<T>>> {
+            if self.is_pair_of(&other) {
+                drop(other);
+                let mutex = Arc::try_unwrap(self.0)
+                    .unwrap_or_else(|_| unreachable!("both halves were consumed"));
+                Ok(mutex.into_inner().unwrap_or_else(|e| e.into_inner()))
+            } else {
+                Err(ReuniteError(self, other))
+            }
+        }
+    }
+
+    impl<T> WriteHalf<T> {
+        /// Returns `true` if `other` was split from the same stream as `self`.
+        pub fn is_pair_of(&self, other: &ReadHalf<T>) -> bool {
+            other.is_pair_of(self)
+        }
+
+        /// Reunites this half with its read half, returning the original stream.
+        ///
+        /// Fails if the halves weren't split from the same stream.
+        pub fn reunite(self, other: ReadHalf<T>) -> Result<T, ReuniteError<ReadHalf<T>, Self>> {
+            other.reunite(self)
+        }
+    }
+
+    impl<T> fmt::Debug for ReadHalf<T> {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            f.debug_struct("ReadHalf").finish()
+        }
+    }
+
+    impl<T> fmt::Debug for WriteHalf<T> {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            f.debug_struct("WriteHalf").finish()
+        }
+    }
+
+    impl<T: AsyncRead + Unpin> AsyncRead for ReadHalf<T> {
+        type Error = T::Error;
+
+        fn poll_read(
+            self: Pin<&mut Self>,
+            cx: &mut Context<'_>,
+            buf: &mut [u8],
+        ) -> Poll<Result<usize, Self::Error>> {
+            Pin::new(&mut *lock(&self.0)).poll_read(cx, buf)
+        }
+
+        fn poll_read_buf(
+            self: Pin<&mut Self>,
+            cx: &mut Context<'_>,
+            buf: &mut ReadBuf<'_>,
+        ) -> Poll<Result<(), Self::Error>> {
+            Pin::new(&mut *lock(&self.0)).poll_read_buf(cx, buf)
+        }
+    }
+
+    impl<T: AsyncWrite + Unpin> AsyncWrite for WriteHalf<T> {
+        type Error = T::Error;
+
+        fn poll_write(
+            self: Pin<&mut Self>,
+            cx: &mut Context<'_>,
+            buf: &[u8],
+        ) -> Poll<Result<usize, Self::Error>> {
+            Pin::new(&mut *lock(&self.0)).poll_write(cx, buf)
+        }
+
+        fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+            Pin::new(&mut *lock(&self.0)).poll_flush(cx)
+        }
+
+        fn poll_close(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+            Pin::new(&mut *lock(&self.0)).poll_close(cx)
+        }
+    }
+
+    impl<T: Read> Read for ReadHalf<T> {
+        type Error = T::Error;
+
+        fn read(&mut self, buf: &mut [u8]) -> Result<usize, Self::Error> {
+            lock(&self.0).read(buf)
+        }
+
+        fn read_buf(&mut self, buf: &mut ReadBuf<'_>) -> Result<(), Self::Error> {
+            lock(&self.0).read_buf(buf)
+        }
+    }
+
+    impl<T: Write> Write for WriteHalf<T> {
+        type Error = T::Error;
+
+        fn write(&mut self, buf: &[u8]) -> Result<usize, Self::Error> {
+            lock(&self.0).write(buf)
+        }
+
+        fn flush(&mut self) -> Result<(), Self::Error> {
+            lock(&self.0).flush()
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+pub use self::sync::*;
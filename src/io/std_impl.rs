@@ -1,4 +1,6 @@
-use crate::io::{Read, Write};
+use crate::io::{Read, Seek, SeekFrom, Write};
+#[cfg(unix)]
+use crate::io::{ReadAt, WriteAt};
 
 /// The bridge between [`std::io`] and [`crate::io`].
 pub struct CoreIO<T>(pub T);
@@ -22,3 +24,53 @@ impl<T: std::io::Write> Write for CoreIO<T> {
         self.0.flush()
     }
 }
+
+impl<T: std::io::Seek> Seek for CoreIO<T> {
+    type Error = std::io::Error;
+
+    fn seek(&mut self, pos: SeekFrom) -> Result<u64, Self::Error> {
+        self.0.seek(pos.into())
+    }
+}
+
+impl From<SeekFrom> for std::io::SeekFrom {
+    fn from(pos: SeekFrom) -> Self {
+        match pos {
+            SeekFrom::Start(n) => std::io::SeekFrom::Start(n),
+            SeekFrom::End(n) => std::io::SeekFrom::End(n),
+            SeekFrom::Current(n) => std::io::SeekFrom::Current(n),
+        }
+    }
+}
+
+impl From<std::io::SeekFrom> for SeekFrom {
+    fn from(pos: std::io::SeekFrom) -> Self {
+        match pos {
+            std::io::SeekFrom::Start(n) => SeekFrom::Start(n),
+            std::io::SeekFrom::End(n) => SeekFrom::End(n),
+            std::io::SeekFrom::Current(n) => SeekFrom::Current(n),
+        }
+    }
+}
+
+#[cfg(unix)]
+impl ReadAt for CoreIO<std::fs::File> {
+    type Error = std::io::Error;
+
+    fn read_at(&self, buf: &mut [u8], offset: u64) -> Result<usize, Self::Error> {
+        std::os::unix::fs::FileExt::read_at(&self.0, buf, offset)
+    }
+}
+
+#[cfg(unix)]
+impl WriteAt for CoreIO<std::fs::File> {
+    type Error = std::io::Error;
+
+    fn write_at(&self, buf: &[u8], offset: u64) -> Result<usize, Self::Error> {
+        std::os::unix::fs::FileExt::write_at(&self.0, buf, offset)
+    }
+
+    fn flush(&self) -> Result<(), Self::Error> {
+        std::io::Write::flush(&mut &self.0)
+    }
+}
@@ -0,0 +1,300 @@
+use core::{
+    cmp,
+    future::Future,
+    pin::Pin,
+    task::{Context, Poll},
+    time::Duration,
+};
+
+use crate::{
+    io::{AsyncRead, AsyncWrite},
+    time::{Instant, Timer},
+};
+
+const NANOS_PER_SEC: u128 = 1_000_000_000;
+
+/// A token bucket.
+struct Bucket<I> {
+    bytes_per_second: u64,
+    capacity: u64,
+    tokens: u64,
+    last: Option<I>,
+}
+
+impl<I: Instant> Bucket<I> {
+    fn new(bytes_per_second: u64, capacity: u64) -> Self {
+        assert!(bytes_per_second != 0, "throttle rate must be non-zero");
+        assert!(capacity != 0, "throttle burst must be non-zero");
+        Self {
+            bytes_per_second,
+            capacity,
+            tokens: capacity,
+            last: None,
+        }
+    }
+
+    fn refill(&mut self, now: I) {
+        let last = match self.last {
+            Some(last) => last,
+            None => {
+                self.last = Some(now);
+                return;
+            }
+        };
+
+        let elapsed = now.saturating_duration_since(last).as_nanos();
+        let added = elapsed * self.bytes_per_second as u128 / NANOS_PER_SEC;
+        let room = (self.capacity - self.tokens) as u128;
+        if added >= room {
+            self.tokens = self.capacity;
+            self.last = Some(now);
+        } else {
+            self.tokens += added as u64;
+            // Only advance by the time that was actually converted into
+            // tokens, so fractional tokens aren't lost between calls.
+            let used = added * NANOS_PER_SEC / self.bytes_per_second as u128;
+            self.last = Some(
+                last.checked_add(Duration::from_nanos(used as u64))
+                    .unwrap_or(now),
+            );
+        }
+    }
+
+    /// How long until `want` tokens are available.
+    fn wait_for(&self, want: u64) -> Duration {
+        let missing = want.saturating_sub(self.tokens) as u128;
+        let rate = self.bytes_per_second as u128;
+        let nanos = missing * NANOS_PER_SEC;
+        // Round up so the wait covers the last fraction of a token too.
+        let nanos = nanos / rate + (nanos % rate != 0) as u128;
+        Duration::from_nanos(nanos as u64)
+    }
+
+    /// Waits until at least one token is available and returns how many bytes
+    /// may be transferred, up to `want`.
+    fn poll_acquire<T: Timer<Instant = I>>(
+        &mut self,
+        timer: &T,
+        mut sleep: Pin<&mut Option<T::Sleep>>,
+        cx: &mut Context<'_>,
+        want: usize,
+    ) -> Poll<usize> {
+        if want == 0 {
+            return Poll::Ready(0);
+        }
+
+        loop {
+            if let Some(s) = sleep.as_mut().as_pin_mut() {
+                if s.poll(cx).is_pending() {
+                    return Poll::Pending;
+                }
+                sleep.set(None);
+            }
+
+            let now = timer.now();
+            self.refill(now);
+            if self.tokens != 0 {
+                return Poll::Ready(cmp::min(self.tokens, want as u64) as usize);
+            }
+
+            // Wait for enough tokens to satisfy the whole request (or a full
+            // bucket), rather than waking up for every single byte.
+            // The tokens were counted as of `last`, not `now`.
+            let target = cmp::min(want as u64, self.capacity);
+            let last = self.last.unwrap_or(now);
+            let deadline = last.checked_add(self.wait_for(target)).unwrap_or(now);
+            sleep.set(Some(timer.sleep_until(deadline)));
+        }
+    }
+
+    fn consume(&mut self, n: usize) {
+        self.tokens = self.tokens.saturating_sub(n as u64);
+    }
+}
+
+/// Limits the bandwidth of the wrapped object with a token bucket.
+///
+/// Reads and writes are limited independently, each to `bytes_per_second`
+/// on average, with bursts of up to `burst` bytes. Waiting is done with a
+/// [`Timer`], so this works with any executor.
+///
+/// # Examples
+///
+/// ```
+/// # #[cfg(feature = "alloc")] {
+/// use core::{pin::Pin, task::Poll, time::Duration};
+/// use coreplus::io::{repeat, sink, AsyncRead, AsyncWrite, Throttle};
+/// use coreplus::task::{block_on, poll_fn};
+/// use coreplus::time::ManualClock;
+///
+/// let clock = ManualClock::new();
+/// let mut reader = Throttle::new(repeat(b'x'), clock.clone(), 4);
+/// let mut writer = Throttle::new(sink(), clock.clone(), 4);
+/// let mut buf = [0; 16];
+///
+/// // Polls once, without waiting for the clock.
+/// let mut read = || {
+///     block_on(poll_fn(|cx| Poll::Ready(Pin::new(&mut reader).poll_read(cx, &mut buf))))
+/// };
+/// let mut write = || {
+///     block_on(poll_fn(|cx| Poll::Ready(Pin::new(&mut writer).poll_write(cx, b"01234567"))))
+/// };
+///
+/// // The first second's worth of bytes goes straight through, then both
+/// // directions have to wait for the clock.
+/// assert_eq!(read(), Poll::Ready(Ok(4)));
+/// assert_eq!(write(), Poll::Ready(Ok(4)));
+/// assert_eq!(read(), Poll::Pending);
+/// assert_eq!(write(), Poll::Pending);
+///
+/// // Half a second only refills half the bucket, which isn't enough to
+/// // wake a full-sized read or write.
+/// clock.advance(Duration::from_millis(500));
+/// assert_eq!(read(), Poll::Pending);
+/// assert_eq!(write(), Poll::Pending);
+///
+/// clock.advance(Duration::from_millis(500));
+/// assert_eq!(read(), Poll::Ready(Ok(4)));
+/// assert_eq!(write(), Poll::Ready(Ok(4)));
+/// # }
+/// ```
+pub struct Throttle<S, T: Timer> {
+    inner: S,
+    timer: T,
+    read: Bucket<T::Instant>,
+    read_sleep: Option<T::Sleep>,
+    write: Bucket<T::Instant>,
+    write_sleep: Option<T::Sleep>,
+}
+
+impl<S, T: Timer> Throttle<S, T> {
+    /// Wraps `inner`, limiting both directions to `bytes_per_second` with a
+    /// burst of one second's worth of bytes.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `bytes_per_second` is zero.
+    pub fn new(inner: S, timer: T, bytes_per_second: u64) -> Self {
+        Self::with_burst(inner, timer, bytes_per_second, bytes_per_second)
+    }
+
+    /// Wraps `inner`, limiting both directions to `bytes_per_second` with
+    /// bursts of up to `burst` bytes.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `bytes_per_second` or `burst` is zero.
+    pub fn with_burst(inner: S, timer: T, bytes_per_second: u64, burst: u64) -> Self {
+        Self {
+            inner,
+            timer,
+            read: Bucket::new(bytes_per_second, burst),
+            read_sleep: None,
+            write: Bucket::new(bytes_per_second, burst),
+            write_sleep: None,
+        }
+    }
+
+    /// Gets a reference to the underlying object.
+    pub fn get_ref(&self) -> &S {
+        &self.inner
+    }
+
+    /// Gets a mutable reference to the underlying object.
+    pub fn get_mut(&mut self) -> &mut S {
+        &mut self.inner
+    }
+
+    /// Gets a pinned mutable reference to the underlying object.
+    pub fn get_pin_mut(self: Pin<&mut Self>) -> Pin<&mut S> {
+        self.project().inner
+    }
+
+    /// Unwraps this `Throttle`, returning the underlying object.
+    pub fn into_inner(self) -> S {
+        self.inner
+    }
+
+    fn project(self: Pin<&mut Self>) -> ThrottleProj<'_, S, T> {
+        // SAFETY: `inner` and the sleep futures are structurally pinned and
+        // never moved out of a pinned `Throttle`. Nothing else is pinned.
+        unsafe {
+            let this = self.get_unchecked_mut();
+            ThrottleProj {
+                inner: Pin::new_unchecked(&mut this.inner),
+                timer: &this.timer,
+                read: &mut this.read,
+                read_sleep: Pin::new_unchecked(&mut this.read_sleep),
+                write: &mut this.write,
+                write_sleep: Pin::new_unchecked(&mut this.write_sleep),
+            }
+        }
+    }
+}
+
+struct ThrottleProj<'a, S, T: Timer> {
+    inner: Pin<&'a mut S>,
+    timer: &'a T,
+    read: &'a mut Bucket<T::Instant>,
+    read_sleep: Pin<&'a mut Option<T::Sleep>>,
+    write: &'a mut Bucket<T::Instant>,
+    write_sleep: Pin<&'a mut Option<T::Sleep>>,
+}
+
+impl<S: AsyncRead, T: Timer> AsyncRead for Throttle<S, T> {
+    type Error = S::Error;
+
+    fn poll_read(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut [u8],
+    ) -> Poll<Result<usize, Self::Error>> {
+        let this = self.project();
+        let allowed = match this
+            .read
+            .poll_acquire(this.timer, this.read_sleep, cx, buf.len())
+        {
+            Poll::Ready(allowed) => allowed,
+            Poll::Pending => return Poll::Pending,
+        };
+
+        let poll = this.inner.poll_read(cx, &mut buf[..allowed]);
+        if let Poll::Ready(Ok(n)) = poll {
+            this.read.consume(n);
+        }
+        poll
+    }
+}
+
+impl<S: AsyncWrite, T: Timer> AsyncWrite for Throttle<S, T> {
+    type Error = S::Error;
+
+    fn poll_write(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<Result<usize, Self::Error>> {
+        let this = self.project();
+        let allowed = match this
+            .write
+            .poll_acquire(this.timer, this.write_sleep, cx, buf.len())
+        {
+            Poll::Ready(allowed) => allowed,
+            Poll::Pending => return Poll::Pending,
+        };
+
+        let poll = this.inner.poll_write(cx, &buf[..allowed]);
+        if let Poll::Ready(Ok(n)) = poll {
+            this.write.consume(n);
+        }
+        poll
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.project().inner.poll_flush(cx)
+    }
+
+    fn poll_close(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.project().inner.poll_close(cx)
+    }
+}
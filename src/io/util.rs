@@ -0,0 +1,608 @@
+use core::{
+    convert::Infallible,
+    fmt,
+    future::Future,
+    pin::Pin,
+    task::{Context, Poll},
+};
+
+use crate::{
+    error::{impl_foreign_error, Error},
+    io::{AsyncRead, AsyncWrite, Read, Write},
+};
+
+/// A writer which will move data into the void.
+///
+/// This struct is generally created by calling [`sink`].
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Sink;
+
+/// Creates an instance of a writer which will successfully consume all data.
+///
+/// # Examples
+///
+/// ```
+/// use coreplus::io::{sink, Write};
+///
+/// let mut writer = sink();
+/// assert_eq!(writer.write(b"hello"), Ok(5));
+/// ```
+pub const fn sink() -> Sink {
+    Sink
+}
+
+impl Write for Sink {
+    type Error = Infallible;
+
+    fn write(&mut self, buf: &[u8]) -> Result<usize, Self::Error> {
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> Result<(), Self::Error> {
+        Ok(())
+    }
+}
+
+impl AsyncWrite for Sink {
+    type Error = Infallible;
+
+    fn poll_write(
+        self: Pin<&mut Self>,
+        _cx: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<Result<usize, Self::Error>> {
+        Poll::Ready(Ok(buf.len()))
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        Poll::Ready(Ok(()))
+    }
+
+    fn poll_close(self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        Poll::Ready(Ok(()))
+    }
+}
+
+/// A reader which is always at EOF.
+///
+/// This struct is generally created by calling [`empty`].
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Empty;
+
+/// Creates an instance of a reader which is always at EOF.
+///
+/// # Examples
+///
+/// ```
+/// use coreplus::io::{empty, Read};
+///
+/// let mut buf = [0; 4];
+/// assert_eq!(empty().read(&mut buf), Ok(0));
+/// ```
+pub const fn empty() -> Empty {
+    Empty
+}
+
+impl Read for Empty {
+    type Error = Infallible;
+
+    fn read(&mut self, _buf: &mut [u8]) -> Result<usize, Self::Error> {
+        Ok(0)
+    }
+}
+
+impl AsyncRead for Empty {
+    type Error = Infallible;
+
+    fn poll_read(
+        self: Pin<&mut Self>,
+        _cx: &mut Context<'_>,
+        _buf: &mut [u8],
+    ) -> Poll<Result<usize, Self::Error>> {
+        Poll::Ready(Ok(0))
+    }
+}
+
+/// A reader which yields one byte over and over.
+///
+/// This struct is generally created by calling [`repeat`].
+#[derive(Clone, Copy, Debug)]
+pub struct Repeat {
+    byte: u8,
+}
+
+/// Creates an instance of a reader that infinitely repeats one byte.
+///
+/// # Examples
+///
+/// ```
+/// use coreplus::io::{repeat, Read};
+///
+/// let mut buf = [0; 3];
+/// repeat(0b101).read(&mut buf).unwrap();
+/// assert_eq!(buf, [0b101, 0b101, 0b101]);
+/// ```
+pub const fn repeat(byte: u8) -> Repeat {
+    Repeat { byte }
+}
+
+impl Read for Repeat {
+    type Error = Infallible;
+
+    fn read(&mut self, buf: &mut [u8]) -> Result<usize, Self::Error> {
+        for slot in &mut *buf {
+            *slot = self.byte;
+        }
+        Ok(buf.len())
+    }
+}
+
+impl AsyncRead for Repeat {
+    type Error = Infallible;
+
+    fn poll_read(
+        mut self: Pin<&mut Self>,
+        _cx: &mut Context<'_>,
+        buf: &mut [u8],
+    ) -> Poll<Result<usize, Self::Error>> {
+        Poll::Ready(self.read(buf))
+    }
+}
+
+/// The error returned by [`copy`] and [`copy_async`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CopyError<R, W> {
+    /// Reading from the reader failed.
+    Read(R),
+    /// Writing to or flushing the writer failed.
+    Write(W),
+    /// The writer accepted zero bytes, so the copy can't make progress.
+    WriteZero,
+}
+
+impl<R, W> fmt::Display for CopyError<R, W> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            CopyError::Read(_) => "error reading",
+            CopyError::Write(_) => "error writing",
+            CopyError::WriteZero => "failed to write whole buffer",
+        })
+    }
+}
+
+impl<R: Error + 'static, W: Error + 'static> Error for CopyError<R, W> {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            CopyError::Read(e) => Some(e),
+            CopyError::Write(e) => Some(e),
+            CopyError::WriteZero => None,
+        }
+    }
+}
+
+impl_foreign_error! {
+    impl<R, W> for CopyError<R, W>;
+    fn source(&self) {
+        match self {
+            CopyError::Read(e) => Some(e),
+            CopyError::Write(e) => Some(e),
+            CopyError::WriteZero => None,
+        }
+    }
+}
+
+/// Copies the entire contents of a reader into a writer.
+///
+/// `buf` is used as intermediate storage, so its size determines how much is
+/// read at a time. On success, the total number of bytes copied is returned.
+///
+/// The reader and writer may have different error types; [`CopyError`] says
+/// which side failed.
+///
+/// # Panics
+///
+/// Panics if `buf` is empty.
+///
+/// # Examples
+///
+/// ```
+/// use coreplus::io::copy;
+///
+/// let mut reader: &[u8] = b"hello";
+/// let mut out = [0; 8];
+/// let mut writer = &mut out[..];
+/// let mut buf = [0; 2];
+///
+/// assert_eq!(copy(&mut reader, &mut writer, &mut buf), Ok(5));
+/// assert_eq!(&out[..5], b"hello");
+/// ```
+pub fn copy<R, W>(
+    reader: &mut R,
+    writer: &mut W,
+    buf: &mut [u8],
+) -> Result<u64, CopyError<R::Error, W::Error>>
+where
+    R: Read + ?Sized,
+    W: Write + ?Sized,
+{
+    assert!(!buf.is_empty(), "copy buffer must not be empty");
+
+    let mut amt = 0;
+    loop {
+        let n = reader.read(buf).map_err(CopyError::Read)?;
+        if n == 0 {
+            break;
+        }
+
+        let mut data = &buf[..n];
+        while !data.is_empty() {
+            match writer.write(data).map_err(CopyError::Write)? {
+                0 => return Err(CopyError::WriteZero),
+                written => data = &data[written..],
+            }
+        }
+        amt += n as u64;
+    }
+    writer.flush().map_err(CopyError::Write)?;
+    Ok(amt)
+}
+
+type AsyncCopyResult<R, W> =
+    Result<u64, CopyError<<R as AsyncRead>::Error, <W as AsyncWrite>::Error>>;
+
+/// The state of one direction of an async copy.
+struct CopyBuffer<'a> {
+    buf: &'a mut [u8],
+    pos: usize,
+    cap: usize,
+    amt: u64,
+    read_done: bool,
+}
+
+impl<'a> CopyBuffer<'a> {
+    fn new(buf: &'a mut [u8]) -> Self {
+        assert!(!buf.is_empty(), "copy buffer must not be empty");
+        Self {
+            buf,
+            pos: 0,
+            cap: 0,
+            amt: 0,
+            read_done: false,
+        }
+    }
+
+    fn poll_copy<R, W>(
+        &mut self,
+        cx: &mut Context<'_>,
+        mut reader: Pin<&mut R>,
+        mut writer: Pin<&mut W>,
+    ) -> Poll<AsyncCopyResult<R, W>>
+    where
+        R: AsyncRead + ?Sized,
+        W: AsyncWrite + ?Sized,
+    {
+        loop {
+            if self.pos == self.cap && !self.read_done {
+                match reader.as_mut().poll_read(cx, self.buf) {
+                    Poll::Ready(Ok(0)) => self.read_done = true,
+                    Poll::Ready(Ok(n)) => {
+                        self.pos = 0;
+                        self.cap = n;
+                    }
+                    Poll::Ready(Err(e)) => return Poll::Ready(Err(CopyError::Read(e))),
+                    Poll::Pending => return Poll::Pending,
+                }
+            }
+
+            while self.pos < self.cap {
+                match writer
+                    .as_mut()
+                    .poll_write(cx, &self.buf[self.pos..self.cap])
+                {
+                    Poll::Ready(Ok(0)) => return Poll::Ready(Err(CopyError::WriteZero)),
+                    Poll::Ready(Ok(n)) => {
+                        self.pos += n;
+                        self.amt += n as u64;
+                    }
+                    Poll::Ready(Err(e)) => return Poll::Ready(Err(CopyError::Write(e))),
+                    Poll::Pending => return Poll::Pending,
+                }
+            }
+
+            if self.read_done {
+                return match writer.as_mut().poll_flush(cx) {
+                    Poll::Ready(Ok(())) => Poll::Ready(Ok(self.amt)),
+                    Poll::Ready(Err(e)) => Poll::Ready(Err(CopyError::Write(e))),
+                    Poll::Pending => Poll::Pending,
+                };
+            }
+        }
+    }
+}
+
+/// Future for the [`copy_async`] function.
+#[must_use = "futures do nothing unless you `.await` or poll them"]
+pub struct CopyFuture<'a, R: ?Sized, W: ?Sized> {
+    reader: &'a mut R,
+    writer: &'a mut W,
+    buf: CopyBuffer<'a>,
+}
+
+/// Asynchronously copies the entire contents of a reader into a writer.
+///
+/// This is the async version of [`copy`]. The returned future resolves to the
+/// number of bytes copied once the reader reaches EOF and the writer has been
+/// flushed.
+///
+/// # Panics
+///
+/// Panics if `buf` is empty.
+///
+/// # Examples
+///
+/// See [`copy_bidirectional`], whose example copies in one direction too.
+pub fn copy_async<'a, R, W>(
+    reader: &'a mut R,
+    writer: &'a mut W,
+    buf: &'a mut [u8],
+) -> CopyFuture<'a, R, W>
+where
+    R: AsyncRead + Unpin + ?Sized,
+    W: AsyncWrite + Unpin + ?Sized,
+{
+    CopyFuture {
+        reader,
+        writer,
+        buf: CopyBuffer::new(buf),
+    }
+}
+
+impl<R, W> Future for CopyFuture<'_, R, W>
+where
+    R: AsyncRead + Unpin + ?Sized,
+    W: AsyncWrite + Unpin + ?Sized,
+{
+    type Output = AsyncCopyResult<R, W>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let this = self.get_mut();
+        this.buf
+            .poll_copy(cx, Pin::new(&mut *this.reader), Pin::new(&mut *this.writer))
+    }
+}
+
+/// The error returned by [`copy_bidirectional`].
+///
+/// The variant says which direction failed, and the [`CopyError`] inside says
+/// whether it was the reading or the writing side.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CopyBidirectionalError<A, B> {
+    /// Copying from `a` to `b` failed.
+    AToB(CopyError<A, B>),
+    /// Copying from `b` to `a` failed.
+    BToA(CopyError<B, A>),
+}
+
+impl<A, B> fmt::Display for CopyBidirectionalError<A, B> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            CopyBidirectionalError::AToB(_) => "copying from a to b failed",
+            CopyBidirectionalError::BToA(_) => "copying from b to a failed",
+        })
+    }
+}
+
+impl<A: Error + 'static, B: Error + 'static> Error for CopyBidirectionalError<A, B> {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            CopyBidirectionalError::AToB(e) => Some(e),
+            CopyBidirectionalError::BToA(e) => Some(e),
+        }
+    }
+}
+
+impl_foreign_error! {
+    impl<A, B> for CopyBidirectionalError<A, B>;
+    fn source(&self) {
+        match self {
+            CopyBidirectionalError::AToB(e) => Some(e),
+            CopyBidirectionalError::BToA(e) => Some(e),
+        }
+    }
+}
+
+enum TransferState<'a> {
+    Running(CopyBuffer<'a>),
+    Closing(u64),
+    Done(u64),
+}
+
+impl TransferState<'_> {
+    fn poll_transfer<R, W>(
+        &mut self,
+        cx: &mut Context<'_>,
+        mut reader: Pin<&mut R>,
+        mut writer: Pin<&mut W>,
+    ) -> Poll<AsyncCopyResult<R, W>>
+    where
+        R: AsyncRead + ?Sized,
+        W: AsyncWrite + ?Sized,
+    {
+        loop {
+            match self {
+                TransferState::Running(buf) => {
+                    match buf.poll_copy(cx, reader.as_mut(), writer.as_mut()) {
+                        Poll::Ready(Ok(amt)) => *self = TransferState::Closing(amt),
+                        Poll::Ready(Err(e)) => return Poll::Ready(Err(e)),
+                        Poll::Pending => return Poll::Pending,
+                    }
+                }
+                TransferState::Closing(amt) => match writer.as_mut().poll_close(cx) {
+                    Poll::Ready(Ok(())) => *self = TransferState::Done(*amt),
+                    Poll::Ready(Err(e)) => return Poll::Ready(Err(CopyError::Write(e))),
+                    Poll::Pending => return Poll::Pending,
+                },
+                TransferState::Done(amt) => return Poll::Ready(Ok(*amt)),
+            }
+        }
+    }
+}
+
+/// Future for the [`copy_bidirectional`] function.
+#[must_use = "futures do nothing unless you `.await` or poll them"]
+pub struct CopyBidirectional<'a, A: ?Sized, B: ?Sized> {
+    a: &'a mut A,
+    b: &'a mut B,
+    a_to_b: TransferState<'a>,
+    b_to_a: TransferState<'a>,
+}
+
+/// Copies data in both directions between `a` and `b`.
+///
+/// Data read from `a` is written to `b`, using `a_to_b_buf` as intermediate
+/// storage, and data read from `b` is written to `a` using `b_to_a_buf`. When
+/// one side reaches EOF, the other side is closed; the future resolves once
+/// both directions are finished, to the number of bytes copied from `a` to `b`
+/// and from `b` to `a`.
+///
+/// # Panics
+///
+/// Panics if either buffer is empty.
+///
+/// # Examples
+///
+/// ```
+/// use coreplus::io::{copy_async, copy_bidirectional, CopyBidirectionalError, CopyError};
+/// use coreplus::task::block_on;
+/// # use core::{pin::Pin, task::{Context, Poll}};
+/// # use coreplus::io::{AsyncRead, AsyncWrite};
+/// # type Res<T> = Poll<Result<T, &'static str>>;
+/// # #[derive(Default)]
+/// # struct Mock {
+/// #     input: &'static [u8],
+/// #     output: Vec<u8>,
+/// #     room: usize,
+/// #     write_error: Option<&'static str>,
+/// #     closed: bool,
+/// # }
+/// # impl Mock {
+/// #     fn new(input: &'static [u8], room: usize) -> Self {
+/// #         Mock { input, room, ..Mock::default() }
+/// #     }
+/// # }
+/// # impl AsyncRead for Mock {
+/// #     type Error = &'static str;
+/// #     fn poll_read(
+/// #         mut self: Pin<&mut Self>,
+/// #         _: &mut Context<'_>,
+/// #         buf: &mut [u8],
+/// #     ) -> Res<usize> {
+/// #         let n = buf.len().min(self.input.len());
+/// #         buf[..n].copy_from_slice(&self.input[..n]);
+/// #         self.input = &self.input[n..];
+/// #         Poll::Ready(Ok(n))
+/// #     }
+/// # }
+/// # impl AsyncWrite for Mock {
+/// #     type Error = &'static str;
+/// #     fn poll_write(mut self: Pin<&mut Self>, _: &mut Context<'_>, buf: &[u8]) -> Res<usize> {
+/// #         if let Some(e) = self.write_error {
+/// #             return Poll::Ready(Err(e));
+/// #         }
+/// #         let n = buf.len().min(self.room - self.output.len());
+/// #         self.output.extend_from_slice(&buf[..n]);
+/// #         Poll::Ready(Ok(n))
+/// #     }
+/// #     fn poll_flush(self: Pin<&mut Self>, _: &mut Context<'_>) -> Res<()> {
+/// #         Poll::Ready(Ok(()))
+/// #     }
+/// #     fn poll_close(mut self: Pin<&mut Self>, _: &mut Context<'_>) -> Res<()> {
+/// #         self.closed = true;
+/// #         Poll::Ready(Ok(()))
+/// #     }
+/// # }
+/// // `Mock::new(input, room)` reads `input` and then reports EOF, and accepts
+/// // writes until it holds `room` bytes.
+/// let mut reader = Mock::new(b"hello world", 0);
+/// let mut writer = Mock::new(b"", 64);
+/// let copied = block_on(copy_async(&mut reader, &mut writer, &mut [0; 4]));
+/// assert_eq!(copied, Ok(11));
+/// assert_eq!(writer.output, b"hello world");
+///
+/// // A writer that stops accepting bytes can't make progress.
+/// let mut reader = Mock::new(b"hello world", 0);
+/// let mut writer = Mock::new(b"", 8);
+/// let copied = block_on(copy_async(&mut reader, &mut writer, &mut [0; 4]));
+/// assert_eq!(copied, Err(CopyError::WriteZero));
+///
+/// // Copying both ways closes each side once the other reaches EOF.
+/// let mut a = Mock::new(b"ping", 64);
+/// let mut b = Mock::new(b"pong!", 64);
+/// let copied = block_on(copy_bidirectional(&mut a, &mut b, &mut [0; 4], &mut [0; 4]));
+/// assert_eq!(copied, Ok((4, 5)));
+/// assert_eq!((&*a.output, &*b.output), (&b"pong!"[..], &b"ping"[..]));
+/// assert!(a.closed && b.closed);
+///
+/// // The error says which direction failed, and whether reading or
+/// // writing did. Here, writing to `a` fails while copying from `b`.
+/// let mut a = Mock::new(b"", 64);
+/// a.write_error = Some("broken pipe");
+/// let mut b = Mock::new(b"pong", 64);
+/// let copied = block_on(copy_bidirectional(&mut a, &mut b, &mut [0; 4], &mut [0; 4]));
+/// assert_eq!(
+///     copied,
+///     Err(CopyBidirectionalError::BToA(CopyError::Write("broken pipe"))),
+/// );
+/// ```
+pub fn copy_bidirectional<'a, A, B, EA, EB>(
+    a: &'a mut A,
+    b: &'a mut B,
+    a_to_b_buf: &'a mut [u8],
+    b_to_a_buf: &'a mut [u8],
+) -> CopyBidirectional<'a, A, B>
+where
+    A: AsyncRead<Error = EA> + AsyncWrite<Error = EA> + Unpin + ?Sized,
+    B: AsyncRead<Error = EB> + AsyncWrite<Error = EB> + Unpin + ?Sized,
+{
+    CopyBidirectional {
+        a,
+        b,
+        a_to_b: TransferState::Running(CopyBuffer::new(a_to_b_buf)),
+        b_to_a: TransferState::Running(CopyBuffer::new(b_to_a_buf)),
+    }
+}
+
+impl<A, B, EA, EB> Future for CopyBidirectional<'_, A, B>
+where
+    A: AsyncRead<Error = EA> + AsyncWrite<Error = EA> + Unpin + ?Sized,
+    B: AsyncRead<Error = EB> + AsyncWrite<Error = EB> + Unpin + ?Sized,
+{
+    type Output = Result<(u64, u64), CopyBidirectionalError<EA, EB>>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let this = self.get_mut();
+
+        let a_to_b = this
+            .a_to_b
+            .poll_transfer(cx, Pin::new(&mut *this.a), Pin::new(&mut *this.b));
+        let a_to_b = match a_to_b {
+            Poll::Ready(Ok(amt)) => Poll::Ready(amt),
+            Poll::Ready(Err(e)) => return Poll::Ready(Err(CopyBidirectionalError::AToB(e))),
+            Poll::Pending => Poll::Pending,
+        };
+
+        let b_to_a = this
+            .b_to_a
+            .poll_transfer(cx, Pin::new(&mut *this.b), Pin::new(&mut *this.a));
+        let b_to_a = match b_to_a {
+            Poll::Ready(Ok(amt)) => Poll::Ready(amt),
+            Poll::Ready(Err(e)) => return Poll::Ready(Err(CopyBidirectionalError::BToA(e))),
+            Poll::Pending => Poll::Pending,
+        };
+
+        match (a_to_b, b_to_a) {
+            (Poll::Ready(a_to_b), Poll::Ready(b_to_a)) => Poll::Ready(Ok((a_to_b, b_to_a))),
+            _ => Poll::Pending,
+        }
+    }
+}
@@ -10,8 +10,32 @@
 //! [dependencies]
 //! coreplus = { version = "0.2.1", features = ["std"] }
 //! ```
+//!
+//! Without the standard library, the `alloc` feature enables the parts of the
+//! crate that only need an allocator, such as [`fs::MemoryFileSystem`].
+//!
+//! The `compression` feature enables the [`compression`] module, which
+//! decompresses DEFLATE, zlib and gzip streams.
+//!
+//! The `core-error` feature implements `core::error::Error` for this crate's
+//! error types when `std` is disabled. See the [`error`] module.
+//!
+//! The `serde` feature enables the [`serde`](crate::serde) module, a compact
+//! binary format for `serde` that reads and writes this crate's I/O traits.
 
 #![cfg_attr(not(feature = "std"), no_std)]
 
+#[cfg(feature = "alloc")]
+extern crate alloc;
+
+#[cfg(feature = "compression")]
+pub mod compression;
+pub mod error;
+pub mod fs;
 pub mod io;
+pub mod nb;
 pub mod net;
+#[cfg(feature = "serde")]
+pub mod serde;
+pub mod task;
+pub mod time;
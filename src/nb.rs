@@ -0,0 +1,384 @@
+//! Non-blocking I/O
+//!
+//! Many drivers, especially on embedded targets, can't register a waker and
+//! instead offer operations that either complete straight away or report
+//! [`WouldBlock`](Error::WouldBlock). The traits here describe those, in the
+//! style of the [`nb`](https://docs.rs/nb) crate.
+//!
+//! [`WakeOnInterrupt`] turns them into the async traits, and [`Spin`] turns
+//! them into the blocking ones.
+
+use core::{
+    fmt, hint,
+    pin::Pin,
+    task::{Context, Poll, Waker},
+};
+
+use crate::{
+    error::{self, impl_foreign_error},
+    io::{AsyncRead, AsyncWrite, Read, Write},
+    net::{AsyncSendTo, SocketAddr},
+};
+
+/// The error type of a non-blocking operation.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Error<E> {
+    /// The operation can't complete yet.
+    WouldBlock,
+    /// The operation failed.
+    Other(E),
+}
+
+impl<E: fmt::Display> fmt::Display for Error<E> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::WouldBlock => f.write_str("operation would block"),
+            Error::Other(e) => e.fmt(f),
+        }
+    }
+}
+
+impl<E: error::Error> error::Error for Error<E> {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match self {
+            Error::WouldBlock => None,
+            Error::Other(e) => e.source(),
+        }
+    }
+}
+
+impl_foreign_error! {
+    impl<E> for Error<E>;
+    fn source(&self) {
+        match self {
+            Error::WouldBlock => None,
+            Error::Other(e) => e.source(),
+        }
+    }
+}
+
+impl<E> From<E> for Error<E> {
+    fn from(error: E) -> Self {
+        Error::Other(error)
+    }
+}
+
+/// The result of a non-blocking operation.
+pub type Result<T, E> = core::result::Result<T, Error<E>>;
+
+/// Read bytes without blocking.
+pub trait NonBlockingRead {
+    /// The associated error type.
+    type Error;
+
+    /// Reads some bytes, or returns [`WouldBlock`](Error::WouldBlock) if none
+    /// are available yet.
+    fn read(&mut self, buf: &mut [u8]) -> Result<usize, Self::Error>;
+}
+
+/// Write bytes without blocking.
+pub trait NonBlockingWrite {
+    /// The associated error type.
+    type Error;
+
+    /// Writes some bytes, or returns [`WouldBlock`](Error::WouldBlock) if
+    /// there's no room for any yet.
+    fn write(&mut self, buf: &[u8]) -> Result<usize, Self::Error>;
+
+    /// Flushes buffered bytes, or returns [`WouldBlock`](Error::WouldBlock)
+    /// if that can't finish yet.
+    fn flush(&mut self) -> Result<(), Self::Error>;
+}
+
+/// Send datagrams without blocking.
+pub trait NonBlockingSendTo {
+    /// The associated error type.
+    type Error;
+
+    /// Sends a datagram to `addr`, or returns
+    /// [`WouldBlock`](Error::WouldBlock) if it can't be sent yet.
+    fn send_to(&mut self, buf: &[u8], addr: SocketAddr) -> Result<usize, Self::Error>;
+}
+
+impl<T: NonBlockingRead + ?Sized> NonBlockingRead for &mut T {
+    type Error = T::Error;
+
+    fn read(&mut self, buf: &mut [u8]) -> Result<usize, Self::Error> {
+        (**self).read(buf)
+    }
+}
+
+impl<T: NonBlockingWrite + ?Sized> NonBlockingWrite for &mut T {
+    type Error = T::Error;
+
+    fn write(&mut self, buf: &[u8]) -> Result<usize, Self::Error> {
+        (**self).write(buf)
+    }
+
+    fn flush(&mut self) -> Result<(), Self::Error> {
+        (**self).flush()
+    }
+}
+
+impl<T: NonBlockingSendTo + ?Sized> NonBlockingSendTo for &mut T {
+    type Error = T::Error;
+
+    fn send_to(&mut self, buf: &[u8], addr: SocketAddr) -> Result<usize, Self::Error> {
+        (**self).send_to(buf, addr)
+    }
+}
+
+/// A hook for registering a waker to be woken when a device might be ready.
+///
+/// This is usually backed by an interrupt handler: `register` stores the
+/// waker somewhere the handler can reach, and the handler wakes it.
+/// Implementations must keep only the most recently registered waker.
+///
+/// It's implemented for closures taking a `&Waker`.
+pub trait RegisterWaker {
+    /// Registers `waker` to be woken the next time the device might be ready.
+    fn register(&self, waker: &Waker);
+}
+
+impl<F: Fn(&Waker)> RegisterWaker for F {
+    fn register(&self, waker: &Waker) {
+        self(waker)
+    }
+}
+
+/// Turns a non-blocking device into an async one, using a [`RegisterWaker`]
+/// hook to be told when to try again.
+///
+/// When an operation would block, the waker is registered and the operation
+/// is tried once more, in case the device became ready in between, before
+/// returning [`Poll::Pending`].
+///
+/// # Examples
+///
+/// ```
+/// use core::{pin::Pin, task::Waker};
+/// use coreplus::io::AsyncRead;
+/// use coreplus::nb::{self, NonBlockingRead, WakeOnInterrupt};
+/// use coreplus::task::{block_on, poll_fn};
+///
+/// // A device that has a byte ready every other time it's asked.
+/// struct Uart {
+///     ready: bool,
+/// }
+///
+/// impl NonBlockingRead for Uart {
+///     type Error = core::convert::Infallible;
+///
+///     fn read(&mut self, buf: &mut [u8]) -> nb::Result<usize, Self::Error> {
+///         self.ready = !self.ready;
+///         if !self.ready {
+///             return Err(nb::Error::WouldBlock);
+///         }
+///         buf[0] = b'!';
+///         Ok(1)
+///     }
+/// }
+///
+/// // Pretend the interrupt fires as soon as the waker is registered.
+/// let hook = |waker: &Waker| waker.wake_by_ref();
+/// let mut uart = WakeOnInterrupt::new(Uart { ready: true }, hook);
+///
+/// let mut buf = [0; 1];
+/// let n = block_on(poll_fn(|cx| Pin::new(&mut uart).poll_read(cx, &mut buf)));
+/// assert_eq!(n, Ok(1));
+/// assert_eq!(buf, *b"!");
+/// ```
+#[derive(Debug)]
+pub struct WakeOnInterrupt<T, R> {
+    inner: T,
+    hook: R,
+}
+
+impl<T, R: RegisterWaker> WakeOnInterrupt<T, R> {
+    /// Wraps `inner`, registering wakers with `hook`.
+    pub fn new(inner: T, hook: R) -> Self {
+        Self { inner, hook }
+    }
+
+    /// Gets a reference to the underlying device.
+    pub fn get_ref(&self) -> &T {
+        &self.inner
+    }
+
+    /// Gets a mutable reference to the underlying device.
+    pub fn get_mut(&mut self) -> &mut T {
+        &mut self.inner
+    }
+
+    /// Unwraps this `WakeOnInterrupt`, returning the underlying device and
+    /// the hook.
+    pub fn into_parts(self) -> (T, R) {
+        (self.inner, self.hook)
+    }
+
+    fn poll_with<U, E>(
+        &mut self,
+        cx: &mut Context<'_>,
+        mut op: impl FnMut(&mut T) -> Result<U, E>,
+    ) -> Poll<core::result::Result<U, E>> {
+        match op(&mut self.inner) {
+            Err(Error::WouldBlock) => {}
+            Err(Error::Other(e)) => return Poll::Ready(Err(e)),
+            Ok(value) => return Poll::Ready(Ok(value)),
+        }
+
+        self.hook.register(cx.waker());
+        match op(&mut self.inner) {
+            Err(Error::WouldBlock) => Poll::Pending,
+            Err(Error::Other(e)) => Poll::Ready(Err(e)),
+            Ok(value) => Poll::Ready(Ok(value)),
+        }
+    }
+}
+
+// Nothing is structurally pinned.
+impl<T, R> Unpin for WakeOnInterrupt<T, R> {}
+
+impl<T: NonBlockingRead, R: RegisterWaker> AsyncRead for WakeOnInterrupt<T, R> {
+    type Error = T::Error;
+
+    fn poll_read(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut [u8],
+    ) -> Poll<core::result::Result<usize, Self::Error>> {
+        self.get_mut().poll_with(cx, |inner| inner.read(buf))
+    }
+}
+
+impl<T: NonBlockingWrite, R: RegisterWaker> AsyncWrite for WakeOnInterrupt<T, R> {
+    type Error = T::Error;
+
+    fn poll_write(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<core::result::Result<usize, Self::Error>> {
+        self.get_mut().poll_with(cx, |inner| inner.write(buf))
+    }
+
+    fn poll_flush(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<core::result::Result<(), Self::Error>> {
+        self.get_mut().poll_with(cx, |inner| inner.flush())
+    }
+
+    fn poll_close(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<core::result::Result<(), Self::Error>> {
+        self.poll_flush(cx)
+    }
+}
+
+impl<T: NonBlockingSendTo, R: RegisterWaker> AsyncSendTo for WakeOnInterrupt<T, R> {
+    type Error = T::Error;
+
+    fn poll_send_to(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &[u8],
+        addr: SocketAddr,
+    ) -> Poll<core::result::Result<usize, Self::Error>> {
+        self.get_mut()
+            .poll_with(cx, |inner| inner.send_to(buf, addr))
+    }
+}
+
+/// Turns a non-blocking device into a blocking one by retrying in a spin
+/// loop until each operation completes.
+///
+/// # Examples
+///
+/// ```
+/// use coreplus::io::Write;
+/// use coreplus::nb::{self, NonBlockingWrite, Spin};
+///
+/// // A device that accepts one byte every other time it's asked.
+/// struct Uart {
+///     busy: bool,
+///     sent: Vec<u8>,
+/// }
+///
+/// impl NonBlockingWrite for Uart {
+///     type Error = core::convert::Infallible;
+///
+///     fn write(&mut self, buf: &[u8]) -> nb::Result<usize, Self::Error> {
+///         self.busy = !self.busy;
+///         if self.busy {
+///             return Err(nb::Error::WouldBlock);
+///         }
+///         self.sent.push(buf[0]);
+///         Ok(1)
+///     }
+///
+///     fn flush(&mut self) -> nb::Result<(), Self::Error> {
+///         Ok(())
+///     }
+/// }
+///
+/// let mut uart = Spin::new(Uart { busy: false, sent: Vec::new() });
+/// uart.write_all(b"hello").unwrap();
+/// assert_eq!(uart.get_ref().sent, b"hello");
+/// ```
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Spin<T>(T);
+
+impl<T> Spin<T> {
+    /// Wraps `inner`.
+    pub fn new(inner: T) -> Self {
+        Self(inner)
+    }
+
+    /// Gets a reference to the underlying device.
+    pub fn get_ref(&self) -> &T {
+        &self.0
+    }
+
+    /// Gets a mutable reference to the underlying device.
+    pub fn get_mut(&mut self) -> &mut T {
+        &mut self.0
+    }
+
+    /// Unwraps this `Spin`, returning the underlying device.
+    pub fn into_inner(self) -> T {
+        self.0
+    }
+}
+
+/// Retries `op` until it doesn't return [`WouldBlock`](Error::WouldBlock).
+fn spin<U, E>(mut op: impl FnMut() -> Result<U, E>) -> core::result::Result<U, E> {
+    loop {
+        match op() {
+            Err(Error::WouldBlock) => hint::spin_loop(),
+            Err(Error::Other(e)) => return Err(e),
+            Ok(value) => return Ok(value),
+        }
+    }
+}
+
+impl<T: NonBlockingRead> Read for Spin<T> {
+    type Error = T::Error;
+
+    fn read(&mut self, buf: &mut [u8]) -> core::result::Result<usize, Self::Error> {
+        spin(|| self.0.read(buf))
+    }
+}
+
+impl<T: NonBlockingWrite> Write for Spin<T> {
+    type Error = T::Error;
+
+    fn write(&mut self, buf: &[u8]) -> core::result::Result<usize, Self::Error> {
+        spin(|| self.0.write(buf))
+    }
+
+    fn flush(&mut self) -> core::result::Result<(), Self::Error> {
+        spin(|| self.0.flush())
+    }
+}
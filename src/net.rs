@@ -5,17 +5,36 @@ use core::{
     task::{Context, Poll},
 };
 
+use crate::io::ReadBuf;
+
 mod addr;
+#[cfg(feature = "alloc")]
+mod happy_eyeballs;
 mod ip;
+mod multicast;
+mod options;
 mod parser;
+mod policy;
 #[cfg(feature = "std")]
 mod std_impl;
+mod tcp;
+mod udp;
 
-pub use self::addr::{GetSocketAddrs, SocketAddr, SocketAddrV4, SocketAddrV6, ToSocketAddrs};
+pub use self::addr::{
+    AsyncGetSocketAddrs, AsyncToSocketAddrs, Dedup, GetSocketAddrs, OneOrMany, Resolve, SocketAddr,
+    SocketAddrIteratorExt, SocketAddrV4, SocketAddrV6, ToSocketAddrs,
+};
+#[cfg(feature = "alloc")]
+pub use self::happy_eyeballs::*;
 pub use self::ip::{IpAddr, Ipv4Addr, Ipv6Addr, Ipv6MulticastScope};
+pub use self::multicast::*;
+pub use self::options::*;
 pub use self::parser::AddrParseError;
+pub use self::policy::{DestinationSorter, Policy, PolicyTable, SortByPolicy};
 #[cfg(feature = "std")]
 pub use self::std_impl::*;
+pub use self::tcp::*;
+pub use self::udp::*;
 
 /// This is intended for async datagram IO.
 pub trait AsyncSendTo {
@@ -48,6 +67,27 @@ pub trait AsyncRecvFrom {
         cx: &mut Context<'_>,
         buf: &mut [u8],
     ) -> Poll<Result<(usize, SocketAddr), Self::Error>>;
+
+    /// Like [`poll_recv_from`](AsyncRecvFrom::poll_recv_from), but receives
+    /// into a [`ReadBuf`], which may not be initialized.
+    ///
+    /// The default implementation initializes the unfilled part of `buf`
+    /// (only the first time, since `ReadBuf` keeps track) and calls
+    /// `poll_recv_from`.
+    fn poll_recv_from_buf(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut ReadBuf<'_>,
+    ) -> Poll<Result<SocketAddr, Self::Error>> {
+        match self.poll_recv_from(cx, buf.initialize_unfilled()) {
+            Poll::Ready(Ok((n, addr))) => {
+                buf.advance(n);
+                Poll::Ready(Ok(addr))
+            }
+            Poll::Ready(Err(e)) => Poll::Ready(Err(e)),
+            Poll::Pending => Poll::Pending,
+        }
+    }
 }
 
 pub trait MulticastSocket {
@@ -55,8 +95,28 @@ pub trait MulticastSocket {
     type Error;
 
     /// Attempts to join the given multicast group.
+    ///
+    /// See [`MulticastSocketExt`] to choose the interface.
     fn join_multicast(&self, addr: IpAddr) -> Result<(), Self::Error>;
 
     /// Attempts to leave the given multicast group.
     fn leave_multicast(&self, addr: IpAddr) -> Result<(), Self::Error>;
 }
+
+/// Sockets that know the local address they're bound to.
+pub trait LocalAddr {
+    /// The associated error type.
+    type Error;
+
+    /// Returns the local address of this socket.
+    fn local_addr(&self) -> Result<SocketAddr, Self::Error>;
+}
+
+/// Sockets that know the address of the remote peer they're connected to.
+pub trait PeerAddr {
+    /// The associated error type.
+    type Error;
+
+    /// Returns the address of the remote peer of this socket.
+    fn peer_addr(&self) -> Result<SocketAddr, Self::Error>;
+}
@@ -1,8 +1,16 @@
-use core::{fmt, option, str};
+use core::{
+    fmt,
+    future::Future,
+    iter::{Chain, Filter, FusedIterator},
+    option,
+    pin::Pin,
+    str,
+    task::{Context, Poll},
+};
 
 use crate::{
     io::Write,
-    net::{AddrParseError, IpAddr, Ipv4Addr, Ipv6Addr},
+    net::{AddrParseError, DestinationSorter, IpAddr, Ipv4Addr, Ipv6Addr, SortByPolicy},
 };
 
 /// An internet socket address, either IPv4 or IPv6.
@@ -598,17 +606,158 @@ pub trait GetSocketAddrs {
     fn get_socket_addrs(&self, host: &str, port: u16) -> Result<Self::Iter, Self::Error>;
 }
 
+/// The iterator of addresses returned by [`ToSocketAddrs`] and
+/// [`AsyncToSocketAddrs`].
+///
+/// It's either the single address that didn't need resolving, or the
+/// addresses a hostname resolved to. [`SocketAddrIteratorExt`] has adapters
+/// to filter and reorder them.
+///
+/// # Examples
+///
+/// ```
+/// # #[cfg(feature = "std")] {
+/// use coreplus::net::{SocketAddr, SocketAddrIteratorExt, StdGetSocketAddrs, ToSocketAddrs};
+///
+/// let addrs = "[::1]:80".to_socket_addrs(&StdGetSocketAddrs)?;
+/// assert_eq!(addrs.len(), 1);
+///
+/// let addrs: Vec<SocketAddr> = ("localhost", 80)
+///     .to_socket_addrs(&StdGetSocketAddrs)?
+///     .dedup()
+///     .ipv6_first()
+///     .collect();
+/// assert!(addrs.iter().all(|addr| addr.port() == 80));
+/// # }
+/// # Ok::<(), std::io::Error>(())
+/// ```
+#[derive(Clone, Debug)]
 pub enum OneOrMany<I: Iterator> {
+    /// A single address.
     One(option::IntoIter<I::Item>),
+    /// The addresses from a resolver.
     Many(I),
 }
 
 impl<I: Iterator> OneOrMany<I> {
+    /// Creates an iterator that yields just `item`.
     pub fn one(item: I::Item) -> Self {
         Self::One(Some(item).into_iter())
     }
 }
 
+impl<I: Iterator> Iterator for OneOrMany<I> {
+    type Item = I::Item;
+
+    fn next(&mut self) -> Option<I::Item> {
+        match self {
+            OneOrMany::One(iter) => iter.next(),
+            OneOrMany::Many(iter) => iter.next(),
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        match self {
+            OneOrMany::One(iter) => iter.size_hint(),
+            OneOrMany::Many(iter) => iter.size_hint(),
+        }
+    }
+}
+
+impl<I: DoubleEndedIterator> DoubleEndedIterator for OneOrMany<I> {
+    fn next_back(&mut self) -> Option<I::Item> {
+        match self {
+            OneOrMany::One(iter) => iter.next_back(),
+            OneOrMany::Many(iter) => iter.next_back(),
+        }
+    }
+}
+
+impl<I: ExactSizeIterator> ExactSizeIterator for OneOrMany<I> {}
+
+impl<I: FusedIterator> FusedIterator for OneOrMany<I> {}
+
+/// Adapters for iterators of [`SocketAddr`]s, such as [`OneOrMany`].
+pub trait SocketAddrIteratorExt: Iterator<Item = SocketAddr> + Sized {
+    /// Skips addresses that have already been yielded.
+    ///
+    /// This doesn't allocate, so each address is compared against all the
+    /// ones before it. That takes quadratic time, like
+    /// [`DestinationSorter`]; see its docs for why that's fine.
+    fn dedup(self) -> Dedup<Self>
+    where
+        Self: Clone,
+    {
+        Dedup {
+            seen: self.clone(),
+            nseen: 0,
+            iter: self,
+        }
+    }
+
+    /// Yields only the IPv4 addresses.
+    fn ipv4_only(self) -> Filter<Self, fn(&SocketAddr) -> bool> {
+        self.filter(SocketAddr::is_ipv4)
+    }
+
+    /// Yields all the IPv6 addresses, then all the IPv4 addresses, otherwise
+    /// keeping them in order.
+    #[allow(clippy::type_complexity)]
+    fn ipv6_first(
+        self,
+    ) -> Chain<Filter<Self, fn(&SocketAddr) -> bool>, Filter<Self, fn(&SocketAddr) -> bool>>
+    where
+        Self: Clone,
+    {
+        let v6: fn(&SocketAddr) -> bool = SocketAddr::is_ipv6;
+        let v4: fn(&SocketAddr) -> bool = SocketAddr::is_ipv4;
+        self.clone().filter(v6).chain(self.filter(v4))
+    }
+
+    /// Yields the addresses in the order `sorter` prefers, as described by
+    /// [`DestinationSorter::sort`].
+    fn sort_by_policy<'a>(self, sorter: &'a DestinationSorter<'a>) -> SortByPolicy<'a, Self>
+    where
+        Self: Clone,
+    {
+        SortByPolicy::new(self, sorter)
+    }
+}
+
+impl<I: Iterator<Item = SocketAddr>> SocketAddrIteratorExt for I {}
+
+/// The iterator returned by [`SocketAddrIteratorExt::dedup`].
+#[derive(Clone, Debug)]
+pub struct Dedup<I> {
+    iter: I,
+    /// A copy of the iterator from the start, to look back at the addresses
+    /// already yielded.
+    seen: I,
+    nseen: usize,
+}
+
+impl<I: Iterator<Item = SocketAddr> + Clone> Iterator for Dedup<I> {
+    type Item = SocketAddr;
+
+    fn next(&mut self) -> Option<SocketAddr> {
+        loop {
+            let addr = self.iter.next()?;
+            let nseen = self.nseen;
+            self.nseen += 1;
+            if !self.seen.clone().take(nseen).any(|seen| seen == addr) {
+                return Some(addr);
+            }
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let (lower, upper) = self.iter.size_hint();
+        (lower.min(1), upper)
+    }
+}
+
+impl<I: FusedIterator<Item = SocketAddr> + Clone> FusedIterator for Dedup<I> {}
+
 /// Retrive the addresses associated with a hostname.
 ///
 /// To use this, you must pass in a type that implements [`GetSocketAddrs`].
@@ -679,8 +828,7 @@ impl<T: GetSocketAddrs> ToSocketAddrs<T> for (&str, u16) {
             return Ok(OneOrMany::one(SocketAddr::V6(addr)));
         }
 
-        get.get_socket_addrs(host, port)
-            .map(|iter| OneOrMany::Many(iter))
+        get.get_socket_addrs(host, port).map(OneOrMany::Many)
     }
 }
 
@@ -699,11 +847,9 @@ impl<T: GetSocketAddrs> ToSocketAddrs<T> for str {
             return Ok(OneOrMany::one(addr));
         }
 
-        let (host, port_str) = self.rsplit_once(':').ok_or(AddrParseError(()))?;
-        let port: u16 = port_str.parse().map_err(|_| AddrParseError(()))?;
+        let (host, port) = parse_host_port(self).ok_or(AddrParseError(()))?;
 
-        get.get_socket_addrs(host, port)
-            .map(|iter| OneOrMany::Many(iter))
+        get.get_socket_addrs(host, port).map(OneOrMany::Many)
     }
 }
 
@@ -731,6 +877,204 @@ impl<T: GetSocketAddrs, U: ToSocketAddrs<T> + ?Sized> ToSocketAddrs<T> for &U {
 //     }
 // }
 
+/// The async counterpart of [`GetSocketAddrs`].
+///
+/// An implementation resolves one hostname at a time. Once
+/// [`poll_get_socket_addrs`](Self::poll_get_socket_addrs) has returned
+/// [`Poll::Pending`], it has to be called with the same host and port until
+/// it's ready. It can then be used for another hostname.
+pub trait AsyncGetSocketAddrs {
+    /// The iterator of addresses a hostname resolved to.
+    type Iter: Iterator<Item = SocketAddr>;
+    /// The error returned when resolving fails. Strings that aren't a valid
+    /// `host:port` are reported with it too.
+    type Error: From<AddrParseError>;
+
+    /// Attempts to resolve `host` to the addresses it has, each with the
+    /// given port.
+    fn poll_get_socket_addrs(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        host: &str,
+        port: u16,
+    ) -> Poll<Result<Self::Iter, Self::Error>>;
+}
+
+/// Retrive the addresses associated with a hostname, without blocking.
+///
+/// This mirrors [`ToSocketAddrs`], but resolves with a type that implements
+/// [`AsyncGetSocketAddrs`]. Addresses that don't need resolving are ready
+/// straight away.
+///
+/// # Examples
+///
+/// ```
+/// # #[cfg(feature = "std")] {
+/// use core::pin::Pin;
+/// use coreplus::net::{AsyncToSocketAddrs, StdAsyncGetSocketAddrs};
+/// use coreplus::task::block_on;
+///
+/// let mut get = StdAsyncGetSocketAddrs::default();
+/// let addrs = block_on("localhost:80".resolve(Pin::new(&mut get)))?;
+/// assert!(addrs.into_iter().all(|addr| addr.port() == 80));
+/// # }
+/// # Ok::<(), std::io::Error>(())
+/// ```
+pub trait AsyncToSocketAddrs<T: AsyncGetSocketAddrs> {
+    /// Attempts to convert this object to an iterator of resolved
+    /// `SocketAddr`s.
+    ///
+    /// Like [`AsyncGetSocketAddrs::poll_get_socket_addrs`], this has to be
+    /// called with the same `get` until it's ready.
+    fn poll_to_socket_addrs(
+        &self,
+        cx: &mut Context<'_>,
+        get: Pin<&mut T>,
+    ) -> Poll<Result<OneOrMany<T::Iter>, T::Error>>;
+
+    /// Returns a future that converts this object to an iterator of resolved
+    /// `SocketAddr`s.
+    fn resolve<'a>(&'a self, get: Pin<&'a mut T>) -> Resolve<'a, Self, T> {
+        Resolve { addr: self, get }
+    }
+}
+
+/// The future returned by [`AsyncToSocketAddrs::resolve`].
+#[must_use = "futures do nothing unless you `.await` or poll them"]
+pub struct Resolve<'a, A: ?Sized, T> {
+    addr: &'a A,
+    get: Pin<&'a mut T>,
+}
+
+impl<A, T> Future for Resolve<'_, A, T>
+where
+    A: AsyncToSocketAddrs<T> + ?Sized,
+    T: AsyncGetSocketAddrs,
+{
+    type Output = Result<OneOrMany<T::Iter>, T::Error>;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let addr = self.addr;
+        addr.poll_to_socket_addrs(cx, self.get.as_mut())
+    }
+}
+
+impl<T: AsyncGetSocketAddrs> AsyncToSocketAddrs<T> for SocketAddr {
+    fn poll_to_socket_addrs(
+        &self,
+        _cx: &mut Context<'_>,
+        _get: Pin<&mut T>,
+    ) -> Poll<Result<OneOrMany<T::Iter>, T::Error>> {
+        Poll::Ready(Ok(OneOrMany::one(*self)))
+    }
+}
+
+impl<T: AsyncGetSocketAddrs> AsyncToSocketAddrs<T> for SocketAddrV4 {
+    fn poll_to_socket_addrs(
+        &self,
+        cx: &mut Context<'_>,
+        get: Pin<&mut T>,
+    ) -> Poll<Result<OneOrMany<T::Iter>, T::Error>> {
+        SocketAddr::V4(*self).poll_to_socket_addrs(cx, get)
+    }
+}
+
+impl<T: AsyncGetSocketAddrs> AsyncToSocketAddrs<T> for SocketAddrV6 {
+    fn poll_to_socket_addrs(
+        &self,
+        cx: &mut Context<'_>,
+        get: Pin<&mut T>,
+    ) -> Poll<Result<OneOrMany<T::Iter>, T::Error>> {
+        SocketAddr::V6(*self).poll_to_socket_addrs(cx, get)
+    }
+}
+
+impl<T: AsyncGetSocketAddrs> AsyncToSocketAddrs<T> for (IpAddr, u16) {
+    fn poll_to_socket_addrs(
+        &self,
+        cx: &mut Context<'_>,
+        get: Pin<&mut T>,
+    ) -> Poll<Result<OneOrMany<T::Iter>, T::Error>> {
+        SocketAddr::new(self.0, self.1).poll_to_socket_addrs(cx, get)
+    }
+}
+
+impl<T: AsyncGetSocketAddrs> AsyncToSocketAddrs<T> for (Ipv4Addr, u16) {
+    fn poll_to_socket_addrs(
+        &self,
+        cx: &mut Context<'_>,
+        get: Pin<&mut T>,
+    ) -> Poll<Result<OneOrMany<T::Iter>, T::Error>> {
+        SocketAddrV4::new(self.0, self.1).poll_to_socket_addrs(cx, get)
+    }
+}
+
+impl<T: AsyncGetSocketAddrs> AsyncToSocketAddrs<T> for (Ipv6Addr, u16) {
+    fn poll_to_socket_addrs(
+        &self,
+        cx: &mut Context<'_>,
+        get: Pin<&mut T>,
+    ) -> Poll<Result<OneOrMany<T::Iter>, T::Error>> {
+        SocketAddrV6::new(self.0, self.1, 0, 0).poll_to_socket_addrs(cx, get)
+    }
+}
+
+impl<T: AsyncGetSocketAddrs> AsyncToSocketAddrs<T> for (&str, u16) {
+    fn poll_to_socket_addrs(
+        &self,
+        cx: &mut Context<'_>,
+        get: Pin<&mut T>,
+    ) -> Poll<Result<OneOrMany<T::Iter>, T::Error>> {
+        let (host, port) = *self;
+
+        // try to parse the host as a regular IP address first
+        if let Ok(addr) = host.parse::<IpAddr>() {
+            return Poll::Ready(Ok(OneOrMany::one(SocketAddr::new(addr, port))));
+        }
+
+        get.poll_get_socket_addrs(cx, host, port)
+            .map_ok(OneOrMany::Many)
+    }
+}
+
+// accepts strings like 'localhost:12345'
+impl<T: AsyncGetSocketAddrs> AsyncToSocketAddrs<T> for str {
+    fn poll_to_socket_addrs(
+        &self,
+        cx: &mut Context<'_>,
+        get: Pin<&mut T>,
+    ) -> Poll<Result<OneOrMany<T::Iter>, T::Error>> {
+        // try to parse as a regular SocketAddr first
+        if let Ok(addr) = self.parse() {
+            return Poll::Ready(Ok(OneOrMany::one(addr)));
+        }
+
+        let (host, port) = match parse_host_port(self) {
+            Some(host_port) => host_port,
+            None => return Poll::Ready(Err(AddrParseError(()).into())),
+        };
+
+        get.poll_get_socket_addrs(cx, host, port)
+            .map_ok(OneOrMany::Many)
+    }
+}
+
+impl<T: AsyncGetSocketAddrs, U: AsyncToSocketAddrs<T> + ?Sized> AsyncToSocketAddrs<T> for &U {
+    fn poll_to_socket_addrs(
+        &self,
+        cx: &mut Context<'_>,
+        get: Pin<&mut T>,
+    ) -> Poll<Result<OneOrMany<T::Iter>, T::Error>> {
+        (**self).poll_to_socket_addrs(cx, get)
+    }
+}
+
+/// Splits a string like `localhost:12345` into its host and port.
+fn parse_host_port(s: &str) -> Option<(&str, u16)> {
+    let (host, port_str) = s.rsplit_once(':')?;
+    Some((host, port_str.parse().ok()?))
+}
+
 #[cfg(feature = "std")]
 impl From<std::net::SocketAddrV4> for SocketAddrV4 {
     fn from(addr: std::net::SocketAddrV4) -> Self {
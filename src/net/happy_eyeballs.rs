@@ -0,0 +1,577 @@
+use alloc::{boxed::Box, collections::VecDeque, vec::Vec};
+use core::{
+    fmt,
+    future::Future,
+    pin::Pin,
+    task::{Context, Poll},
+    time::Duration,
+};
+
+use crate::{
+    error::{impl_foreign_error, Error},
+    net::{
+        AsyncGetSocketAddrs, AsyncTcpConnect, AsyncToSocketAddrs, GetSocketAddrs, SocketAddr,
+        ToSocketAddrs,
+    },
+    time::{Instant, Timer},
+};
+
+/// The error returned when every connection attempt made by
+/// [`HappyEyeballs`] failed.
+///
+/// It keeps the error from each attempt, in the order they failed. If
+/// there were no addresses to try, there are no errors.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct HappyEyeballsError<E> {
+    errors: Vec<(SocketAddr, E)>,
+}
+
+impl<E> HappyEyeballsError<E> {
+    /// Returns the address and error of each failed attempt.
+    pub fn errors(&self) -> &[(SocketAddr, E)] {
+        &self.errors
+    }
+
+    /// Unwraps this error, returning the address and error of each failed
+    /// attempt.
+    pub fn into_errors(self) -> Vec<(SocketAddr, E)> {
+        self.errors
+    }
+}
+
+impl<E> fmt::Display for HappyEyeballsError<E> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.errors.len() {
+            0 => f.write_str("no addresses to connect to"),
+            1 => f.write_str("failed to connect"),
+            n => write!(f, "all {} connection attempts failed", n),
+        }
+    }
+}
+
+impl<E: Error + 'static> Error for HappyEyeballsError<E> {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self.errors.last() {
+            Some((_, e)) => Some(e),
+            None => None,
+        }
+    }
+}
+
+impl_foreign_error! {
+    impl<E> for HappyEyeballsError<E>;
+    fn source(&self) {
+        match self.errors.last() {
+            Some((_, e)) => Some(e),
+            None => None,
+        }
+    }
+}
+
+/// The error returned by [`HappyEyeballs::resolve_connect`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum HappyConnectError<R, E> {
+    /// Resolving the address failed.
+    Resolve(R),
+    /// Every connection attempt failed.
+    Connect(HappyEyeballsError<E>),
+}
+
+impl<R, E> fmt::Display for HappyConnectError<R, E> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            HappyConnectError::Resolve(_) => "error resolving address",
+            HappyConnectError::Connect(_) => "error connecting",
+        })
+    }
+}
+
+impl<R: Error + 'static, E: Error + 'static> Error for HappyConnectError<R, E> {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            HappyConnectError::Resolve(e) => Some(e),
+            HappyConnectError::Connect(e) => Some(e),
+        }
+    }
+}
+
+impl_foreign_error! {
+    impl<R, E> for HappyConnectError<R, E>;
+    fn source(&self) {
+        match self {
+            HappyConnectError::Resolve(e) => Some(e),
+            HappyConnectError::Connect(e) => Some(e),
+        }
+    }
+}
+
+/// Connects to dual-stack hosts quickly with Happy Eyeballs
+/// ([RFC 8305]).
+///
+/// The addresses are interleaved by address family, starting with the
+/// family of the first one, and a connection attempt is started for each in
+/// turn. If an attempt hasn't connected within the
+/// [attempt delay](Self::attempt_delay), or fails, the next one starts
+/// without cancelling it. The first attempt to connect wins and the rest are
+/// dropped. That way a broken IPv6 network only costs the attempt delay
+/// before IPv4 is tried, instead of a full connection timeout.
+///
+/// The addresses are tried in the order they're given, apart from the
+/// interleaving. [`SocketAddrIteratorExt::sort_by_policy`] sorts them as RFC
+/// 6724 describes, if the resolver hasn't already.
+///
+/// This type is available when the `alloc` feature is enabled.
+///
+/// # Examples
+///
+/// ```
+/// # #[cfg(feature = "std")] {
+/// use coreplus::net::{HappyEyeballs, LocalAddr, PeerAddr, StdGetSocketAddrs, StdNet, TcpBind};
+/// use coreplus::task::block_on;
+/// use coreplus::time::StdClock;
+///
+/// let listener = StdNet.bind("127.0.0.1:0".parse().unwrap())?;
+/// let port = LocalAddr::local_addr(&listener)?.port();
+///
+/// let happy = HappyEyeballs::new(StdNet, StdClock);
+/// let stream = block_on(happy.connect(("127.0.0.1", port), &StdGetSocketAddrs)?)?;
+/// assert_eq!(PeerAddr::peer_addr(&stream)?.port(), port);
+/// # }
+/// # Ok::<(), Box<dyn std::error::Error>>(())
+/// ```
+///
+/// [RFC 8305]: https://tools.ietf.org/html/rfc8305
+/// [`SocketAddrIteratorExt::sort_by_policy`]: crate::net::SocketAddrIteratorExt::sort_by_policy
+#[derive(Clone, Debug)]
+pub struct HappyEyeballs<C, T> {
+    connector: C,
+    timer: T,
+    attempt_delay: Duration,
+    first_family_count: usize,
+}
+
+impl<C: AsyncTcpConnect, T: Timer> HappyEyeballs<C, T> {
+    /// Creates a connector that makes connections with `connector` and
+    /// staggers them with `timer`, with the delays RFC 8305 recommends.
+    pub fn new(connector: C, timer: T) -> Self {
+        Self {
+            connector,
+            timer,
+            attempt_delay: Duration::from_millis(250),
+            first_family_count: 1,
+        }
+    }
+
+    /// Returns how long an attempt has before the next one starts. The
+    /// default is 250 milliseconds.
+    pub fn attempt_delay(&self) -> Duration {
+        self.attempt_delay
+    }
+
+    /// Sets how long an attempt has before the next one starts.
+    ///
+    /// RFC 8305 recommends at least 100 milliseconds, and never less than
+    /// 10, so shorter delays are raised to 10 milliseconds.
+    pub fn set_attempt_delay(&mut self, delay: Duration) {
+        self.attempt_delay = delay.max(Duration::from_millis(10));
+    }
+
+    /// Returns how many addresses of the first address family are tried
+    /// before the other family. The default is 1.
+    pub fn first_family_count(&self) -> usize {
+        self.first_family_count
+    }
+
+    /// Sets how many addresses of the first address family are tried before
+    /// the other family.
+    ///
+    /// Zero is treated as 1.
+    pub fn set_first_family_count(&mut self, count: usize) {
+        self.first_family_count = count.max(1);
+    }
+
+    /// Gets a reference to the underlying connector.
+    pub fn get_ref(&self) -> &C {
+        &self.connector
+    }
+
+    /// Gets a mutable reference to the underlying connector.
+    pub fn get_mut(&mut self) -> &mut C {
+        &mut self.connector
+    }
+
+    /// Unwraps this `HappyEyeballs`, returning the connector and timer.
+    pub fn into_parts(self) -> (C, T) {
+        (self.connector, self.timer)
+    }
+
+    /// Resolves `addr` with `get` and returns a future that races
+    /// connections to the addresses it resolved to.
+    ///
+    /// This blocks while `get` resolves a hostname.
+    /// [`resolve_connect`](Self::resolve_connect) doesn't.
+    pub fn connect<A, G>(&self, addr: A, get: &G) -> Result<HappyConnect<'_, C, T>, G::Error>
+    where
+        A: ToSocketAddrs<G>,
+        G: GetSocketAddrs,
+    {
+        Ok(self.connect_addrs(addr.to_socket_addrs(get)?))
+    }
+
+    /// Returns a future that resolves `addr` with `get`, then races
+    /// connections to the addresses it resolved to.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # #[cfg(feature = "std")] {
+    /// use core::pin::Pin;
+    /// use coreplus::net::{
+    ///     HappyEyeballs, LocalAddr, PeerAddr, StdAsyncGetSocketAddrs, StdNet, TcpBind,
+    /// };
+    /// use coreplus::task::block_on;
+    /// use coreplus::time::StdClock;
+    ///
+    /// let listener = StdNet.bind("127.0.0.1:0".parse().unwrap())?;
+    /// let port = LocalAddr::local_addr(&listener)?.port();
+    ///
+    /// let happy = HappyEyeballs::new(StdNet, StdClock);
+    /// let mut get = StdAsyncGetSocketAddrs::default();
+    /// let stream = block_on(happy.resolve_connect(("localhost", port), Pin::new(&mut get)))?;
+    /// assert_eq!(PeerAddr::peer_addr(&stream)?.port(), port);
+    /// # }
+    /// # Ok::<(), Box<dyn std::error::Error>>(())
+    /// ```
+    pub fn resolve_connect<'a, A, G>(
+        &'a self,
+        addr: A,
+        get: Pin<&'a mut G>,
+    ) -> HappyResolveConnect<'a, A, G, C, T>
+    where
+        A: AsyncToSocketAddrs<G>,
+        G: AsyncGetSocketAddrs,
+    {
+        HappyResolveConnect {
+            happy: self,
+            addr,
+            get,
+            connect: None,
+        }
+    }
+
+    /// Returns a future that races connections to `addrs`.
+    ///
+    /// # Examples
+    ///
+    /// A connector that makes each address fail, connect or hang at a given
+    /// time shows the attempts being staggered:
+    ///
+    /// ```
+    /// use core::{cell::RefCell, future::Future, pin::Pin, time::Duration};
+    /// use coreplus::net::{AsyncTcpConnect, HappyEyeballs, SocketAddr};
+    /// use coreplus::task::{block_on, poll_fn};
+    /// use coreplus::time::{Clock, Instant, ManualClock, ManualSleep, Timer};
+    /// # use core::task::{Context, Poll};
+    /// # use coreplus::io::{AsyncRead, AsyncWrite};
+    /// # struct Conn(SocketAddr);
+    /// # impl AsyncRead for Conn {
+    /// #     type Error = ();
+    /// #     fn poll_read(self: Pin<&mut Self>, _: &mut Context<'_>, _: &mut [u8]) -> Poll<Result<usize, ()>> {
+    /// #         Poll::Ready(Ok(0))
+    /// #     }
+    /// # }
+    /// # impl AsyncWrite for Conn {
+    /// #     type Error = ();
+    /// #     fn poll_write(self: Pin<&mut Self>, _: &mut Context<'_>, buf: &[u8]) -> Poll<Result<usize, ()>> {
+    /// #         Poll::Ready(Ok(buf.len()))
+    /// #     }
+    /// #     fn poll_flush(self: Pin<&mut Self>, _: &mut Context<'_>) -> Poll<Result<(), ()>> {
+    /// #         Poll::Ready(Ok(()))
+    /// #     }
+    /// #     fn poll_close(self: Pin<&mut Self>, _: &mut Context<'_>) -> Poll<Result<(), ()>> {
+    /// #         Poll::Ready(Ok(()))
+    /// #     }
+    /// # }
+    ///
+    /// // Each address either fails or connects after some milliseconds, or
+    /// // never finishes. Attempts are logged with the time they started.
+    /// struct Mock {
+    ///     clock: ManualClock,
+    ///     outcomes: Vec<(SocketAddr, Option<(u64, bool)>)>,
+    ///     started: RefCell<Vec<(u128, SocketAddr)>>,
+    /// }
+    ///
+    /// struct MockConnect(SocketAddr, Option<(ManualSleep, bool)>);
+    ///
+    /// impl Future for MockConnect {
+    ///     type Output = Result<Conn, SocketAddr>;
+    ///
+    ///     fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+    ///         let addr = self.0;
+    ///         match &mut self.1 {
+    ///             Some((sleep, ok)) => match Pin::new(sleep).poll(cx) {
+    ///                 Poll::Ready(()) if *ok => Poll::Ready(Ok(Conn(addr))),
+    ///                 Poll::Ready(()) => Poll::Ready(Err(addr)),
+    ///                 Poll::Pending => Poll::Pending,
+    ///             },
+    ///             None => Poll::Pending,
+    ///         }
+    ///     }
+    /// }
+    ///
+    /// impl AsyncTcpConnect for Mock {
+    ///     type Stream = Conn;
+    ///     type Error = SocketAddr;
+    ///     type Connect = MockConnect;
+    ///
+    ///     fn connect(&self, addr: SocketAddr) -> MockConnect {
+    ///         let now = self.clock.now();
+    ///         self.started.borrow_mut().push((now.since_start().as_millis(), addr));
+    ///         let (_, outcome) = self.outcomes.iter().find(|(a, _)| *a == addr).unwrap();
+    ///         MockConnect(addr, outcome.map(|(ms, ok)| {
+    ///             let deadline = now.checked_add(Duration::from_millis(ms)).unwrap();
+    ///             (self.clock.sleep_until(deadline), ok)
+    ///         }))
+    ///     }
+    /// }
+    ///
+    /// let v6_hangs: SocketAddr = "[2001:db8::1]:80".parse().unwrap();
+    /// let v6_connects: SocketAddr = "[2001:db8::2]:80".parse().unwrap();
+    /// let v4_fails: SocketAddr = "192.0.2.1:80".parse().unwrap();
+    /// let v4_unused: SocketAddr = "192.0.2.2:80".parse().unwrap();
+    /// let v6_fails: SocketAddr = "[2001:db8::3]:80".parse().unwrap();
+    ///
+    /// let clock = ManualClock::new();
+    /// let mock = Mock {
+    ///     clock: clock.clone(),
+    ///     outcomes: vec![
+    ///         (v6_hangs, None),
+    ///         (v6_connects, Some((100, true))),
+    ///         (v4_fails, Some((50, false))),
+    ///         (v4_unused, None),
+    ///         (v6_fails, Some((20, false))),
+    ///     ],
+    ///     started: RefCell::new(Vec::new()),
+    /// };
+    /// let happy = HappyEyeballs::new(mock, clock.clone());
+    ///
+    /// // Runs `future`, moving the clock forward while it's pending.
+    /// let run = |future: &mut (dyn Future<Output = _> + Unpin)| {
+    ///     block_on(poll_fn(|cx| loop {
+    ///         match Pin::new(&mut *future).poll(cx) {
+    ///             Poll::Ready(result) => return Poll::Ready(result),
+    ///             Poll::Pending => clock.advance(Duration::from_millis(10)),
+    ///         }
+    ///     }))
+    /// };
+    ///
+    /// let addrs = [v6_hangs, v6_connects, v4_fails, v4_unused];
+    /// let stream = run(&mut happy.connect_addrs(addrs)).unwrap();
+    /// assert_eq!(stream.0, v6_connects);
+    ///
+    /// // The families alternate. The second attempt starts when the first
+    /// // has had the attempt delay, and the third as soon as the second
+    /// // fails. The third connects before the fourth is due.
+    /// assert_eq!(
+    ///     *happy.get_ref().started.borrow(),
+    ///     [(0, v6_hangs), (250, v4_fails), (300, v6_connects)],
+    /// );
+    ///
+    /// // If every attempt fails, each error is kept, in the order they failed.
+    /// let error = run(&mut happy.connect_addrs([v6_fails, v4_fails])).err().unwrap();
+    /// assert_eq!(error.errors(), [(v6_fails, v6_fails), (v4_fails, v4_fails)]);
+    /// ```
+    pub fn connect_addrs<I>(&self, addrs: I) -> HappyConnect<'_, C, T>
+    where
+        I: IntoIterator<Item = SocketAddr>,
+    {
+        HappyConnect {
+            happy: self,
+            queue: interleave(addrs.into_iter().collect(), self.first_family_count),
+            attempts: Vec::new(),
+            delay: None,
+            errors: Vec::new(),
+        }
+    }
+}
+
+/// Reorders `addrs` so the address families alternate, after the first
+/// `first_count` addresses of the first family.
+fn interleave(addrs: Vec<SocketAddr>, first_count: usize) -> VecDeque<SocketAddr> {
+    let first_is_ipv6 = match addrs.first() {
+        Some(addr) => addr.is_ipv6(),
+        None => return VecDeque::new(),
+    };
+    let (mut first, mut second): (VecDeque<_>, VecDeque<_>) = addrs
+        .into_iter()
+        .partition(|addr| addr.is_ipv6() == first_is_ipv6);
+
+    let mut queue = VecDeque::with_capacity(first.len() + second.len());
+    queue.extend(first.drain(..first_count.min(first.len())));
+    loop {
+        match (second.pop_front(), first.pop_front()) {
+            (None, None) => break,
+            (a, b) => queue.extend(a.into_iter().chain(b)),
+        }
+    }
+    queue
+}
+
+/// The future returned by [`HappyEyeballs::connect`] and
+/// [`HappyEyeballs::connect_addrs`].
+///
+/// This type is available when the `alloc` feature is enabled.
+#[must_use = "futures do nothing unless you `.await` or poll them"]
+pub struct HappyConnect<'a, C: AsyncTcpConnect, T: Timer> {
+    happy: &'a HappyEyeballs<C, T>,
+    queue: VecDeque<SocketAddr>,
+    #[allow(clippy::type_complexity)]
+    attempts: Vec<(SocketAddr, Pin<Box<C::Connect>>)>,
+    delay: Option<Pin<Box<T::Sleep>>>,
+    errors: Vec<(SocketAddr, C::Error)>,
+}
+
+impl<C: AsyncTcpConnect, T: Timer> HappyConnect<'_, C, T> {
+    /// Starts an attempt to connect to the next address, if there is one,
+    /// and restarts the delay before the one after.
+    fn start_next(&mut self) {
+        self.delay = None;
+        if let Some(addr) = self.queue.pop_front() {
+            let connect = self.happy.connector.connect(addr);
+            self.attempts.push((addr, Box::pin(connect)));
+            if !self.queue.is_empty() {
+                let timer = &self.happy.timer;
+                if let Some(deadline) = timer.now().checked_add(self.happy.attempt_delay) {
+                    self.delay = Some(Box::pin(timer.sleep_until(deadline)));
+                }
+            }
+        }
+    }
+}
+
+// Nothing is pinned in place: the futures are boxed, and the errors are
+// never pinned.
+impl<C: AsyncTcpConnect, T: Timer> Unpin for HappyConnect<'_, C, T> {}
+
+/// The future returned by [`HappyEyeballs::resolve_connect`].
+///
+/// This type is available when the `alloc` feature is enabled.
+#[must_use = "futures do nothing unless you `.await` or poll them"]
+pub struct HappyResolveConnect<'a, A, G, C: AsyncTcpConnect, T: Timer> {
+    happy: &'a HappyEyeballs<C, T>,
+    addr: A,
+    get: Pin<&'a mut G>,
+    connect: Option<HappyConnect<'a, C, T>>,
+}
+
+// The address is only ever borrowed, and the rest is `Unpin`.
+impl<A, G, C: AsyncTcpConnect, T: Timer> Unpin for HappyResolveConnect<'_, A, G, C, T> {}
+
+impl<A, G, C, T> Future for HappyResolveConnect<'_, A, G, C, T>
+where
+    A: AsyncToSocketAddrs<G>,
+    G: AsyncGetSocketAddrs,
+    C: AsyncTcpConnect,
+    T: Timer,
+{
+    type Output = Result<C::Stream, HappyConnectError<G::Error, C::Error>>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let this = self.get_mut();
+        let connect = match &mut this.connect {
+            Some(connect) => connect,
+            None => {
+                let addrs = match this.addr.poll_to_socket_addrs(cx, this.get.as_mut()) {
+                    Poll::Ready(Ok(addrs)) => addrs,
+                    Poll::Ready(Err(e)) => return Poll::Ready(Err(HappyConnectError::Resolve(e))),
+                    Poll::Pending => return Poll::Pending,
+                };
+                this.connect.get_or_insert(this.happy.connect_addrs(addrs))
+            }
+        };
+        Pin::new(connect)
+            .poll(cx)
+            .map_err(HappyConnectError::Connect)
+    }
+}
+
+impl<A, G, C, T> fmt::Debug for HappyResolveConnect<'_, A, G, C, T>
+where
+    A: fmt::Debug,
+    C: AsyncTcpConnect + fmt::Debug,
+    T: Timer + fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("HappyResolveConnect")
+            .field("addr", &self.addr)
+            .field("connect", &self.connect)
+            .finish()
+    }
+}
+
+impl<C: AsyncTcpConnect, T: Timer> Future for HappyConnect<'_, C, T> {
+    type Output = Result<C::Stream, HappyEyeballsError<C::Error>>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let this = self.get_mut();
+
+        if this.attempts.is_empty() && this.errors.is_empty() {
+            this.start_next();
+        }
+
+        loop {
+            let mut failed = false;
+            let mut i = 0;
+            while i < this.attempts.len() {
+                match this.attempts[i].1.as_mut().poll(cx) {
+                    Poll::Ready(Ok(stream)) => return Poll::Ready(Ok(stream)),
+                    Poll::Ready(Err(e)) => {
+                        let (addr, _) = this.attempts.swap_remove(i);
+                        this.errors.push((addr, e));
+                        failed = true;
+                    }
+                    Poll::Pending => i += 1,
+                }
+            }
+
+            // A failed attempt starts the next one straight away, rather than
+            // waiting out the delay.
+            let delay_done = match &mut this.delay {
+                Some(delay) => delay.as_mut().poll(cx).is_ready(),
+                None => false,
+            };
+            if (failed || delay_done) && !this.queue.is_empty() {
+                this.start_next();
+                continue;
+            }
+
+            if this.attempts.is_empty() {
+                return Poll::Ready(Err(HappyEyeballsError {
+                    errors: core::mem::take(&mut this.errors),
+                }));
+            }
+            return Poll::Pending;
+        }
+    }
+}
+
+impl<C, T> fmt::Debug for HappyConnect<'_, C, T>
+where
+    C: AsyncTcpConnect + fmt::Debug,
+    T: Timer + fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("HappyConnect")
+            .field("happy", &self.happy)
+            .field("queue", &self.queue)
+            .field(
+                "attempts",
+                &self
+                    .attempts
+                    .iter()
+                    .map(|(addr, _)| addr)
+                    .collect::<Vec<_>>(),
+            )
+            .finish()
+    }
+}
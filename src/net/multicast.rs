@@ -0,0 +1,229 @@
+use core::fmt;
+
+use crate::{
+    error::{impl_foreign_error, Error},
+    net::{Ipv4Addr, Ipv6Addr, MulticastSocket},
+};
+
+/// The error type for [`MulticastSocketExt`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum MulticastError<E> {
+    /// The group address isn't a multicast address.
+    NotMulticast,
+    /// The socket, or the network stack behind it, doesn't support the
+    /// operation.
+    Unsupported,
+    /// The operation failed.
+    Other(E),
+}
+
+impl<E> From<E> for MulticastError<E> {
+    fn from(e: E) -> Self {
+        MulticastError::Other(e)
+    }
+}
+
+impl<E> fmt::Display for MulticastError<E> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            MulticastError::NotMulticast => f.write_str("group is not a multicast address"),
+            MulticastError::Unsupported => f.write_str("multicast operation is not supported"),
+            MulticastError::Other(_) => f.write_str("multicast operation failed"),
+        }
+    }
+}
+
+impl<E: Error + 'static> Error for MulticastError<E> {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            MulticastError::Other(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl_foreign_error! {
+    impl<E> for MulticastError<E>;
+    fn source(&self) {
+        match self {
+            MulticastError::Other(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+/// More control over multicast than [`MulticastSocket`] gives: which
+/// interface to join on, the TTL or hop limit of multicast packets, whether
+/// they loop back to the local host, and source-specific multicast
+/// ([RFC 4607]).
+///
+/// Every method has a default implementation that returns
+/// [`MulticastError::Unsupported`], so an implementation only overrides what
+/// its network stack has. Implementations of the join and leave methods
+/// return [`MulticastError::NotMulticast`] if `group` isn't a multicast
+/// address, without asking the stack.
+///
+/// # Examples
+///
+/// ```
+/// # #[cfg(feature = "std")] {
+/// use coreplus::net::{Ipv4Addr, MulticastError, MulticastSocketExt, StdNet, UdpBind};
+///
+/// let socket = UdpBind::bind(&StdNet, "0.0.0.0:0".parse().unwrap())?;
+/// MulticastSocketExt::set_multicast_ttl_v4(&socket, 4)?;
+/// MulticastSocketExt::set_multicast_loop_v4(&socket, false)?;
+/// assert_eq!(MulticastSocketExt::multicast_ttl_v4(&socket)?, 4);
+///
+/// let not_a_group = Ipv4Addr::new(192, 0, 2, 1);
+/// assert!(matches!(
+///     MulticastSocketExt::join_multicast_v4(&socket, not_a_group, Ipv4Addr::UNSPECIFIED),
+///     Err(MulticastError::NotMulticast),
+/// ));
+/// # }
+/// # Ok::<(), coreplus::net::MulticastError<std::io::Error>>(())
+/// ```
+///
+/// [RFC 4607]: https://tools.ietf.org/html/rfc4607
+pub trait MulticastSocketExt: MulticastSocket {
+    /// Joins an IPv4 multicast group on the interface with the address
+    /// `interface`, or on one the stack chooses if it's
+    /// [`Ipv4Addr::UNSPECIFIED`].
+    fn join_multicast_v4(
+        &self,
+        group: Ipv4Addr,
+        interface: Ipv4Addr,
+    ) -> Result<(), MulticastError<Self::Error>> {
+        let _ = (group, interface);
+        Err(MulticastError::Unsupported)
+    }
+
+    /// Leaves an IPv4 multicast group joined with
+    /// [`join_multicast_v4`](Self::join_multicast_v4).
+    fn leave_multicast_v4(
+        &self,
+        group: Ipv4Addr,
+        interface: Ipv4Addr,
+    ) -> Result<(), MulticastError<Self::Error>> {
+        let _ = (group, interface);
+        Err(MulticastError::Unsupported)
+    }
+
+    /// Joins an IPv6 multicast group on the interface with the index
+    /// `interface`, or on one the stack chooses if it's `0`.
+    fn join_multicast_v6(
+        &self,
+        group: Ipv6Addr,
+        interface: u32,
+    ) -> Result<(), MulticastError<Self::Error>> {
+        let _ = (group, interface);
+        Err(MulticastError::Unsupported)
+    }
+
+    /// Leaves an IPv6 multicast group joined with
+    /// [`join_multicast_v6`](Self::join_multicast_v6).
+    fn leave_multicast_v6(
+        &self,
+        group: Ipv6Addr,
+        interface: u32,
+    ) -> Result<(), MulticastError<Self::Error>> {
+        let _ = (group, interface);
+        Err(MulticastError::Unsupported)
+    }
+
+    /// Joins an IPv4 source-specific multicast group, receiving only what
+    /// `source` sends to `group`.
+    fn join_source_multicast_v4(
+        &self,
+        source: Ipv4Addr,
+        group: Ipv4Addr,
+        interface: Ipv4Addr,
+    ) -> Result<(), MulticastError<Self::Error>> {
+        let _ = (source, group, interface);
+        Err(MulticastError::Unsupported)
+    }
+
+    /// Leaves an IPv4 source-specific multicast group joined with
+    /// [`join_source_multicast_v4`](Self::join_source_multicast_v4).
+    fn leave_source_multicast_v4(
+        &self,
+        source: Ipv4Addr,
+        group: Ipv4Addr,
+        interface: Ipv4Addr,
+    ) -> Result<(), MulticastError<Self::Error>> {
+        let _ = (source, group, interface);
+        Err(MulticastError::Unsupported)
+    }
+
+    /// Joins an IPv6 source-specific multicast group, receiving only what
+    /// `source` sends to `group`.
+    fn join_source_multicast_v6(
+        &self,
+        source: Ipv6Addr,
+        group: Ipv6Addr,
+        interface: u32,
+    ) -> Result<(), MulticastError<Self::Error>> {
+        let _ = (source, group, interface);
+        Err(MulticastError::Unsupported)
+    }
+
+    /// Leaves an IPv6 source-specific multicast group joined with
+    /// [`join_source_multicast_v6`](Self::join_source_multicast_v6).
+    fn leave_source_multicast_v6(
+        &self,
+        source: Ipv6Addr,
+        group: Ipv6Addr,
+        interface: u32,
+    ) -> Result<(), MulticastError<Self::Error>> {
+        let _ = (source, group, interface);
+        Err(MulticastError::Unsupported)
+    }
+
+    /// Gets the TTL of IPv4 multicast packets sent from this socket.
+    fn multicast_ttl_v4(&self) -> Result<u32, MulticastError<Self::Error>> {
+        Err(MulticastError::Unsupported)
+    }
+
+    /// Sets the TTL of IPv4 multicast packets sent from this socket. The
+    /// default of `1` keeps them on the local network.
+    fn set_multicast_ttl_v4(&self, ttl: u32) -> Result<(), MulticastError<Self::Error>> {
+        let _ = ttl;
+        Err(MulticastError::Unsupported)
+    }
+
+    /// Gets the hop limit of IPv6 multicast packets sent from this socket.
+    fn multicast_hops_v6(&self) -> Result<u32, MulticastError<Self::Error>> {
+        Err(MulticastError::Unsupported)
+    }
+
+    /// Sets the hop limit of IPv6 multicast packets sent from this socket.
+    fn set_multicast_hops_v6(&self, hops: u32) -> Result<(), MulticastError<Self::Error>> {
+        let _ = hops;
+        Err(MulticastError::Unsupported)
+    }
+
+    /// Gets whether IPv4 multicast packets sent from this socket are looped
+    /// back to the local host.
+    fn multicast_loop_v4(&self) -> Result<bool, MulticastError<Self::Error>> {
+        Err(MulticastError::Unsupported)
+    }
+
+    /// Sets whether IPv4 multicast packets sent from this socket are looped
+    /// back to the local host.
+    fn set_multicast_loop_v4(&self, on: bool) -> Result<(), MulticastError<Self::Error>> {
+        let _ = on;
+        Err(MulticastError::Unsupported)
+    }
+
+    /// Gets whether IPv6 multicast packets sent from this socket are looped
+    /// back to the local host.
+    fn multicast_loop_v6(&self) -> Result<bool, MulticastError<Self::Error>> {
+        Err(MulticastError::Unsupported)
+    }
+
+    /// Sets whether IPv6 multicast packets sent from this socket are looped
+    /// back to the local host.
+    fn set_multicast_loop_v6(&self, on: bool) -> Result<(), MulticastError<Self::Error>> {
+        let _ = on;
+        Err(MulticastError::Unsupported)
+    }
+}
@@ -0,0 +1,209 @@
+use core::{fmt, time::Duration};
+
+use crate::error::{impl_foreign_error, Error};
+
+/// The error type for socket options.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum OptionError<E> {
+    /// The socket, or the network stack behind it, doesn't support the
+    /// option.
+    Unsupported,
+    /// Getting or setting the option failed.
+    Other(E),
+}
+
+impl<E> From<E> for OptionError<E> {
+    fn from(e: E) -> Self {
+        OptionError::Other(e)
+    }
+}
+
+impl<E> fmt::Display for OptionError<E> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            OptionError::Unsupported => f.write_str("socket option is not supported"),
+            OptionError::Other(_) => f.write_str("failed to access socket option"),
+        }
+    }
+}
+
+impl<E: Error + 'static> Error for OptionError<E> {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            OptionError::Unsupported => None,
+            OptionError::Other(e) => Some(e),
+        }
+    }
+}
+
+impl_foreign_error! {
+    impl<E> for OptionError<E>;
+    fn source(&self) {
+        match self {
+            OptionError::Unsupported => None,
+            OptionError::Other(e) => Some(e),
+        }
+    }
+}
+
+/// Options common to all sockets.
+///
+/// Every method has a default implementation that returns
+/// [`OptionError::Unsupported`], so an implementation only overrides the
+/// options its network stack has.
+///
+/// # Examples
+///
+/// ```
+/// # #[cfg(feature = "std")] {
+/// use coreplus::net::{OptionError, SocketOptions, StdNet, UdpBind};
+///
+/// let socket = UdpBind::bind(&StdNet, "127.0.0.1:0".parse().unwrap())?;
+/// SocketOptions::set_ttl(&socket, 16)?;
+/// assert_eq!(SocketOptions::ttl(&socket)?, 16);
+///
+/// // std has no way to set `SO_REUSEADDR`.
+/// assert!(matches!(
+///     SocketOptions::set_reuse_address(&socket, true),
+///     Err(OptionError::Unsupported)
+/// ));
+/// # }
+/// # Ok::<(), coreplus::net::OptionError<std::io::Error>>(())
+/// ```
+pub trait SocketOptions {
+    /// The associated error type.
+    type Error;
+
+    /// Gets the time-to-live of IPv4 packets sent from this socket.
+    ///
+    /// This is `IP_TTL`, which doesn't set the hop limit of IPv6 packets;
+    /// see [`hop_limit`](Self::hop_limit) for that.
+    fn ttl(&self) -> Result<u32, OptionError<Self::Error>> {
+        Err(OptionError::Unsupported)
+    }
+
+    /// Sets the time-to-live of IPv4 packets sent from this socket.
+    ///
+    /// This is `IP_TTL`, which doesn't set the hop limit of IPv6 packets;
+    /// see [`set_hop_limit`](Self::set_hop_limit) for that.
+    fn set_ttl(&self, ttl: u32) -> Result<(), OptionError<Self::Error>> {
+        let _ = ttl;
+        Err(OptionError::Unsupported)
+    }
+
+    /// Gets the hop limit of unicast IPv6 packets sent from this socket.
+    ///
+    /// This is `IPV6_UNICAST_HOPS`, the IPv6 counterpart of
+    /// [`ttl`](Self::ttl).
+    fn hop_limit(&self) -> Result<u32, OptionError<Self::Error>> {
+        Err(OptionError::Unsupported)
+    }
+
+    /// Sets the hop limit of unicast IPv6 packets sent from this socket.
+    ///
+    /// This is `IPV6_UNICAST_HOPS`, the IPv6 counterpart of
+    /// [`set_ttl`](Self::set_ttl).
+    fn set_hop_limit(&self, hops: u32) -> Result<(), OptionError<Self::Error>> {
+        let _ = hops;
+        Err(OptionError::Unsupported)
+    }
+
+    /// Gets whether `SO_REUSEADDR` is set.
+    fn reuse_address(&self) -> Result<bool, OptionError<Self::Error>> {
+        Err(OptionError::Unsupported)
+    }
+
+    /// Sets `SO_REUSEADDR`, which lets the socket bind to an address that's
+    /// still in use.
+    fn set_reuse_address(&self, reuse: bool) -> Result<(), OptionError<Self::Error>> {
+        let _ = reuse;
+        Err(OptionError::Unsupported)
+    }
+
+    /// Gets the size of the send buffer in bytes.
+    fn send_buffer_size(&self) -> Result<usize, OptionError<Self::Error>> {
+        Err(OptionError::Unsupported)
+    }
+
+    /// Sets the size of the send buffer in bytes.
+    ///
+    /// The stack may round or clamp the size, so read it back to see what
+    /// was actually used.
+    fn set_send_buffer_size(&self, size: usize) -> Result<(), OptionError<Self::Error>> {
+        let _ = size;
+        Err(OptionError::Unsupported)
+    }
+
+    /// Gets the size of the receive buffer in bytes.
+    fn recv_buffer_size(&self) -> Result<usize, OptionError<Self::Error>> {
+        Err(OptionError::Unsupported)
+    }
+
+    /// Sets the size of the receive buffer in bytes.
+    ///
+    /// The stack may round or clamp the size, so read it back to see what
+    /// was actually used.
+    fn set_recv_buffer_size(&self, size: usize) -> Result<(), OptionError<Self::Error>> {
+        let _ = size;
+        Err(OptionError::Unsupported)
+    }
+}
+
+/// Options for TCP sockets.
+///
+/// Like [`SocketOptions`], every method defaults to returning
+/// [`OptionError::Unsupported`].
+pub trait TcpSocketOptions: SocketOptions {
+    /// Gets whether `TCP_NODELAY` is set.
+    fn nodelay(&self) -> Result<bool, OptionError<Self::Error>> {
+        Err(OptionError::Unsupported)
+    }
+
+    /// Sets `TCP_NODELAY`, which sends data as soon as possible instead of
+    /// waiting to fill a segment.
+    fn set_nodelay(&self, nodelay: bool) -> Result<(), OptionError<Self::Error>> {
+        let _ = nodelay;
+        Err(OptionError::Unsupported)
+    }
+
+    /// Gets the keepalive interval, or `None` if keepalive is off.
+    fn keepalive(&self) -> Result<Option<Duration>, OptionError<Self::Error>> {
+        Err(OptionError::Unsupported)
+    }
+
+    /// Turns keepalive on with the given interval, or off with `None`.
+    fn set_keepalive(&self, interval: Option<Duration>) -> Result<(), OptionError<Self::Error>> {
+        let _ = interval;
+        Err(OptionError::Unsupported)
+    }
+
+    /// Gets how long closing the socket waits for unsent data, or `None` if
+    /// `SO_LINGER` is off.
+    fn linger(&self) -> Result<Option<Duration>, OptionError<Self::Error>> {
+        Err(OptionError::Unsupported)
+    }
+
+    /// Sets how long closing the socket waits for unsent data, or turns
+    /// `SO_LINGER` off with `None`.
+    fn set_linger(&self, linger: Option<Duration>) -> Result<(), OptionError<Self::Error>> {
+        let _ = linger;
+        Err(OptionError::Unsupported)
+    }
+}
+
+/// Options for UDP sockets.
+///
+/// Like [`SocketOptions`], every method defaults to returning
+/// [`OptionError::Unsupported`].
+pub trait UdpSocketOptions: SocketOptions {
+    /// Gets whether `SO_BROADCAST` is set.
+    fn broadcast(&self) -> Result<bool, OptionError<Self::Error>> {
+        Err(OptionError::Unsupported)
+    }
+
+    /// Sets `SO_BROADCAST`, which allows sending to broadcast addresses.
+    fn set_broadcast(&self, broadcast: bool) -> Result<(), OptionError<Self::Error>> {
+        let _ = broadcast;
+        Err(OptionError::Unsupported)
+    }
+}
@@ -347,3 +347,7 @@ impl fmt::Display for AddrParseError {
         fmt.write_str("invalid IP address syntax")
     }
 }
+
+impl crate::error::Error for AddrParseError {}
+
+crate::error::impl_foreign_error!(AddrParseError);
@@ -0,0 +1,360 @@
+use core::fmt;
+
+use crate::net::{IpAddr, Ipv6Addr, Ipv6MulticastScope, SocketAddr};
+
+/// An entry in a [`PolicyTable`]: the precedence and label of every address
+/// that starts with `prefix`.
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub struct Policy {
+    /// The prefix this entry applies to. IPv4 addresses are looked up as
+    /// IPv4-mapped IPv6 addresses.
+    pub prefix: Ipv6Addr,
+    /// The length of the prefix in bits.
+    pub len: u8,
+    /// How much destinations with this prefix are preferred. Higher is
+    /// tried first.
+    pub precedence: u8,
+    /// Destinations are preferred when their label matches the label of
+    /// the source address that would be used to reach them.
+    pub label: u8,
+}
+
+impl Policy {
+    /// Creates a policy entry.
+    pub const fn new(prefix: Ipv6Addr, len: u8, precedence: u8, label: u8) -> Self {
+        Self {
+            prefix,
+            len,
+            precedence,
+            label,
+        }
+    }
+
+    fn matches(&self, addr: &Ipv6Addr) -> bool {
+        prefix_len(&self.prefix, addr) >= u32::from(self.len)
+    }
+}
+
+impl fmt::Debug for Policy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Policy")
+            .field("prefix", &format_args!("{}/{}", self.prefix, self.len))
+            .field("precedence", &self.precedence)
+            .field("label", &self.label)
+            .finish()
+    }
+}
+
+/// A policy table, as described in [RFC 6724 section 2.1].
+///
+/// Each address gets the precedence and label of the entry with the longest
+/// prefix that matches it. An address that matches nothing gets precedence
+/// and label `0`.
+///
+/// [RFC 6724 section 2.1]: https://tools.ietf.org/html/rfc6724#section-2.1
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct PolicyTable<'a> {
+    policies: &'a [Policy],
+}
+
+const DEFAULT_POLICIES: &[Policy] = &[
+    Policy::new(Ipv6Addr::new(0, 0, 0, 0, 0, 0, 0, 1), 128, 50, 0),
+    Policy::new(Ipv6Addr::new(0, 0, 0, 0, 0, 0, 0, 0), 0, 40, 1),
+    Policy::new(Ipv6Addr::new(0, 0, 0, 0, 0, 0xffff, 0, 0), 96, 35, 4),
+    Policy::new(Ipv6Addr::new(0x2002, 0, 0, 0, 0, 0, 0, 0), 16, 30, 2),
+    Policy::new(Ipv6Addr::new(0x2001, 0, 0, 0, 0, 0, 0, 0), 32, 5, 5),
+    Policy::new(Ipv6Addr::new(0xfc00, 0, 0, 0, 0, 0, 0, 0), 7, 3, 13),
+    Policy::new(Ipv6Addr::new(0, 0, 0, 0, 0, 0, 0, 0), 96, 1, 3),
+    Policy::new(Ipv6Addr::new(0xfec0, 0, 0, 0, 0, 0, 0, 0), 10, 1, 11),
+    Policy::new(Ipv6Addr::new(0x3ffe, 0, 0, 0, 0, 0, 0, 0), 16, 1, 12),
+];
+
+impl<'a> PolicyTable<'a> {
+    /// The default policy table from RFC 6724, which prefers IPv6 over
+    /// IPv4, and both over the deprecated transition mechanisms.
+    pub const DEFAULT: PolicyTable<'static> = PolicyTable {
+        policies: DEFAULT_POLICIES,
+    };
+
+    /// Creates a policy table from its entries, in any order.
+    pub const fn new(policies: &'a [Policy]) -> Self {
+        Self { policies }
+    }
+
+    /// Returns the entries of this table.
+    pub fn policies(&self) -> &'a [Policy] {
+        self.policies
+    }
+
+    /// Returns the precedence of `addr`.
+    pub fn precedence(&self, addr: IpAddr) -> u8 {
+        self.lookup(addr).map_or(0, |policy| policy.precedence)
+    }
+
+    /// Returns the label of `addr`.
+    pub fn label(&self, addr: IpAddr) -> u8 {
+        self.lookup(addr).map_or(0, |policy| policy.label)
+    }
+
+    /// Returns the entry with the longest prefix that matches `addr`.
+    pub fn lookup(&self, addr: IpAddr) -> Option<&'a Policy> {
+        let addr = to_ipv6(addr);
+        self.policies
+            .iter()
+            .filter(|policy| policy.matches(&addr))
+            .max_by_key(|policy| policy.len)
+    }
+}
+
+impl Default for PolicyTable<'_> {
+    fn default() -> Self {
+        PolicyTable::DEFAULT
+    }
+}
+
+/// Sorts destination addresses in the order [RFC 6724] says to try them.
+///
+/// Without knowing which source address would be used for each destination,
+/// only the rules about the destinations themselves apply: higher precedence
+/// first (rule 6), then smaller scope (rule 8), otherwise keeping the
+/// original order (rule 10). If the sources are known,
+/// [`sort_with_sources`](Self::sort_with_sources) applies the rules about
+/// them too. The rules about deprecated, home and encapsulated addresses
+/// (3, 4 and 7) are never applied, since nothing here knows about them.
+///
+/// The sorts are stable and don't allocate, but take quadratic time, which
+/// is fine for the handful of addresses a hostname usually has.
+///
+/// # Examples
+///
+/// ```
+/// use coreplus::net::{DestinationSorter, SocketAddr, SocketAddrIteratorExt};
+///
+/// let mut addrs: [SocketAddr; 3] = [
+///     "192.0.2.1:80".parse().unwrap(),
+///     "[fd00::1]:80".parse().unwrap(),
+///     "[2001:db8::1]:80".parse().unwrap(),
+/// ];
+///
+/// let sorter = DestinationSorter::default();
+/// sorter.sort(&mut addrs);
+/// assert_eq!(addrs[0], "[2001:db8::1]:80".parse().unwrap());
+/// assert_eq!(addrs[1], "192.0.2.1:80".parse().unwrap());
+/// assert_eq!(addrs[2], "[fd00::1]:80".parse().unwrap());
+///
+/// // Or as an iterator adapter.
+/// let first = addrs.iter().copied().rev().sort_by_policy(&sorter).next();
+/// assert_eq!(first, Some(addrs[0]));
+/// ```
+///
+/// [RFC 6724]: https://tools.ietf.org/html/rfc6724#section-6
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct DestinationSorter<'a> {
+    table: PolicyTable<'a>,
+}
+
+impl<'a> DestinationSorter<'a> {
+    /// Creates a sorter that uses `table` for precedences and labels.
+    pub const fn new(table: PolicyTable<'a>) -> Self {
+        Self { table }
+    }
+
+    /// Returns the policy table this sorter uses.
+    pub fn table(&self) -> PolicyTable<'a> {
+        self.table
+    }
+
+    /// Sorts `addrs`, most preferred first.
+    pub fn sort(&self, addrs: &mut [SocketAddr]) {
+        insertion_sort(addrs, |a, b| self.key(a.ip()) < self.key(b.ip()));
+    }
+
+    /// Sorts destinations paired with the source address that would be used
+    /// to reach each of them, most preferred first.
+    ///
+    /// A destination without a source is unreachable, and goes last
+    /// (rule 1). Otherwise destinations whose source has the same scope
+    /// (rule 2), then the same label (rule 5), are preferred, followed by
+    /// the rules [`sort`](Self::sort) uses. Between two IPv6 destinations,
+    /// the one sharing a longer prefix with its source goes first (rule 9).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use coreplus::net::{DestinationSorter, IpAddr, SocketAddr};
+    ///
+    /// let unreachable: SocketAddr = "[2001:db8::1]:80".parse().unwrap();
+    /// let mismatched: SocketAddr = "[2001:db8::2]:80".parse().unwrap();
+    /// let matched: SocketAddr = "[2002:c000:201::1]:80".parse().unwrap();
+    /// let six_to_four: IpAddr = "2002:c000:201::5".parse().unwrap();
+    ///
+    /// let mut addrs = [
+    ///     (unreachable, None),
+    ///     (mismatched, Some(six_to_four)),
+    ///     (matched, Some(six_to_four)),
+    /// ];
+    /// DestinationSorter::default().sort_with_sources(&mut addrs);
+    ///
+    /// // A 6to4 source has a different label from a native destination, so
+    /// // the 6to4 destination goes first despite its lower precedence, and
+    /// // the destination with no source goes last.
+    /// let sorted: Vec<_> = addrs.iter().map(|&(addr, _)| addr).collect();
+    /// assert_eq!(sorted, [matched, mismatched, unreachable]);
+    /// ```
+    pub fn sort_with_sources(&self, addrs: &mut [(SocketAddr, Option<IpAddr>)]) {
+        insertion_sort(addrs, |a, b| self.prefer(a, b));
+    }
+
+    /// The key [`sort`](Self::sort) orders by, smallest first.
+    pub(crate) fn key(&self, addr: IpAddr) -> (u8, u8) {
+        (u8::MAX - self.table.precedence(addr), scope(addr))
+    }
+
+    /// Returns whether `a` should be tried before `b`.
+    fn prefer(&self, a: &(SocketAddr, Option<IpAddr>), b: &(SocketAddr, Option<IpAddr>)) -> bool {
+        let (da, db) = (a.0.ip(), b.0.ip());
+        let (sa, sb) = match (a.1, b.1) {
+            (Some(sa), Some(sb)) => (sa, sb),
+            // Rule 1: avoid unusable destinations.
+            (sa, sb) => return sa.is_some() && sb.is_none(),
+        };
+
+        // Rule 2: prefer matching scope.
+        let scope_a = scope(da) == scope(sa);
+        let scope_b = scope(db) == scope(sb);
+        if scope_a != scope_b {
+            return scope_a;
+        }
+
+        // Rule 5: prefer matching label.
+        let label_a = self.table.label(da) == self.table.label(sa);
+        let label_b = self.table.label(db) == self.table.label(sb);
+        if label_a != label_b {
+            return label_a;
+        }
+
+        // Rules 6 and 8: prefer higher precedence, then smaller scope.
+        let (key_a, key_b) = (self.key(da), self.key(db));
+        if key_a != key_b {
+            return key_a < key_b;
+        }
+
+        // Rule 9: use the longest matching prefix.
+        match (da, sa, db, sb) {
+            (IpAddr::V6(da), IpAddr::V6(sa), IpAddr::V6(db), IpAddr::V6(sb)) => {
+                prefix_len(&da, &sa) > prefix_len(&db, &sb)
+            }
+            _ => false,
+        }
+    }
+}
+
+/// The scope of `addr`, as the scope values used by IPv6 multicast.
+fn scope(addr: IpAddr) -> u8 {
+    const INTERFACE_LOCAL: u8 = 0x1;
+    const LINK_LOCAL: u8 = 0x2;
+    const SITE_LOCAL: u8 = 0x5;
+    const GLOBAL: u8 = 0xe;
+
+    match addr {
+        // IPv4 loopback and autoconfiguration addresses count as link-local
+        // (RFC 6724 section 3.2).
+        IpAddr::V4(addr) if addr.is_loopback() || addr.is_link_local() => LINK_LOCAL,
+        IpAddr::V4(_) => GLOBAL,
+        IpAddr::V6(addr) => match addr.multicast_scope() {
+            Some(scope) => match scope {
+                Ipv6MulticastScope::InterfaceLocal => INTERFACE_LOCAL,
+                Ipv6MulticastScope::LinkLocal => LINK_LOCAL,
+                Ipv6MulticastScope::RealmLocal => 0x3,
+                Ipv6MulticastScope::AdminLocal => 0x4,
+                Ipv6MulticastScope::SiteLocal => SITE_LOCAL,
+                Ipv6MulticastScope::OrganizationLocal => 0x8,
+                Ipv6MulticastScope::Global => GLOBAL,
+            },
+            None if addr.is_multicast() => addr.segments()[0] as u8 & 0xf,
+            None => match addr.to_ipv4_mapped() {
+                Some(v4) => scope(IpAddr::V4(v4)),
+                None if addr.is_loopback() || addr.is_unicast_link_local() => LINK_LOCAL,
+                // Unique local addresses are global in scope, unlike the
+                // deprecated site-local ones.
+                None if addr.is_unique_local() => GLOBAL,
+                None if addr.segments()[0] & 0xffc0 == 0xfec0 => SITE_LOCAL,
+                None => GLOBAL,
+            },
+        },
+    }
+}
+
+fn to_ipv6(addr: IpAddr) -> Ipv6Addr {
+    match addr {
+        IpAddr::V4(addr) => addr.to_ipv6_mapped(),
+        IpAddr::V6(addr) => addr,
+    }
+}
+
+/// The number of leading bits `a` and `b` have in common.
+fn prefix_len(a: &Ipv6Addr, b: &Ipv6Addr) -> u32 {
+    let a = u128::from_be_bytes(a.octets());
+    let b = u128::from_be_bytes(b.octets());
+    (a ^ b).leading_zeros()
+}
+
+/// A stable sort that doesn't need an allocator.
+fn insertion_sort<T>(items: &mut [T], mut less: impl FnMut(&T, &T) -> bool) {
+    for i in 1..items.len() {
+        let mut j = i;
+        while j > 0 && less(&items[j], &items[j - 1]) {
+            items.swap(j, j - 1);
+            j -= 1;
+        }
+    }
+}
+
+/// The iterator returned by [`SocketAddrIteratorExt::sort_by_policy`].
+///
+/// [`SocketAddrIteratorExt::sort_by_policy`]: crate::net::SocketAddrIteratorExt::sort_by_policy
+#[derive(Clone, Debug)]
+pub struct SortByPolicy<'a, I> {
+    iter: I,
+    sorter: &'a DestinationSorter<'a>,
+    /// The key and position of the last address yielded.
+    last: Option<((u8, u8), usize)>,
+}
+
+impl<'a, I> SortByPolicy<'a, I> {
+    pub(crate) fn new(iter: I, sorter: &'a DestinationSorter<'a>) -> Self {
+        Self {
+            iter,
+            sorter,
+            last: None,
+        }
+    }
+}
+
+impl<I: Iterator<Item = SocketAddr> + Clone> Iterator for SortByPolicy<'_, I> {
+    type Item = SocketAddr;
+
+    fn next(&mut self) -> Option<SocketAddr> {
+        // Each call finds the smallest (key, position) after the last one
+        // yielded, so nothing needs to be buffered.
+        let last = self.last;
+        let (key, index, addr) = self
+            .iter
+            .clone()
+            .enumerate()
+            .map(|(index, addr)| (self.sorter.key(addr.ip()), index, addr))
+            .filter(|&(key, index, _)| match last {
+                Some(last) => (key, index) > last,
+                None => true,
+            })
+            .min_by_key(|&(key, index, _)| (key, index))?;
+        self.last = Some((key, index));
+        Some(addr)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (0, self.iter.size_hint().1)
+    }
+}
+
+impl<I: Iterator<Item = SocketAddr> + Clone> core::iter::FusedIterator for SortByPolicy<'_, I> {}
@@ -1,10 +1,29 @@
-use crate::net::{
-    AddrParseError, AsyncRecvFrom, AsyncSendTo, GetSocketAddrs, IpAddr, MulticastSocket, SocketAddr,
+use crate::{
+    io::{AsyncRead, AsyncWrite, CoreIO},
+    net::{
+        AddrParseError, AsyncGetSocketAddrs, AsyncRecv, AsyncRecvFrom, AsyncSend, AsyncSendTo,
+        AsyncTcpConnect, AsyncTcpListener, GetSocketAddrs, IpAddr, Ipv4Addr, Ipv6Addr, LocalAddr,
+        MulticastError, MulticastSocket, MulticastSocketExt, OptionError, PeerAddr, SocketAddr,
+        SocketOptions, TcpAccept, TcpBind, TcpConnect, TcpSocketOptions, UdpBind, UdpConnect,
+        UdpSocketOptions,
+    },
 };
 use core::{
+    future::Future,
     pin::Pin,
-    task::{Context, Poll},
+    task::{Context, Poll, Waker},
 };
+use std::{
+    io::{Read, Write},
+    net::{Shutdown, TcpListener, TcpStream, UdpSocket},
+    sync::{Arc, Mutex},
+    thread,
+    time::Duration,
+};
+
+mod nonblocking;
+
+pub use self::nonblocking::NonBlockingUdp;
 
 /// Zero-sized struct that is used for [`GetSocketAddrs`] for std.
 ///
@@ -25,6 +44,84 @@ impl GetSocketAddrs for StdGetSocketAddrs {
     }
 }
 
+/// Resolves hostnames with [`StdGetSocketAddrs`] on another thread, so it
+/// doesn't block.
+///
+/// Each hostname is resolved on a new thread, which wakes the task when
+/// it's done. Polling with a different host or port before then starts a
+/// new lookup, and the result of the old one is thrown away.
+///
+/// This type is available when the `std` feature is enabled.
+///
+/// # Examples
+///
+/// ```
+/// use core::pin::Pin;
+/// use coreplus::net::{AsyncGetSocketAddrs, StdAsyncGetSocketAddrs};
+/// use coreplus::task::{block_on, poll_fn};
+///
+/// let mut get = StdAsyncGetSocketAddrs::default();
+/// let mut addrs =
+///     block_on(poll_fn(|cx| Pin::new(&mut get).poll_get_socket_addrs(cx, "localhost", 80)))?;
+/// assert!(addrs.all(|addr| addr.port() == 80));
+/// # Ok::<(), std::io::Error>(())
+/// ```
+#[derive(Debug, Default)]
+pub struct StdAsyncGetSocketAddrs {
+    /// The lookup in progress, with the host and port it's for.
+    pending: Option<(String, u16, Arc<Mutex<Resolving>>)>,
+}
+
+#[derive(Debug)]
+struct Resolving {
+    result: Option<std::io::Result<std::vec::IntoIter<SocketAddr>>>,
+    waker: Waker,
+}
+
+impl AsyncGetSocketAddrs for StdAsyncGetSocketAddrs {
+    type Iter = std::vec::IntoIter<SocketAddr>;
+    type Error = std::io::Error;
+
+    fn poll_get_socket_addrs(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        host: &str,
+        port: u16,
+    ) -> Poll<Result<Self::Iter, Self::Error>> {
+        match &self.pending {
+            Some((pending_host, pending_port, shared))
+                if pending_host == host && *pending_port == port =>
+            {
+                let mut shared = shared.lock().unwrap_or_else(|e| e.into_inner());
+                if let Some(result) = shared.result.take() {
+                    drop(shared);
+                    self.pending = None;
+                    return Poll::Ready(result);
+                }
+                if !shared.waker.will_wake(cx.waker()) {
+                    shared.waker = cx.waker().clone();
+                }
+            }
+            _ => {
+                let shared = Arc::new(Mutex::new(Resolving {
+                    result: None,
+                    waker: cx.waker().clone(),
+                }));
+                let owned_host = host.to_owned();
+                let resolving = Arc::clone(&shared);
+                thread::spawn(move || {
+                    let result = StdGetSocketAddrs.get_socket_addrs(&owned_host, port);
+                    let mut resolving = resolving.lock().unwrap_or_else(|e| e.into_inner());
+                    resolving.result = Some(result);
+                    resolving.waker.wake_by_ref();
+                });
+                self.pending = Some((host.to_owned(), port, shared));
+            }
+        }
+        Poll::Pending
+    }
+}
+
 impl From<AddrParseError> for std::io::Error {
     fn from(_: AddrParseError) -> Self {
         std::io::Error::new(std::io::ErrorKind::InvalidInput, "address parsing error")
@@ -57,11 +154,94 @@ impl AsyncRecvFrom for std::net::UdpSocket {
     }
 }
 
+impl AsyncSend for std::net::UdpSocket {
+    type Error = std::io::Error;
+
+    fn poll_send(
+        self: Pin<&mut Self>,
+        _cx: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<Result<usize, std::io::Error>> {
+        Poll::Ready(self.send(buf))
+    }
+}
+
+impl AsyncRecv for std::net::UdpSocket {
+    type Error = std::io::Error;
+
+    fn poll_recv(
+        self: Pin<&mut Self>,
+        _cx: &mut Context<'_>,
+        buf: &mut [u8],
+    ) -> Poll<Result<usize, std::io::Error>> {
+        Poll::Ready(self.recv(buf))
+    }
+}
+
+impl UdpConnect for std::net::UdpSocket {
+    type Error = std::io::Error;
+
+    fn connect(&self, addr: SocketAddr) -> Result<(), std::io::Error> {
+        UdpSocket::connect(self, std::net::SocketAddr::from(addr))
+    }
+}
+
+impl LocalAddr for std::net::UdpSocket {
+    type Error = std::io::Error;
+
+    fn local_addr(&self) -> Result<SocketAddr, std::io::Error> {
+        UdpSocket::local_addr(self).map(Into::into)
+    }
+}
+
+impl PeerAddr for std::net::UdpSocket {
+    type Error = std::io::Error;
+
+    fn peer_addr(&self) -> Result<SocketAddr, std::io::Error> {
+        UdpSocket::peer_addr(self).map(Into::into)
+    }
+}
+
+impl SocketOptions for std::net::UdpSocket {
+    type Error = std::io::Error;
+
+    fn ttl(&self) -> Result<u32, OptionError<std::io::Error>> {
+        Ok(UdpSocket::ttl(self)?)
+    }
+
+    fn set_ttl(&self, ttl: u32) -> Result<(), OptionError<std::io::Error>> {
+        Ok(UdpSocket::set_ttl(self, ttl)?)
+    }
+
+    #[cfg(unix)]
+    fn hop_limit(&self) -> Result<u32, OptionError<std::io::Error>> {
+        Ok(unicast_hops(self)?)
+    }
+
+    #[cfg(unix)]
+    fn set_hop_limit(&self, hops: u32) -> Result<(), OptionError<std::io::Error>> {
+        Ok(set_unicast_hops(self, hops)?)
+    }
+}
+
+impl UdpSocketOptions for std::net::UdpSocket {
+    fn broadcast(&self) -> Result<bool, OptionError<std::io::Error>> {
+        Ok(UdpSocket::broadcast(self)?)
+    }
+
+    fn set_broadcast(&self, broadcast: bool) -> Result<(), OptionError<std::io::Error>> {
+        Ok(UdpSocket::set_broadcast(self, broadcast)?)
+    }
+}
+
 impl MulticastSocket for std::net::UdpSocket {
     type Error = std::io::Error;
 
     fn join_multicast(&self, addr: IpAddr) -> Result<(), std::io::Error> {
         use std::net::{IpAddr, SocketAddr};
+        if !addr.is_multicast() {
+            return Err(not_multicast());
+        }
         let local = self.local_addr()?;
         match &(addr.into(), local) {
             (IpAddr::V4(addr), SocketAddr::V4(local)) => self.join_multicast_v4(addr, local.ip()),
@@ -80,6 +260,9 @@ impl MulticastSocket for std::net::UdpSocket {
 
     fn leave_multicast(&self, addr: IpAddr) -> Result<(), std::io::Error> {
         use std::net::{IpAddr, SocketAddr};
+        if !addr.is_multicast() {
+            return Err(not_multicast());
+        }
         let local = self.local_addr()?;
         match &(addr.into(), local) {
             (IpAddr::V4(addr), SocketAddr::V4(local)) => self.leave_multicast_v4(addr, local.ip()),
@@ -96,3 +279,399 @@ impl MulticastSocket for std::net::UdpSocket {
         }
     }
 }
+
+impl MulticastSocketExt for std::net::UdpSocket {
+    fn join_multicast_v4(
+        &self,
+        group: Ipv4Addr,
+        interface: Ipv4Addr,
+    ) -> Result<(), MulticastError<std::io::Error>> {
+        if !group.is_multicast() {
+            return Err(MulticastError::NotMulticast);
+        }
+        Ok(UdpSocket::join_multicast_v4(
+            self,
+            &group.into(),
+            &interface.into(),
+        )?)
+    }
+
+    fn leave_multicast_v4(
+        &self,
+        group: Ipv4Addr,
+        interface: Ipv4Addr,
+    ) -> Result<(), MulticastError<std::io::Error>> {
+        if !group.is_multicast() {
+            return Err(MulticastError::NotMulticast);
+        }
+        Ok(UdpSocket::leave_multicast_v4(
+            self,
+            &group.into(),
+            &interface.into(),
+        )?)
+    }
+
+    fn join_multicast_v6(
+        &self,
+        group: Ipv6Addr,
+        interface: u32,
+    ) -> Result<(), MulticastError<std::io::Error>> {
+        if !group.is_multicast() {
+            return Err(MulticastError::NotMulticast);
+        }
+        Ok(UdpSocket::join_multicast_v6(
+            self,
+            &group.into(),
+            interface,
+        )?)
+    }
+
+    fn leave_multicast_v6(
+        &self,
+        group: Ipv6Addr,
+        interface: u32,
+    ) -> Result<(), MulticastError<std::io::Error>> {
+        if !group.is_multicast() {
+            return Err(MulticastError::NotMulticast);
+        }
+        Ok(UdpSocket::leave_multicast_v6(
+            self,
+            &group.into(),
+            interface,
+        )?)
+    }
+
+    fn multicast_ttl_v4(&self) -> Result<u32, MulticastError<std::io::Error>> {
+        Ok(UdpSocket::multicast_ttl_v4(self)?)
+    }
+
+    fn set_multicast_ttl_v4(&self, ttl: u32) -> Result<(), MulticastError<std::io::Error>> {
+        Ok(UdpSocket::set_multicast_ttl_v4(self, ttl)?)
+    }
+
+    fn multicast_loop_v4(&self) -> Result<bool, MulticastError<std::io::Error>> {
+        Ok(UdpSocket::multicast_loop_v4(self)?)
+    }
+
+    fn set_multicast_loop_v4(&self, on: bool) -> Result<(), MulticastError<std::io::Error>> {
+        Ok(UdpSocket::set_multicast_loop_v4(self, on)?)
+    }
+
+    fn multicast_loop_v6(&self) -> Result<bool, MulticastError<std::io::Error>> {
+        Ok(UdpSocket::multicast_loop_v6(self)?)
+    }
+
+    fn set_multicast_loop_v6(&self, on: bool) -> Result<(), MulticastError<std::io::Error>> {
+        Ok(UdpSocket::set_multicast_loop_v6(self, on)?)
+    }
+}
+
+/// Gets `IPV6_UNICAST_HOPS`, which std has no method for.
+#[cfg(unix)]
+fn unicast_hops(socket: &impl std::os::unix::io::AsRawFd) -> std::io::Result<u32> {
+    let mut hops: libc::c_int = 0;
+    let mut len = core::mem::size_of::<libc::c_int>() as libc::socklen_t;
+    // SAFETY: `hops` and `len` are valid for writes, and `len` is the size
+    // of `hops`.
+    let ret = unsafe {
+        libc::getsockopt(
+            socket.as_raw_fd(),
+            libc::IPPROTO_IPV6,
+            libc::IPV6_UNICAST_HOPS,
+            (&mut hops as *mut libc::c_int).cast(),
+            &mut len,
+        )
+    };
+    if ret != 0 {
+        return Err(std::io::Error::last_os_error());
+    }
+    Ok(hops as u32)
+}
+
+/// Sets `IPV6_UNICAST_HOPS`, which std has no method for.
+#[cfg(unix)]
+fn set_unicast_hops(socket: &impl std::os::unix::io::AsRawFd, hops: u32) -> std::io::Result<()> {
+    use core::convert::TryFrom;
+
+    let hops = libc::c_int::try_from(hops).map_err(|_| {
+        std::io::Error::new(std::io::ErrorKind::InvalidInput, "hop limit out of range")
+    })?;
+    // SAFETY: `hops` is valid for reads of the given size.
+    let ret = unsafe {
+        libc::setsockopt(
+            socket.as_raw_fd(),
+            libc::IPPROTO_IPV6,
+            libc::IPV6_UNICAST_HOPS,
+            (&hops as *const libc::c_int).cast(),
+            core::mem::size_of::<libc::c_int>() as libc::socklen_t,
+        )
+    };
+    if ret != 0 {
+        return Err(std::io::Error::last_os_error());
+    }
+    Ok(())
+}
+
+fn not_multicast() -> std::io::Error {
+    std::io::Error::new(
+        std::io::ErrorKind::InvalidInput,
+        "group is not a multicast address",
+    )
+}
+
+/// Zero-sized struct that implements the network stack traits, such as
+/// [`AsyncTcpConnect`], [`TcpConnect`], [`TcpBind`] and [`UdpBind`], with
+/// [`std::net`].
+///
+/// Connecting doesn't need a runtime: each pending [`StdTcpConnect`] starts a
+/// thread that makes the blocking connection and wakes it when it's done.
+/// That's a thread per attempt, so it suits a handful of connections rather
+/// than thousands. Once connected, the streams and listeners themselves block, like
+/// [`std::net::UdpSocket`]'s implementation of [`AsyncSendTo`].
+///
+/// This type is available when the `std` feature is enabled.
+///
+/// # Examples
+///
+/// ```
+/// use core::pin::Pin;
+/// use coreplus::net::{AsyncTcpConnect, AsyncTcpListener, LocalAddr, StdNet, TcpBind};
+/// use coreplus::task::{block_on, poll_fn};
+///
+/// let mut listener = StdNet.bind("127.0.0.1:0".parse().unwrap())?;
+/// let addr = LocalAddr::local_addr(&listener)?;
+///
+/// let stream = block_on(StdNet.connect(addr))?;
+/// let (_, peer) = block_on(poll_fn(|cx| Pin::new(&mut listener).poll_accept(cx)))?;
+/// assert_eq!(peer, LocalAddr::local_addr(&stream)?);
+/// # Ok::<(), std::io::Error>(())
+/// ```
+#[derive(Clone, Copy, Debug, Default)]
+pub struct StdNet;
+
+impl AsyncTcpConnect for StdNet {
+    type Stream = TcpStream;
+    type Error = std::io::Error;
+    type Connect = StdTcpConnect;
+
+    fn connect(&self, addr: SocketAddr) -> Self::Connect {
+        StdTcpConnect {
+            addr: addr.into(),
+            shared: None,
+        }
+    }
+}
+
+impl TcpConnect for StdNet {
+    type Stream = CoreIO<TcpStream>;
+    type Error = std::io::Error;
+
+    fn connect_addr(&self, addr: SocketAddr) -> Result<Self::Stream, Self::Error> {
+        TcpStream::connect(std::net::SocketAddr::from(addr)).map(CoreIO)
+    }
+}
+
+impl UdpBind for StdNet {
+    type Socket = UdpSocket;
+    type Error = std::io::Error;
+
+    fn bind(&self, addr: SocketAddr) -> Result<Self::Socket, Self::Error> {
+        UdpSocket::bind(std::net::SocketAddr::from(addr))
+    }
+}
+
+impl TcpBind for StdNet {
+    type Listener = TcpListener;
+    type Error = std::io::Error;
+
+    fn bind(&self, addr: SocketAddr) -> Result<Self::Listener, Self::Error> {
+        TcpListener::bind(std::net::SocketAddr::from(addr))
+    }
+}
+
+/// The future returned by [`StdNet::connect`](AsyncTcpConnect::connect).
+///
+/// The first poll spawns a thread to connect. The thread can't be
+/// cancelled, so dropping the future, for example when a
+/// [`Timeout`](crate::time::Timeout) expires, leaves it running until the
+/// connection succeeds or fails. It gives up after 30 seconds, so
+/// an unreachable address doesn't hold a thread for as long as the
+/// operating system would retry, which is about two minutes on Linux.
+///
+/// This type is available when the `std` feature is enabled.
+#[derive(Debug)]
+#[must_use = "futures do nothing unless you `.await` or poll them"]
+pub struct StdTcpConnect {
+    addr: std::net::SocketAddr,
+    shared: Option<Arc<Mutex<Connecting>>>,
+}
+
+/// How long a [`StdTcpConnect`] waits for a connection before failing with
+/// [`TimedOut`](std::io::ErrorKind::TimedOut).
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(30);
+
+#[derive(Debug)]
+struct Connecting {
+    result: Option<std::io::Result<TcpStream>>,
+    waker: Waker,
+}
+
+impl Future for StdTcpConnect {
+    type Output = std::io::Result<TcpStream>;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        match &self.shared {
+            Some(shared) => {
+                let mut shared = shared.lock().unwrap_or_else(|e| e.into_inner());
+                if let Some(result) = shared.result.take() {
+                    return Poll::Ready(result);
+                }
+                if !shared.waker.will_wake(cx.waker()) {
+                    shared.waker = cx.waker().clone();
+                }
+            }
+            None => {
+                let shared = Arc::new(Mutex::new(Connecting {
+                    result: None,
+                    waker: cx.waker().clone(),
+                }));
+                let addr = self.addr;
+                let connecting = Arc::clone(&shared);
+                thread::spawn(move || {
+                    let result = TcpStream::connect_timeout(&addr, CONNECT_TIMEOUT);
+                    let mut connecting = connecting.lock().unwrap_or_else(|e| e.into_inner());
+                    connecting.result = Some(result);
+                    connecting.waker.wake_by_ref();
+                });
+                self.shared = Some(shared);
+            }
+        }
+        Poll::Pending
+    }
+}
+
+impl AsyncTcpListener for TcpListener {
+    type Stream = TcpStream;
+    type Error = std::io::Error;
+
+    fn poll_accept(
+        self: Pin<&mut Self>,
+        _cx: &mut Context<'_>,
+    ) -> Poll<Result<(TcpStream, SocketAddr), std::io::Error>> {
+        Poll::Ready(self.accept().map(|(stream, addr)| (stream, addr.into())))
+    }
+}
+
+impl TcpAccept for TcpListener {
+    type Stream = CoreIO<TcpStream>;
+    type Error = std::io::Error;
+
+    fn accept(&mut self) -> Result<(CoreIO<TcpStream>, SocketAddr), std::io::Error> {
+        TcpListener::accept(self).map(|(stream, addr)| (CoreIO(stream), addr.into()))
+    }
+}
+
+impl AsyncRead for TcpStream {
+    type Error = std::io::Error;
+
+    fn poll_read(
+        self: Pin<&mut Self>,
+        _cx: &mut Context<'_>,
+        buf: &mut [u8],
+    ) -> Poll<Result<usize, std::io::Error>> {
+        Poll::Ready(self.get_mut().read(buf))
+    }
+}
+
+impl AsyncWrite for TcpStream {
+    type Error = std::io::Error;
+
+    fn poll_write(
+        self: Pin<&mut Self>,
+        _cx: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<Result<usize, std::io::Error>> {
+        Poll::Ready(self.get_mut().write(buf))
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<Result<(), std::io::Error>> {
+        Poll::Ready(self.get_mut().flush())
+    }
+
+    /// Shuts down the writing half of the connection.
+    fn poll_close(self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<Result<(), std::io::Error>> {
+        Poll::Ready(self.shutdown(Shutdown::Write))
+    }
+}
+
+impl LocalAddr for TcpStream {
+    type Error = std::io::Error;
+
+    fn local_addr(&self) -> Result<SocketAddr, std::io::Error> {
+        TcpStream::local_addr(self).map(Into::into)
+    }
+}
+
+impl PeerAddr for TcpStream {
+    type Error = std::io::Error;
+
+    fn peer_addr(&self) -> Result<SocketAddr, std::io::Error> {
+        TcpStream::peer_addr(self).map(Into::into)
+    }
+}
+
+impl LocalAddr for CoreIO<TcpStream> {
+    type Error = std::io::Error;
+
+    fn local_addr(&self) -> Result<SocketAddr, std::io::Error> {
+        self.0.local_addr().map(Into::into)
+    }
+}
+
+impl PeerAddr for CoreIO<TcpStream> {
+    type Error = std::io::Error;
+
+    fn peer_addr(&self) -> Result<SocketAddr, std::io::Error> {
+        self.0.peer_addr().map(Into::into)
+    }
+}
+
+impl LocalAddr for TcpListener {
+    type Error = std::io::Error;
+
+    fn local_addr(&self) -> Result<SocketAddr, std::io::Error> {
+        TcpListener::local_addr(self).map(Into::into)
+    }
+}
+
+impl SocketOptions for TcpStream {
+    type Error = std::io::Error;
+
+    fn ttl(&self) -> Result<u32, OptionError<std::io::Error>> {
+        Ok(TcpStream::ttl(self)?)
+    }
+
+    fn set_ttl(&self, ttl: u32) -> Result<(), OptionError<std::io::Error>> {
+        Ok(TcpStream::set_ttl(self, ttl)?)
+    }
+
+    #[cfg(unix)]
+    fn hop_limit(&self) -> Result<u32, OptionError<std::io::Error>> {
+        Ok(unicast_hops(self)?)
+    }
+
+    #[cfg(unix)]
+    fn set_hop_limit(&self, hops: u32) -> Result<(), OptionError<std::io::Error>> {
+        Ok(set_unicast_hops(self, hops)?)
+    }
+}
+
+impl TcpSocketOptions for TcpStream {
+    fn nodelay(&self) -> Result<bool, OptionError<std::io::Error>> {
+        Ok(TcpStream::nodelay(self)?)
+    }
+
+    fn set_nodelay(&self, nodelay: bool) -> Result<(), OptionError<std::io::Error>> {
+        Ok(TcpStream::set_nodelay(self, nodelay)?)
+    }
+}
@@ -0,0 +1,189 @@
+use crate::net::{
+    AsyncRecv, AsyncRecvFrom, AsyncSend, AsyncSendTo, LocalAddr, PeerAddr, SocketAddr,
+};
+use core::{
+    pin::Pin,
+    sync::atomic::{AtomicUsize, Ordering},
+    task::{Context, Poll},
+};
+use std::{io::ErrorKind, net::UdpSocket};
+
+#[cfg(not(unix))]
+mod backoff;
+#[cfg(unix)]
+mod poll;
+
+#[cfg(not(unix))]
+use self::backoff as reactor;
+#[cfg(unix)]
+use self::poll as reactor;
+
+/// A UDP socket in non-blocking mode, whose async IO returns
+/// [`Poll::Pending`] instead of blocking the thread.
+///
+/// A single readiness thread, started the first time anything has to wait,
+/// wakes the tasks whose sockets are ready. On Unix it blocks in `poll(2)`,
+/// so a socket waiting for a datagram costs nothing until one arrives, and
+/// each new wait costs a write to a pipe to add it to the set.
+///
+/// std has no portable way to wait for a socket elsewhere, so there the
+/// thread checks the waiting sockets itself, starting a millisecond after a
+/// wait begins and backing off to every 64 milliseconds while nothing
+/// happens. A receive is woken when a datagram has arrived, and a send that
+/// would block is woken on the next check to try again. That costs a
+/// wakeup and a syscall per waiting socket each check, and up to 64
+/// milliseconds of latency on a socket that has been idle.
+///
+/// This type is available when the `std` feature is enabled.
+///
+/// # Examples
+///
+/// ```
+/// use core::pin::Pin;
+/// use coreplus::net::{AsyncRecvFrom, NonBlockingUdp};
+/// use coreplus::task::{block_on, poll_fn};
+/// use std::{net::UdpSocket, thread, time::Duration};
+///
+/// let mut socket = NonBlockingUdp::new(UdpSocket::bind("127.0.0.1:0")?)?;
+/// let addr = socket.get_ref().local_addr()?;
+///
+/// let sender = thread::spawn(move || {
+///     thread::sleep(Duration::from_millis(20));
+///     UdpSocket::bind("127.0.0.1:0")?.send_to(b"ping", addr)
+/// });
+///
+/// // Nothing has arrived yet, so this is pending until the sender sends.
+/// let mut buf = [0; 8];
+/// let (n, _) = block_on(poll_fn(|cx| Pin::new(&mut socket).poll_recv_from(cx, &mut buf)))?;
+/// assert_eq!(&buf[..n], b"ping");
+/// sender.join().unwrap()?;
+/// # Ok::<(), std::io::Error>(())
+/// ```
+#[derive(Debug)]
+pub struct NonBlockingUdp {
+    socket: UdpSocket,
+    id: usize,
+}
+
+impl NonBlockingUdp {
+    /// Puts `socket` in non-blocking mode and wraps it.
+    pub fn new(socket: UdpSocket) -> std::io::Result<Self> {
+        static NEXT_ID: AtomicUsize = AtomicUsize::new(0);
+
+        socket.set_nonblocking(true)?;
+        Ok(Self {
+            socket,
+            id: NEXT_ID.fetch_add(1, Ordering::Relaxed),
+        })
+    }
+
+    /// Gets a reference to the underlying socket.
+    pub fn get_ref(&self) -> &UdpSocket {
+        &self.socket
+    }
+
+    /// Unwraps this `NonBlockingUdp`, returning the underlying socket.
+    ///
+    /// The socket is left in non-blocking mode.
+    pub fn into_inner(self) -> UdpSocket {
+        let this = core::mem::ManuallyDrop::new(self);
+        reactor::deregister(this.id);
+        // SAFETY: `this` is never used or dropped again.
+        unsafe { core::ptr::read(&this.socket) }
+    }
+
+    fn poll_io<T>(
+        &self,
+        cx: &mut Context<'_>,
+        interest: Interest,
+        io: impl FnOnce(&UdpSocket) -> std::io::Result<T>,
+    ) -> Poll<std::io::Result<T>> {
+        match io(&self.socket) {
+            Err(e) if e.kind() == ErrorKind::WouldBlock => {
+                reactor::register(self.id, &self.socket, interest, cx.waker())?;
+                Poll::Pending
+            }
+            result => Poll::Ready(result),
+        }
+    }
+}
+
+impl Drop for NonBlockingUdp {
+    fn drop(&mut self) {
+        reactor::deregister(self.id);
+    }
+}
+
+impl AsyncSendTo for NonBlockingUdp {
+    type Error = std::io::Error;
+
+    fn poll_send_to(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &[u8],
+        addr: SocketAddr,
+    ) -> Poll<Result<usize, std::io::Error>> {
+        let addr: std::net::SocketAddr = addr.into();
+        self.poll_io(cx, Interest::Write, |s| s.send_to(buf, addr))
+    }
+}
+
+impl AsyncRecvFrom for NonBlockingUdp {
+    type Error = std::io::Error;
+
+    fn poll_recv_from(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut [u8],
+    ) -> Poll<Result<(usize, SocketAddr), std::io::Error>> {
+        self.poll_io(cx, Interest::Read, |s| {
+            s.recv_from(buf).map(|(n, addr)| (n, addr.into()))
+        })
+    }
+}
+
+impl AsyncSend for NonBlockingUdp {
+    type Error = std::io::Error;
+
+    fn poll_send(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<Result<usize, std::io::Error>> {
+        self.poll_io(cx, Interest::Write, |s| s.send(buf))
+    }
+}
+
+impl AsyncRecv for NonBlockingUdp {
+    type Error = std::io::Error;
+
+    fn poll_recv(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut [u8],
+    ) -> Poll<Result<usize, std::io::Error>> {
+        self.poll_io(cx, Interest::Read, |s| s.recv(buf))
+    }
+}
+
+impl LocalAddr for NonBlockingUdp {
+    type Error = std::io::Error;
+
+    fn local_addr(&self) -> Result<SocketAddr, std::io::Error> {
+        self.socket.local_addr().map(Into::into)
+    }
+}
+
+impl PeerAddr for NonBlockingUdp {
+    type Error = std::io::Error;
+
+    fn peer_addr(&self) -> Result<SocketAddr, std::io::Error> {
+        self.socket.peer_addr().map(Into::into)
+    }
+}
+
+#[derive(Clone, Copy)]
+enum Interest {
+    Read,
+    Write,
+}
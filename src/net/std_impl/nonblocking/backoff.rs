@@ -0,0 +1,132 @@
+//! A readiness thread that checks the waiting sockets itself, for platforms
+//! without `poll(2)`.
+
+use std::{
+    io,
+    net::UdpSocket,
+    sync::{Mutex, MutexGuard},
+    task::Waker,
+    thread::{self, Thread},
+    time::Duration,
+};
+
+use super::Interest;
+
+/// How long the thread waits before the first check after a wait begins.
+const MIN_DELAY: Duration = Duration::from_millis(1);
+/// How long the thread waits between checks once nothing has happened for a
+/// while.
+const MAX_DELAY: Duration = Duration::from_millis(64);
+
+/// A socket with a task waiting on it.
+struct Waiting {
+    id: usize,
+    /// A handle to the same socket. It's non-blocking too, since the flag
+    /// belongs to the underlying socket.
+    socket: UdpSocket,
+    read: Option<Waker>,
+    write: Option<Waker>,
+}
+
+struct Reactor {
+    waiting: Vec<Waiting>,
+    thread: Option<Thread>,
+}
+
+static REACTOR: Mutex<Reactor> = Mutex::new(Reactor {
+    waiting: Vec::new(),
+    thread: None,
+});
+
+fn lock() -> MutexGuard<'static, Reactor> {
+    REACTOR.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+pub(super) fn register(
+    id: usize,
+    socket: &UdpSocket,
+    interest: Interest,
+    waker: &Waker,
+) -> io::Result<()> {
+    let mut reactor = lock();
+    if reactor.thread.is_none() {
+        let thread = thread::Builder::new()
+            .name("coreplus-udp-readiness".into())
+            .spawn(run)?;
+        reactor.thread = Some(thread.thread().clone());
+    }
+
+    let index = match reactor.waiting.iter().position(|w| w.id == id) {
+        Some(index) => index,
+        None => {
+            let socket = socket.try_clone()?;
+            reactor.waiting.push(Waiting {
+                id,
+                socket,
+                read: None,
+                write: None,
+            });
+            reactor.waiting.len() - 1
+        }
+    };
+    let slot = match interest {
+        Interest::Read => &mut reactor.waiting[index].read,
+        Interest::Write => &mut reactor.waiting[index].write,
+    };
+    match slot {
+        Some(old) if old.will_wake(waker) => {}
+        _ => *slot = Some(waker.clone()),
+    }
+
+    // Check again soon, rather than after however long the thread has
+    // backed off to.
+    if let Some(thread) = &reactor.thread {
+        thread.unpark();
+    }
+    Ok(())
+}
+
+pub(super) fn deregister(id: usize) {
+    lock().waiting.retain(|w| w.id != id);
+}
+
+fn run() {
+    let mut delay = MIN_DELAY;
+    let mut ready = Vec::new();
+    loop {
+        let mut reactor = lock();
+        for w in &mut reactor.waiting {
+            ready.extend(w.write.take());
+            if w.read.is_some() && is_readable(&w.socket) {
+                ready.extend(w.read.take());
+            }
+        }
+        reactor.waiting.retain(|w| w.read.is_some());
+        let waiting = !reactor.waiting.is_empty();
+        drop(reactor);
+
+        let woke = !ready.is_empty();
+        for waker in ready.drain(..) {
+            waker.wake();
+        }
+
+        if !waiting {
+            delay = MIN_DELAY;
+            thread::park();
+            continue;
+        }
+        delay = if woke {
+            MIN_DELAY
+        } else {
+            (delay * 2).min(MAX_DELAY)
+        };
+        // A new wait unparks the thread, so it's checked promptly.
+        thread::park_timeout(delay);
+    }
+}
+
+fn is_readable(socket: &UdpSocket) -> bool {
+    // Any error other than `WouldBlock` is reported by the receive itself,
+    // so it counts as ready too.
+    !matches!(socket.peek_from(&mut [0]), Err(e) if e.kind() == io::ErrorKind::WouldBlock)
+}
@@ -0,0 +1,201 @@
+//! A readiness thread that waits in `poll(2)`.
+
+use std::{
+    io,
+    net::UdpSocket,
+    os::unix::io::{AsRawFd, RawFd},
+    sync::{Mutex, MutexGuard},
+    task::Waker,
+    thread,
+};
+
+use super::Interest;
+
+/// A socket with a task waiting on it.
+struct Waiting {
+    id: usize,
+    fd: RawFd,
+    read: Option<Waker>,
+    write: Option<Waker>,
+}
+
+struct Reactor {
+    waiting: Vec<Waiting>,
+    /// The write end of the pipe that interrupts `poll`, once the thread has
+    /// started.
+    notify: Option<RawFd>,
+}
+
+static REACTOR: Mutex<Reactor> = Mutex::new(Reactor {
+    waiting: Vec::new(),
+    notify: None,
+});
+
+fn lock() -> MutexGuard<'static, Reactor> {
+    REACTOR.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+pub(super) fn register(
+    id: usize,
+    socket: &UdpSocket,
+    interest: Interest,
+    waker: &Waker,
+) -> io::Result<()> {
+    let mut reactor = lock();
+    let notify = match reactor.notify {
+        Some(notify) => notify,
+        None => {
+            let notify = start()?;
+            reactor.notify = Some(notify);
+            notify
+        }
+    };
+
+    let index = match reactor.waiting.iter().position(|w| w.id == id) {
+        Some(index) => index,
+        None => {
+            reactor.waiting.push(Waiting {
+                id,
+                fd: socket.as_raw_fd(),
+                read: None,
+                write: None,
+            });
+            reactor.waiting.len() - 1
+        }
+    };
+    let slot = match interest {
+        Interest::Read => &mut reactor.waiting[index].read,
+        Interest::Write => &mut reactor.waiting[index].write,
+    };
+    match slot {
+        // Already in the set, so `poll` doesn't need to hear about it.
+        Some(old) => {
+            if !old.will_wake(waker) {
+                *old = waker.clone();
+            }
+        }
+        None => {
+            *slot = Some(waker.clone());
+            // If the pipe is full, `poll` is going to return anyway.
+            // SAFETY: `notify` is the open write end of the pipe, and the
+            // buffer is one byte long.
+            unsafe { libc::write(notify, [0u8].as_ptr().cast(), 1) };
+        }
+    }
+    Ok(())
+}
+
+pub(super) fn deregister(id: usize) {
+    // The thread may still be polling the descriptor, but nothing is woken
+    // for it once it's gone from here.
+    lock().waiting.retain(|w| w.id != id);
+}
+
+/// Creates the pipe and starts the thread, returning the write end.
+fn start() -> io::Result<RawFd> {
+    let mut fds = [0; 2];
+    // SAFETY: `fds` has room for the two descriptors `pipe` writes.
+    if unsafe { libc::pipe(fds.as_mut_ptr()) } != 0 {
+        return Err(io::Error::last_os_error());
+    }
+    for &fd in &fds {
+        // SAFETY: `fd` was just opened by `pipe`.
+        unsafe {
+            libc::fcntl(fd, libc::F_SETFL, libc::O_NONBLOCK);
+            libc::fcntl(fd, libc::F_SETFD, libc::FD_CLOEXEC);
+        }
+    }
+    let [read, write] = fds;
+
+    let spawned = thread::Builder::new()
+        .name("coreplus-udp-readiness".into())
+        .spawn(move || run(read));
+    if let Err(e) = spawned {
+        // SAFETY: nothing else has seen the descriptors.
+        unsafe {
+            libc::close(read);
+            libc::close(write);
+        }
+        return Err(e);
+    }
+    Ok(write)
+}
+
+fn run(notify: RawFd) -> ! {
+    let mut fds = Vec::new();
+    let mut ids = Vec::new();
+    let mut ready = Vec::new();
+    loop {
+        fds.clear();
+        ids.clear();
+        fds.push(libc::pollfd {
+            fd: notify,
+            events: libc::POLLIN,
+            revents: 0,
+        });
+        for w in &lock().waiting {
+            let mut events = 0;
+            if w.read.is_some() {
+                events |= libc::POLLIN;
+            }
+            if w.write.is_some() {
+                events |= libc::POLLOUT;
+            }
+            fds.push(libc::pollfd {
+                fd: w.fd,
+                events,
+                revents: 0,
+            });
+            ids.push(w.id);
+        }
+
+        // SAFETY: `fds` is an array of `fds.len()` initialized `pollfd`s.
+        let n = unsafe { libc::poll(fds.as_mut_ptr(), fds.len() as libc::nfds_t, -1) };
+        if n < 0 {
+            // Only `EINTR` is expected here. Anything else would fail again
+            // straight away, so stop rather than spin.
+            let error = io::Error::last_os_error();
+            assert_eq!(
+                error.kind(),
+                io::ErrorKind::Interrupted,
+                "poll failed: {}",
+                error
+            );
+            continue;
+        }
+
+        if fds[0].revents != 0 {
+            let mut buf = [0u8; 64];
+            // SAFETY: `notify` is the open, non-blocking read end of the
+            // pipe, and `buf` has room for what's asked for.
+            while unsafe { libc::read(notify, buf.as_mut_ptr().cast(), buf.len()) } > 0 {}
+        }
+
+        let mut reactor = lock();
+        for (fd, &id) in fds[1..].iter().zip(&ids) {
+            if fd.revents == 0 {
+                continue;
+            }
+            // Errors and hangups are reported by the IO itself, so they count
+            // as ready for both directions.
+            let always = libc::POLLERR | libc::POLLHUP | libc::POLLNVAL;
+            if let Some(w) = reactor.waiting.iter_mut().find(|w| w.id == id) {
+                if fd.revents & (libc::POLLIN | always) != 0 {
+                    ready.extend(w.read.take());
+                }
+                if fd.revents & (libc::POLLOUT | always) != 0 {
+                    ready.extend(w.write.take());
+                }
+            }
+        }
+        reactor
+            .waiting
+            .retain(|w| w.read.is_some() || w.write.is_some());
+        drop(reactor);
+
+        // Wake outside the lock, since waking can poll right away.
+        for waker in ready.drain(..) {
+            waker.wake();
+        }
+    }
+}
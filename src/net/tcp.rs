@@ -0,0 +1,179 @@
+use core::{
+    fmt,
+    future::Future,
+    pin::Pin,
+    task::{Context, Poll},
+};
+
+use crate::{
+    error::{impl_foreign_error, Error},
+    io::{AsyncRead, AsyncWrite, Read, Write},
+    net::{GetSocketAddrs, SocketAddr, ToSocketAddrs},
+};
+
+/// A network stack that can open TCP connections asynchronously.
+///
+/// Each call to [`connect`](AsyncTcpConnect::connect) returns an independent
+/// future, so several connection attempts can be in flight at once.
+///
+/// # Examples
+///
+/// ```
+/// use coreplus::net::{AsyncTcpConnect, SocketAddr};
+///
+/// // Code written like this works with any network stack.
+/// async fn open<N: AsyncTcpConnect>(net: &N, addr: SocketAddr) -> Result<N::Stream, N::Error> {
+///     net.connect(addr).await
+/// }
+/// ```
+pub trait AsyncTcpConnect {
+    /// The type of connected stream.
+    type Stream: AsyncRead + AsyncWrite;
+    /// The associated error type.
+    type Error;
+    /// The future returned by [`connect`](AsyncTcpConnect::connect).
+    type Connect: Future<Output = Result<Self::Stream, Self::Error>>;
+
+    /// Opens a TCP connection to `addr`.
+    fn connect(&self, addr: SocketAddr) -> Self::Connect;
+}
+
+/// A network stack that can bind TCP listeners.
+pub trait TcpBind {
+    /// The type of listener.
+    type Listener;
+    /// The associated error type.
+    type Error;
+
+    /// Creates a listener bound to `addr`.
+    ///
+    /// Binding to port 0 asks the stack to pick a free port, which can be
+    /// read back with [`LocalAddr`](crate::net::LocalAddr) if the listener
+    /// implements it.
+    fn bind(&self, addr: SocketAddr) -> Result<Self::Listener, Self::Error>;
+}
+
+/// A TCP listener that accepts connections asynchronously.
+pub trait AsyncTcpListener {
+    /// The type of accepted stream.
+    type Stream: AsyncRead + AsyncWrite;
+    /// The associated error type.
+    type Error;
+
+    /// Accepts a new incoming connection, returning the stream and the
+    /// address of the remote peer.
+    ///
+    /// This doesn't _have_ to be implemented as async. The implementation
+    /// for [`std::net::TcpListener`] is blocking.
+    #[allow(clippy::type_complexity)]
+    fn poll_accept(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Result<(Self::Stream, SocketAddr), Self::Error>>;
+}
+
+/// The error type for [`TcpConnect::connect`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ConnectError<R, C> {
+    /// Resolving the address failed.
+    Resolve(R),
+    /// The address resolved to nothing.
+    NoAddresses,
+    /// Connecting failed. If there were several addresses, this is the
+    /// error from the last one.
+    Connect(C),
+}
+
+impl<R, C> fmt::Display for ConnectError<R, C> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ConnectError::Resolve(_) => f.write_str("failed to resolve address"),
+            ConnectError::NoAddresses => f.write_str("could not resolve to any addresses"),
+            ConnectError::Connect(_) => f.write_str("failed to connect"),
+        }
+    }
+}
+
+impl<R: Error + 'static, C: Error + 'static> Error for ConnectError<R, C> {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            ConnectError::Resolve(e) => Some(e),
+            ConnectError::NoAddresses => None,
+            ConnectError::Connect(e) => Some(e),
+        }
+    }
+}
+
+impl_foreign_error! {
+    impl<R, C> for ConnectError<R, C>;
+    fn source(&self) {
+        match self {
+            ConnectError::Resolve(e) => Some(e),
+            ConnectError::NoAddresses => None,
+            ConnectError::Connect(e) => Some(e),
+        }
+    }
+}
+
+/// A network stack that can open TCP connections, blocking until they're
+/// established.
+///
+/// # Examples
+///
+/// ```
+/// # #[cfg(feature = "std")] {
+/// use coreplus::io::Write;
+/// use coreplus::net::{StdGetSocketAddrs, StdNet, TcpBind, TcpConnect};
+///
+/// let listener = StdNet.bind("127.0.0.1:0".parse().unwrap())?;
+/// let port = listener.local_addr()?.port();
+///
+/// let mut stream = TcpConnect::connect(&StdNet, ("localhost", port), &StdGetSocketAddrs)?;
+/// stream.write_all(b"hello")?;
+/// # }
+/// # Ok::<(), Box<dyn std::error::Error>>(())
+/// ```
+pub trait TcpConnect {
+    /// The type of connected stream.
+    type Stream: Read + Write;
+    /// The associated error type.
+    type Error;
+
+    /// Opens a TCP connection to `addr`.
+    fn connect_addr(&self, addr: SocketAddr) -> Result<Self::Stream, Self::Error>;
+
+    /// Resolves `addr` with `get` and opens a TCP connection to it.
+    ///
+    /// If `addr` resolves to several addresses, each is tried in turn until
+    /// one of them connects, like [`std::net::TcpStream::connect`].
+    fn connect<A, G>(
+        &self,
+        addr: A,
+        get: &G,
+    ) -> Result<Self::Stream, ConnectError<G::Error, Self::Error>>
+    where
+        A: ToSocketAddrs<G>,
+        G: GetSocketAddrs,
+    {
+        let mut error = ConnectError::NoAddresses;
+        for addr in addr.to_socket_addrs(get).map_err(ConnectError::Resolve)? {
+            match self.connect_addr(addr) {
+                Ok(stream) => return Ok(stream),
+                Err(e) => error = ConnectError::Connect(e),
+            }
+        }
+        Err(error)
+    }
+}
+
+/// A TCP listener that accepts connections, blocking until one arrives.
+pub trait TcpAccept {
+    /// The type of accepted stream.
+    type Stream: Read + Write;
+    /// The associated error type.
+    type Error;
+
+    /// Accepts a new incoming connection, returning the stream and the
+    /// address of the remote peer.
+    fn accept(&mut self) -> Result<(Self::Stream, SocketAddr), Self::Error>;
+}
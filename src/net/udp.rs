@@ -0,0 +1,107 @@
+use core::{
+    pin::Pin,
+    task::{Context, Poll},
+};
+
+use crate::{io::ReadBuf, net::SocketAddr};
+
+/// A network stack that can bind UDP sockets.
+///
+/// # Examples
+///
+/// ```
+/// # #[cfg(feature = "std")] {
+/// use core::pin::Pin;
+/// use coreplus::net::{AsyncRecv, AsyncSend, LocalAddr, StdNet, UdpBind, UdpConnect};
+/// use coreplus::task::{block_on, poll_fn};
+///
+/// let mut a = UdpBind::bind(&StdNet, "127.0.0.1:0".parse().unwrap())?;
+/// let mut b = UdpBind::bind(&StdNet, "127.0.0.1:0".parse().unwrap())?;
+/// UdpConnect::connect(&a, LocalAddr::local_addr(&b)?)?;
+/// UdpConnect::connect(&b, LocalAddr::local_addr(&a)?)?;
+///
+/// block_on(poll_fn(|cx| Pin::new(&mut a).poll_send(cx, b"ping")))?;
+/// let mut buf = [0; 8];
+/// let n = block_on(poll_fn(|cx| Pin::new(&mut b).poll_recv(cx, &mut buf)))?;
+/// assert_eq!(&buf[..n], b"ping");
+/// # }
+/// # Ok::<(), std::io::Error>(())
+/// ```
+pub trait UdpBind {
+    /// The type of socket.
+    type Socket;
+    /// The associated error type.
+    type Error;
+
+    /// Creates a UDP socket bound to `addr`.
+    ///
+    /// Binding to port 0 asks the stack to pick a free port, which can be
+    /// read back with [`LocalAddr`](crate::net::LocalAddr) if the socket
+    /// implements it.
+    fn bind(&self, addr: SocketAddr) -> Result<Self::Socket, Self::Error>;
+}
+
+/// UDP sockets that can be connected to a single remote address.
+pub trait UdpConnect {
+    /// The associated error type.
+    type Error;
+
+    /// Connects this socket to `addr`, so [`AsyncSend`] sends to it and
+    /// [`AsyncRecv`] only receives from it.
+    ///
+    /// Connecting again replaces the previous remote address.
+    fn connect(&self, addr: SocketAddr) -> Result<(), Self::Error>;
+}
+
+/// This is intended for async datagram IO on connected sockets.
+pub trait AsyncSend {
+    /// The associated error type.
+    type Error;
+
+    /// A non-blocking, poll-based variant of [`std::net::UdpSocket::send`].
+    ///
+    /// This doesn't _have_ to be implemented as async. The implementation
+    /// for [`std::net::UdpSocket`] is blocking.
+    fn poll_send(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<Result<usize, Self::Error>>;
+}
+
+/// This is intended for async datagram IO on connected sockets.
+pub trait AsyncRecv {
+    /// The associated error type.
+    type Error;
+
+    /// A non-blocking, poll-based variant of [`std::net::UdpSocket::recv`].
+    ///
+    /// This doesn't _have_ to be implemented as async. The implementation
+    /// for [`std::net::UdpSocket`] is blocking.
+    fn poll_recv(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut [u8],
+    ) -> Poll<Result<usize, Self::Error>>;
+
+    /// Like [`poll_recv`](AsyncRecv::poll_recv), but receives into a
+    /// [`ReadBuf`], which may not be initialized.
+    ///
+    /// The default implementation initializes the unfilled part of `buf`
+    /// (only the first time, since `ReadBuf` keeps track) and calls
+    /// `poll_recv`.
+    fn poll_recv_buf(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut ReadBuf<'_>,
+    ) -> Poll<Result<(), Self::Error>> {
+        match self.poll_recv(cx, buf.initialize_unfilled()) {
+            Poll::Ready(Ok(n)) => {
+                buf.advance(n);
+                Poll::Ready(Ok(()))
+            }
+            Poll::Ready(Err(e)) => Poll::Ready(Err(e)),
+            Poll::Pending => Poll::Pending,
+        }
+    }
+}
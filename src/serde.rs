@@ -0,0 +1,177 @@
+//! A compact binary format for [`serde`](::serde).
+//!
+//! The format is close to [postcard]'s: there are no field names or type
+//! tags, integers wider than a byte are [LEB128] varints (signed ones
+//! zigzag-encoded first), and strings, byte strings, sequences and maps are
+//! prefixed with their length as a varint. Enum variants are written as
+//! their index. Because nothing describes itself, the reader has to know the
+//! type it's reading, and [`deserialize_any`] isn't supported.
+//!
+//! The [`Serializer`] streams into any [`Write`](crate::io::Write), and the
+//! [`Deserializer`] reads from any [`Read`](crate::io::Read). Neither
+//! allocates. Strings and byte strings are read into a scratch buffer
+//! supplied by the caller, and borrowed from it where the type being
+//! deserialized allows, so `&str` and `&[u8]` fields work without an
+//! allocator.
+//!
+//! This module is available when the `serde` feature is enabled.
+//!
+//! # Examples
+//!
+//! ```
+//! use serde::{Deserialize, Serialize};
+//!
+//! #[derive(Debug, PartialEq, Serialize, Deserialize)]
+//! struct Reading<'a> {
+//!     sensor: &'a str,
+//!     celsius: i16,
+//!     samples: [u8; 3],
+//! }
+//!
+//! let reading = Reading { sensor: "porch", celsius: -4, samples: [1, 2, 3] };
+//!
+//! let mut buf = [0; 32];
+//! let rest = coreplus::serde::to_writer(&reading, &mut buf[..])?;
+//! let len = 32 - rest.len();
+//! assert_eq!(&buf[..len], b"\x05porch\x07\x01\x02\x03");
+//!
+//! let mut scratch = [0; 16];
+//! let decoded: Reading = coreplus::serde::from_reader(&buf[..len], &mut scratch)?;
+//! assert_eq!(decoded, reading);
+//! # Ok::<(), Box<dyn std::error::Error>>(())
+//! ```
+//!
+//! [postcard]: https://docs.rs/postcard
+//! [LEB128]: https://en.wikipedia.org/wiki/LEB128
+//! [`deserialize_any`]: ::serde::Deserializer::deserialize_any
+
+use core::fmt;
+
+use crate::error::Error;
+
+mod de;
+mod ser;
+
+pub use self::de::{from_reader, Deserializer};
+pub use self::ser::{to_writer, Serializer};
+
+/// The error type for [`Serializer`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SerializeError<E> {
+    /// Writing to the underlying writer failed.
+    Write(E),
+    /// The writer accepted zero bytes before everything was written.
+    WriteZero,
+    /// A sequence or map didn't say how long it was up front.
+    UnknownLength,
+    /// The value's `Serialize` implementation reported an error.
+    ///
+    /// The message is dropped, since there's nowhere to keep it.
+    Custom,
+}
+
+impl<E> fmt::Display for SerializeError<E> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SerializeError::Write(_) => f.write_str("error writing serialized data"),
+            SerializeError::WriteZero => f.write_str("failed to write whole buffer"),
+            SerializeError::UnknownLength => f.write_str("sequence length must be known"),
+            SerializeError::Custom => f.write_str("error serializing value"),
+        }
+    }
+}
+
+impl<E: Error + 'static> Error for SerializeError<E> {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            SerializeError::Write(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+// serde's error traits need `std::error::Error`, or its no_std stand-in,
+// whatever the underlying error is, so this one has no source.
+impl<E: fmt::Debug + fmt::Display> ::serde::ser::StdError for SerializeError<E> {}
+
+impl<E: fmt::Debug + fmt::Display> ::serde::ser::Error for SerializeError<E> {
+    fn custom<T: fmt::Display>(_msg: T) -> Self {
+        SerializeError::Custom
+    }
+}
+
+/// The error type for [`Deserializer`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DeserializeError<E> {
+    /// Reading from the underlying reader failed.
+    Read(E),
+    /// The reader ended in the middle of a value.
+    UnexpectedEof,
+    /// A `bool` that wasn't `0` or `1`.
+    InvalidBool(u8),
+    /// An `Option` tag that wasn't `0` or `1`.
+    InvalidOption(u8),
+    /// A `char` that wasn't a single valid character.
+    InvalidChar,
+    /// A string that wasn't valid UTF-8.
+    InvalidUtf8,
+    /// A varint that doesn't fit in the type being read.
+    VarintOverflow,
+    /// A string or byte string didn't fit in what's left of the scratch
+    /// buffer.
+    ScratchFull,
+    /// Allocating space for a string or byte string failed.
+    OutOfMemory,
+    /// The type asked for something the format can't provide, such as
+    /// [`deserialize_any`](::serde::Deserializer::deserialize_any).
+    NotSelfDescribing,
+    /// The type's `Deserialize` implementation reported an error.
+    ///
+    /// The message is dropped, since there's nowhere to keep it.
+    Custom,
+}
+
+impl<E> fmt::Display for DeserializeError<E> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DeserializeError::Read(_) => f.write_str("error reading serialized data"),
+            DeserializeError::UnexpectedEof => f.write_str("serialized data ended early"),
+            DeserializeError::InvalidBool(b) => write!(f, "invalid bool {:#04x}", b),
+            DeserializeError::InvalidOption(b) => write!(f, "invalid option tag {:#04x}", b),
+            DeserializeError::InvalidChar => f.write_str("invalid char"),
+            DeserializeError::InvalidUtf8 => f.write_str("invalid UTF-8 in string"),
+            DeserializeError::VarintOverflow => f.write_str("varint out of range"),
+            DeserializeError::ScratchFull => f.write_str("scratch buffer is full"),
+            DeserializeError::OutOfMemory => f.write_str("out of memory"),
+            DeserializeError::NotSelfDescribing => f.write_str("the format is not self-describing"),
+            DeserializeError::Custom => f.write_str("error deserializing value"),
+        }
+    }
+}
+
+impl<E: Error + 'static> Error for DeserializeError<E> {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            DeserializeError::Read(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+// serde's error traits need `std::error::Error`, or its no_std stand-in,
+// whatever the underlying error is, so this one has no source.
+impl<E: fmt::Debug + fmt::Display> ::serde::de::StdError for DeserializeError<E> {}
+
+impl<E: fmt::Debug + fmt::Display> ::serde::de::Error for DeserializeError<E> {
+    fn custom<T: fmt::Display>(_msg: T) -> Self {
+        DeserializeError::Custom
+    }
+}
+
+fn zigzag(value: i128) -> u128 {
+    ((value << 1) ^ (value >> 127)) as u128
+}
+
+fn unzigzag(value: u128) -> i128 {
+    (value >> 1) as i128 ^ -((value & 1) as i128)
+}
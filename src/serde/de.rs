@@ -0,0 +1,551 @@
+use core::{convert::TryFrom, fmt, mem, str};
+
+use ::serde::de::{self, Deserialize, DeserializeSeed, IntoDeserializer, Visitor};
+
+use crate::{
+    io::Read,
+    serde::{unzigzag, DeserializeError},
+};
+
+/// Deserializes a `T` from `reader`, using `scratch` to hold strings and byte
+/// strings.
+///
+/// `T` can borrow from `scratch`. Nothing is read past the end of the value.
+///
+/// # Examples
+///
+/// ```
+/// let mut scratch = [0; 8];
+/// let value: (u16, &str) = coreplus::serde::from_reader(&b"\xac\x02\x02hi"[..], &mut scratch)?;
+/// assert_eq!(value, (300, "hi"));
+/// # Ok::<(), coreplus::serde::DeserializeError<core::convert::Infallible>>(())
+/// ```
+pub fn from_reader<'de, T, R>(
+    reader: R,
+    scratch: &'de mut [u8],
+) -> Result<T, DeserializeError<R::Error>>
+where
+    T: Deserialize<'de>,
+    R: Read,
+    R::Error: fmt::Debug + fmt::Display,
+{
+    T::deserialize(&mut Deserializer::new(reader, scratch))
+}
+
+/// A serde deserializer that reads the [compact binary format](crate::serde)
+/// from a [`Read`].
+///
+/// Strings and byte strings are read into the scratch buffer. When the type
+/// being deserialized can borrow them (`&str`, `&[u8]`, and anything that
+/// uses `deserialize_str` or `deserialize_bytes`), they keep their place in
+/// the buffer for as long as the deserializer's lifetime `'de`, so the buffer
+/// has to be big enough for all of them together. Owned strings only use the
+/// buffer while they're being deserialized. With the `alloc` feature, owned
+/// strings too big for what's left of the buffer are read into a new
+/// allocation instead.
+///
+/// Values are read a few bytes at a time, so wrap slow readers in a buffer.
+///
+/// # Examples
+///
+/// A length prefix that claims more data than there is fails without
+/// allocating it all up front.
+///
+/// ```
+/// # #[cfg(feature = "alloc")]
+/// # {
+/// use coreplus::serde::DeserializeError;
+///
+/// let hostile = b"\xff\xff\xff\xff\xff\xff\xff\x0f";
+/// let mut scratch = [0; 8];
+/// let result = coreplus::serde::from_reader::<String, _>(&hostile[..], &mut scratch);
+/// assert_eq!(result, Err(DeserializeError::UnexpectedEof));
+/// # }
+/// ```
+#[derive(Debug)]
+pub struct Deserializer<'de, R> {
+    reader: R,
+    scratch: &'de mut [u8],
+}
+
+impl<'de, R> Deserializer<'de, R> {
+    /// Creates a deserializer that reads from `reader`, using `scratch` to
+    /// hold strings.
+    pub fn new(reader: R, scratch: &'de mut [u8]) -> Self {
+        Self { reader, scratch }
+    }
+
+    /// Gets a reference to the underlying reader.
+    pub fn get_ref(&self) -> &R {
+        &self.reader
+    }
+
+    /// Gets a mutable reference to the underlying reader.
+    pub fn get_mut(&mut self) -> &mut R {
+        &mut self.reader
+    }
+
+    /// Unwraps this `Deserializer`, returning the underlying reader and the
+    /// part of the scratch buffer that hasn't been borrowed.
+    pub fn into_parts(self) -> (R, &'de mut [u8]) {
+        (self.reader, self.scratch)
+    }
+}
+
+impl<'de, R: Read> Deserializer<'de, R> {
+    fn read_exact(reader: &mut R, mut buf: &mut [u8]) -> Result<(), DeserializeError<R::Error>> {
+        while !buf.is_empty() {
+            match reader.read(buf) {
+                Ok(0) => return Err(DeserializeError::UnexpectedEof),
+                Ok(n) => buf = &mut buf[n..],
+                Err(e) => return Err(DeserializeError::Read(e)),
+            }
+        }
+        Ok(())
+    }
+
+    fn read_byte(&mut self) -> Result<u8, DeserializeError<R::Error>> {
+        let mut byte = [0];
+        Self::read_exact(&mut self.reader, &mut byte)?;
+        Ok(byte[0])
+    }
+
+    fn read_array<const N: usize>(&mut self) -> Result<[u8; N], DeserializeError<R::Error>> {
+        let mut bytes = [0; N];
+        Self::read_exact(&mut self.reader, &mut bytes)?;
+        Ok(bytes)
+    }
+
+    /// Reads a varint of at most `bits` bits.
+    fn read_varint(&mut self, bits: u32) -> Result<u128, DeserializeError<R::Error>> {
+        let mut value = 0;
+        let mut shift = 0;
+        loop {
+            let byte = self.read_byte()?;
+            let low = u128::from(byte & 0x7f);
+            if shift >= bits || (shift > 0 && low >> (bits - shift) != 0) {
+                return Err(DeserializeError::VarintOverflow);
+            }
+            value |= low << shift;
+            if byte & 0x80 == 0 {
+                return Ok(value);
+            }
+            shift += 7;
+        }
+    }
+
+    fn read_unsigned<T: TryFrom<u128>>(&mut self) -> Result<T, DeserializeError<R::Error>> {
+        let bits = mem::size_of::<T>() as u32 * 8;
+        T::try_from(self.read_varint(bits)?).map_err(|_| DeserializeError::VarintOverflow)
+    }
+
+    fn read_signed<T: TryFrom<i128>>(&mut self) -> Result<T, DeserializeError<R::Error>> {
+        let bits = mem::size_of::<T>() as u32 * 8;
+        T::try_from(unzigzag(self.read_varint(bits)?)).map_err(|_| DeserializeError::VarintOverflow)
+    }
+
+    fn read_len(&mut self) -> Result<usize, DeserializeError<R::Error>> {
+        self.read_unsigned()
+    }
+
+    /// Reads `len` bytes into the front of the scratch buffer and takes them
+    /// out of it for good.
+    fn read_borrowed(&mut self, len: usize) -> Result<&'de [u8], DeserializeError<R::Error>> {
+        if len > self.scratch.len() {
+            return Err(DeserializeError::ScratchFull);
+        }
+        let (bytes, rest) = mem::take(&mut self.scratch).split_at_mut(len);
+        self.scratch = rest;
+        Self::read_exact(&mut self.reader, bytes)?;
+        Ok(bytes)
+    }
+
+    /// Reads a string or byte string that the visitor doesn't need to
+    /// borrow, into the scratch buffer if it fits and otherwise into a new
+    /// allocation.
+    fn read_transient(&mut self, len: usize) -> Result<Transient<'_>, DeserializeError<R::Error>> {
+        if let Some(buf) = self.scratch.get_mut(..len) {
+            Self::read_exact(&mut self.reader, buf)?;
+            return Ok(Transient::Scratch(buf));
+        }
+
+        #[cfg(feature = "alloc")]
+        {
+            // The length comes from the input, so don't trust it: grow the
+            // buffer as the data actually arrives, and fail cleanly if the
+            // allocation does.
+            const CHUNK: usize = 4096;
+
+            let mut buf = alloc::vec::Vec::new();
+            while buf.len() < len {
+                let start = buf.len();
+                let chunk = CHUNK.min(len - start);
+                buf.try_reserve(chunk)
+                    .map_err(|_| DeserializeError::OutOfMemory)?;
+                buf.resize(start + chunk, 0);
+                Self::read_exact(&mut self.reader, &mut buf[start..])?;
+            }
+            Ok(Transient::Owned(buf))
+        }
+        #[cfg(not(feature = "alloc"))]
+        Err(DeserializeError::ScratchFull)
+    }
+}
+
+/// A string or byte string that's only needed while it's being visited.
+enum Transient<'a> {
+    Scratch(&'a [u8]),
+    #[cfg(feature = "alloc")]
+    Owned(alloc::vec::Vec<u8>),
+}
+
+impl Transient<'_> {
+    fn visit_str<'de, V: Visitor<'de>, E>(self, visitor: V) -> Result<V::Value, DeserializeError<E>>
+    where
+        DeserializeError<E>: de::Error,
+    {
+        match self {
+            Transient::Scratch(bytes) => visitor.visit_str(utf8(bytes)?),
+            #[cfg(feature = "alloc")]
+            Transient::Owned(bytes) => visitor.visit_string(
+                alloc::string::String::from_utf8(bytes)
+                    .map_err(|_| DeserializeError::InvalidUtf8)?,
+            ),
+        }
+    }
+
+    fn visit_bytes<'de, V: Visitor<'de>, E>(
+        self,
+        visitor: V,
+    ) -> Result<V::Value, DeserializeError<E>>
+    where
+        DeserializeError<E>: de::Error,
+    {
+        match self {
+            Transient::Scratch(bytes) => visitor.visit_bytes(bytes),
+            #[cfg(feature = "alloc")]
+            Transient::Owned(bytes) => visitor.visit_byte_buf(bytes),
+        }
+    }
+}
+
+fn utf8<E>(bytes: &[u8]) -> Result<&str, DeserializeError<E>> {
+    str::from_utf8(bytes).map_err(|_| DeserializeError::InvalidUtf8)
+}
+
+impl<'de, R> de::Deserializer<'de> for &mut Deserializer<'de, R>
+where
+    R: Read,
+    R::Error: fmt::Debug + fmt::Display,
+{
+    type Error = DeserializeError<R::Error>;
+
+    fn deserialize_any<V: Visitor<'de>>(self, _visitor: V) -> Result<V::Value, Self::Error> {
+        Err(DeserializeError::NotSelfDescribing)
+    }
+
+    fn deserialize_bool<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        match self.read_byte()? {
+            0 => visitor.visit_bool(false),
+            1 => visitor.visit_bool(true),
+            b => Err(DeserializeError::InvalidBool(b)),
+        }
+    }
+
+    fn deserialize_i8<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        visitor.visit_i8(self.read_byte()? as i8)
+    }
+
+    fn deserialize_i16<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        visitor.visit_i16(self.read_signed()?)
+    }
+
+    fn deserialize_i32<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        visitor.visit_i32(self.read_signed()?)
+    }
+
+    fn deserialize_i64<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        visitor.visit_i64(self.read_signed()?)
+    }
+
+    fn deserialize_i128<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        visitor.visit_i128(self.read_signed()?)
+    }
+
+    fn deserialize_u8<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        visitor.visit_u8(self.read_byte()?)
+    }
+
+    fn deserialize_u16<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        visitor.visit_u16(self.read_unsigned()?)
+    }
+
+    fn deserialize_u32<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        visitor.visit_u32(self.read_unsigned()?)
+    }
+
+    fn deserialize_u64<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        visitor.visit_u64(self.read_unsigned()?)
+    }
+
+    fn deserialize_u128<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        visitor.visit_u128(self.read_unsigned()?)
+    }
+
+    fn deserialize_f32<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        visitor.visit_f32(f32::from_le_bytes(self.read_array()?))
+    }
+
+    fn deserialize_f64<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        visitor.visit_f64(f64::from_le_bytes(self.read_array()?))
+    }
+
+    fn deserialize_char<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        let len = self.read_len()?;
+        let mut buf = [0; 4];
+        let buf = buf.get_mut(..len).ok_or(DeserializeError::InvalidChar)?;
+        Deserializer::read_exact(&mut self.reader, buf)?;
+
+        let mut chars = utf8(buf)?.chars();
+        match (chars.next(), chars.next()) {
+            (Some(c), None) => visitor.visit_char(c),
+            _ => Err(DeserializeError::InvalidChar),
+        }
+    }
+
+    fn deserialize_str<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        let len = self.read_len()?;
+        if len <= self.scratch.len() {
+            return visitor.visit_borrowed_str(utf8(self.read_borrowed(len)?)?);
+        }
+        self.read_transient(len)?.visit_str(visitor)
+    }
+
+    fn deserialize_string<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        let len = self.read_len()?;
+        self.read_transient(len)?.visit_str(visitor)
+    }
+
+    fn deserialize_bytes<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        let len = self.read_len()?;
+        if len <= self.scratch.len() {
+            return visitor.visit_borrowed_bytes(self.read_borrowed(len)?);
+        }
+        self.read_transient(len)?.visit_bytes(visitor)
+    }
+
+    fn deserialize_byte_buf<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        let len = self.read_len()?;
+        self.read_transient(len)?.visit_bytes(visitor)
+    }
+
+    fn deserialize_option<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        match self.read_byte()? {
+            0 => visitor.visit_none(),
+            1 => visitor.visit_some(self),
+            b => Err(DeserializeError::InvalidOption(b)),
+        }
+    }
+
+    fn deserialize_unit<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        visitor.visit_unit()
+    }
+
+    fn deserialize_unit_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, Self::Error> {
+        visitor.visit_unit()
+    }
+
+    fn deserialize_newtype_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, Self::Error> {
+        visitor.visit_newtype_struct(self)
+    }
+
+    fn deserialize_seq<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        let remaining = self.read_len()?;
+        visitor.visit_seq(Access {
+            de: self,
+            remaining,
+        })
+    }
+
+    fn deserialize_tuple<V: Visitor<'de>>(
+        self,
+        len: usize,
+        visitor: V,
+    ) -> Result<V::Value, Self::Error> {
+        visitor.visit_seq(Access {
+            de: self,
+            remaining: len,
+        })
+    }
+
+    fn deserialize_tuple_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        len: usize,
+        visitor: V,
+    ) -> Result<V::Value, Self::Error> {
+        self.deserialize_tuple(len, visitor)
+    }
+
+    fn deserialize_map<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        let remaining = self.read_len()?;
+        visitor.visit_map(Access {
+            de: self,
+            remaining,
+        })
+    }
+
+    fn deserialize_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        fields: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Self::Error> {
+        self.deserialize_tuple(fields.len(), visitor)
+    }
+
+    fn deserialize_enum<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        _variants: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Self::Error> {
+        visitor.visit_enum(self)
+    }
+
+    fn deserialize_identifier<V: Visitor<'de>>(self, _visitor: V) -> Result<V::Value, Self::Error> {
+        Err(DeserializeError::NotSelfDescribing)
+    }
+
+    fn deserialize_ignored_any<V: Visitor<'de>>(
+        self,
+        _visitor: V,
+    ) -> Result<V::Value, Self::Error> {
+        Err(DeserializeError::NotSelfDescribing)
+    }
+
+    fn is_human_readable(&self) -> bool {
+        false
+    }
+}
+
+/// Reads the elements of a sequence, tuple, struct or map whose length is
+/// already known.
+struct Access<'a, 'de, R> {
+    de: &'a mut Deserializer<'de, R>,
+    remaining: usize,
+}
+
+impl<'de, R> de::SeqAccess<'de> for Access<'_, 'de, R>
+where
+    R: Read,
+    R::Error: fmt::Debug + fmt::Display,
+{
+    type Error = DeserializeError<R::Error>;
+
+    fn next_element_seed<T: DeserializeSeed<'de>>(
+        &mut self,
+        seed: T,
+    ) -> Result<Option<T::Value>, Self::Error> {
+        if self.remaining == 0 {
+            return Ok(None);
+        }
+        self.remaining -= 1;
+        seed.deserialize(&mut *self.de).map(Some)
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        Some(self.remaining)
+    }
+}
+
+impl<'de, R> de::MapAccess<'de> for Access<'_, 'de, R>
+where
+    R: Read,
+    R::Error: fmt::Debug + fmt::Display,
+{
+    type Error = DeserializeError<R::Error>;
+
+    fn next_key_seed<K: DeserializeSeed<'de>>(
+        &mut self,
+        seed: K,
+    ) -> Result<Option<K::Value>, Self::Error> {
+        if self.remaining == 0 {
+            return Ok(None);
+        }
+        self.remaining -= 1;
+        seed.deserialize(&mut *self.de).map(Some)
+    }
+
+    fn next_value_seed<V: DeserializeSeed<'de>>(
+        &mut self,
+        seed: V,
+    ) -> Result<V::Value, Self::Error> {
+        seed.deserialize(&mut *self.de)
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        Some(self.remaining)
+    }
+}
+
+impl<'de, R> de::EnumAccess<'de> for &mut Deserializer<'de, R>
+where
+    R: Read,
+    R::Error: fmt::Debug + fmt::Display,
+{
+    type Error = DeserializeError<R::Error>;
+    type Variant = Self;
+
+    fn variant_seed<V: DeserializeSeed<'de>>(
+        self,
+        seed: V,
+    ) -> Result<(V::Value, Self), Self::Error> {
+        let index: u32 = self.read_unsigned()?;
+        let value = seed.deserialize(index.into_deserializer())?;
+        Ok((value, self))
+    }
+}
+
+impl<'de, R> de::VariantAccess<'de> for &mut Deserializer<'de, R>
+where
+    R: Read,
+    R::Error: fmt::Debug + fmt::Display,
+{
+    type Error = DeserializeError<R::Error>;
+
+    fn unit_variant(self) -> Result<(), Self::Error> {
+        Ok(())
+    }
+
+    fn newtype_variant_seed<T: DeserializeSeed<'de>>(
+        self,
+        seed: T,
+    ) -> Result<T::Value, Self::Error> {
+        seed.deserialize(self)
+    }
+
+    fn tuple_variant<V: Visitor<'de>>(
+        self,
+        len: usize,
+        visitor: V,
+    ) -> Result<V::Value, Self::Error> {
+        de::Deserializer::deserialize_tuple(self, len, visitor)
+    }
+
+    fn struct_variant<V: Visitor<'de>>(
+        self,
+        fields: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Self::Error> {
+        de::Deserializer::deserialize_tuple(self, fields.len(), visitor)
+    }
+}
@@ -0,0 +1,355 @@
+use core::fmt;
+
+use ::serde::ser::{self, Serialize};
+
+use crate::{
+    io::Write,
+    serde::{zigzag, SerializeError},
+};
+
+/// Serializes `value` into `writer`, returning the writer once it's done.
+///
+/// # Examples
+///
+/// ```
+/// let mut buf = [0; 8];
+/// let rest = coreplus::serde::to_writer(&(300u16, "hi"), &mut buf[..])?;
+/// let len = 8 - rest.len();
+/// assert_eq!(&buf[..len], b"\xac\x02\x02hi");
+/// # Ok::<(), coreplus::serde::SerializeError<core::convert::Infallible>>(())
+/// ```
+pub fn to_writer<T, W>(value: &T, writer: W) -> Result<W, SerializeError<W::Error>>
+where
+    T: Serialize + ?Sized,
+    W: Write,
+    W::Error: fmt::Debug + fmt::Display,
+{
+    let mut serializer = Serializer::new(writer);
+    value.serialize(&mut serializer)?;
+    Ok(serializer.into_inner())
+}
+
+/// A serde serializer that writes the [compact binary format](crate::serde)
+/// to a [`Write`].
+///
+/// Nothing is buffered, so small values turn into small writes. Wrap the
+/// writer in a buffer if that's a problem.
+#[derive(Debug)]
+pub struct Serializer<W> {
+    writer: W,
+}
+
+impl<W> Serializer<W> {
+    /// Creates a serializer that writes to `writer`.
+    pub fn new(writer: W) -> Self {
+        Self { writer }
+    }
+
+    /// Gets a reference to the underlying writer.
+    pub fn get_ref(&self) -> &W {
+        &self.writer
+    }
+
+    /// Gets a mutable reference to the underlying writer.
+    pub fn get_mut(&mut self) -> &mut W {
+        &mut self.writer
+    }
+
+    /// Unwraps this `Serializer`, returning the underlying writer.
+    pub fn into_inner(self) -> W {
+        self.writer
+    }
+}
+
+impl<W: Write> Serializer<W> {
+    fn write(&mut self, mut buf: &[u8]) -> Result<(), SerializeError<W::Error>> {
+        while !buf.is_empty() {
+            match self.writer.write(buf) {
+                Ok(0) => return Err(SerializeError::WriteZero),
+                Ok(n) => buf = &buf[n..],
+                Err(e) => return Err(SerializeError::Write(e)),
+            }
+        }
+        Ok(())
+    }
+
+    fn write_varint(&mut self, mut value: u128) -> Result<(), SerializeError<W::Error>> {
+        let mut buf = [0; 19];
+        let mut len = 0;
+        loop {
+            let byte = value as u8 & 0x7f;
+            value >>= 7;
+            if value == 0 {
+                buf[len] = byte;
+                len += 1;
+                break;
+            }
+            buf[len] = byte | 0x80;
+            len += 1;
+        }
+        self.write(&buf[..len])
+    }
+
+    fn write_len(&mut self, len: Option<usize>) -> Result<(), SerializeError<W::Error>> {
+        match len {
+            Some(len) => self.write_varint(len as u128),
+            None => Err(SerializeError::UnknownLength),
+        }
+    }
+}
+
+/// Counts the bytes a `Display` implementation writes.
+struct Counter(usize);
+
+impl fmt::Write for Counter {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        self.0 += s.len();
+        Ok(())
+    }
+}
+
+/// Writes what a `Display` implementation writes, keeping the first error.
+struct Adaptor<'a, W: Write> {
+    serializer: &'a mut Serializer<W>,
+    error: Option<SerializeError<W::Error>>,
+}
+
+impl<W: Write> fmt::Write for Adaptor<'_, W> {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        self.serializer.write(s.as_bytes()).map_err(|e| {
+            self.error = Some(e);
+            fmt::Error
+        })
+    }
+}
+
+impl<W> ser::Serializer for &mut Serializer<W>
+where
+    W: Write,
+    W::Error: fmt::Debug + fmt::Display,
+{
+    type Ok = ();
+    type Error = SerializeError<W::Error>;
+
+    type SerializeSeq = Self;
+    type SerializeTuple = Self;
+    type SerializeTupleStruct = Self;
+    type SerializeTupleVariant = Self;
+    type SerializeMap = Self;
+    type SerializeStruct = Self;
+    type SerializeStructVariant = Self;
+
+    fn serialize_bool(self, v: bool) -> Result<(), Self::Error> {
+        self.write(&[v as u8])
+    }
+
+    fn serialize_i8(self, v: i8) -> Result<(), Self::Error> {
+        self.write(&[v as u8])
+    }
+
+    fn serialize_i16(self, v: i16) -> Result<(), Self::Error> {
+        self.write_varint(zigzag(v.into()))
+    }
+
+    fn serialize_i32(self, v: i32) -> Result<(), Self::Error> {
+        self.write_varint(zigzag(v.into()))
+    }
+
+    fn serialize_i64(self, v: i64) -> Result<(), Self::Error> {
+        self.write_varint(zigzag(v.into()))
+    }
+
+    fn serialize_i128(self, v: i128) -> Result<(), Self::Error> {
+        self.write_varint(zigzag(v))
+    }
+
+    fn serialize_u8(self, v: u8) -> Result<(), Self::Error> {
+        self.write(&[v])
+    }
+
+    fn serialize_u16(self, v: u16) -> Result<(), Self::Error> {
+        self.write_varint(v.into())
+    }
+
+    fn serialize_u32(self, v: u32) -> Result<(), Self::Error> {
+        self.write_varint(v.into())
+    }
+
+    fn serialize_u64(self, v: u64) -> Result<(), Self::Error> {
+        self.write_varint(v.into())
+    }
+
+    fn serialize_u128(self, v: u128) -> Result<(), Self::Error> {
+        self.write_varint(v)
+    }
+
+    fn serialize_f32(self, v: f32) -> Result<(), Self::Error> {
+        self.write(&v.to_le_bytes())
+    }
+
+    fn serialize_f64(self, v: f64) -> Result<(), Self::Error> {
+        self.write(&v.to_le_bytes())
+    }
+
+    fn serialize_char(self, v: char) -> Result<(), Self::Error> {
+        self.serialize_str(v.encode_utf8(&mut [0; 4]))
+    }
+
+    fn serialize_str(self, v: &str) -> Result<(), Self::Error> {
+        self.serialize_bytes(v.as_bytes())
+    }
+
+    fn serialize_bytes(self, v: &[u8]) -> Result<(), Self::Error> {
+        self.write_len(Some(v.len()))?;
+        self.write(v)
+    }
+
+    fn serialize_none(self) -> Result<(), Self::Error> {
+        self.write(&[0])
+    }
+
+    fn serialize_some<T: Serialize + ?Sized>(self, value: &T) -> Result<(), Self::Error> {
+        self.write(&[1])?;
+        value.serialize(self)
+    }
+
+    fn serialize_unit(self) -> Result<(), Self::Error> {
+        Ok(())
+    }
+
+    fn serialize_unit_struct(self, _name: &'static str) -> Result<(), Self::Error> {
+        Ok(())
+    }
+
+    fn serialize_unit_variant(
+        self,
+        _name: &'static str,
+        variant_index: u32,
+        _variant: &'static str,
+    ) -> Result<(), Self::Error> {
+        self.write_varint(variant_index.into())
+    }
+
+    fn serialize_newtype_struct<T: Serialize + ?Sized>(
+        self,
+        _name: &'static str,
+        value: &T,
+    ) -> Result<(), Self::Error> {
+        value.serialize(self)
+    }
+
+    fn serialize_newtype_variant<T: Serialize + ?Sized>(
+        self,
+        _name: &'static str,
+        variant_index: u32,
+        _variant: &'static str,
+        value: &T,
+    ) -> Result<(), Self::Error> {
+        self.write_varint(variant_index.into())?;
+        value.serialize(self)
+    }
+
+    fn serialize_seq(self, len: Option<usize>) -> Result<Self, Self::Error> {
+        self.write_len(len)?;
+        Ok(self)
+    }
+
+    fn serialize_tuple(self, _len: usize) -> Result<Self, Self::Error> {
+        Ok(self)
+    }
+
+    fn serialize_tuple_struct(self, _name: &'static str, _len: usize) -> Result<Self, Self::Error> {
+        Ok(self)
+    }
+
+    fn serialize_tuple_variant(
+        self,
+        _name: &'static str,
+        variant_index: u32,
+        _variant: &'static str,
+        _len: usize,
+    ) -> Result<Self, Self::Error> {
+        self.write_varint(variant_index.into())?;
+        Ok(self)
+    }
+
+    fn serialize_map(self, len: Option<usize>) -> Result<Self, Self::Error> {
+        self.write_len(len)?;
+        Ok(self)
+    }
+
+    fn serialize_struct(self, _name: &'static str, _len: usize) -> Result<Self, Self::Error> {
+        Ok(self)
+    }
+
+    fn serialize_struct_variant(
+        self,
+        _name: &'static str,
+        variant_index: u32,
+        _variant: &'static str,
+        _len: usize,
+    ) -> Result<Self, Self::Error> {
+        self.write_varint(variant_index.into())?;
+        Ok(self)
+    }
+
+    fn collect_str<T: fmt::Display + ?Sized>(self, value: &T) -> Result<(), Self::Error> {
+        use core::fmt::Write as _;
+
+        // The length comes first, so format the value twice rather than
+        // buffering it.
+        let mut counter = Counter(0);
+        write!(counter, "{}", value).map_err(|_| SerializeError::Custom)?;
+        self.write_len(Some(counter.0))?;
+
+        let mut adaptor = Adaptor {
+            serializer: self,
+            error: None,
+        };
+        match write!(adaptor, "{}", value) {
+            Ok(()) => Ok(()),
+            Err(_) => Err(adaptor.error.unwrap_or(SerializeError::Custom)),
+        }
+    }
+
+    fn is_human_readable(&self) -> bool {
+        false
+    }
+}
+
+macro_rules! impl_compound {
+    ($($trait:ident { $($method:ident($($key:ident: $key_ty:ty),*);)* })*) => {$(
+        impl<W> ser::$trait for &mut Serializer<W>
+        where
+            W: Write,
+            W::Error: fmt::Debug + fmt::Display,
+        {
+            type Ok = ();
+            type Error = SerializeError<W::Error>;
+
+            $(
+                fn $method<T: Serialize + ?Sized>(
+                    &mut self,
+                    $($key: $key_ty,)*
+                    value: &T,
+                ) -> Result<(), Self::Error> {
+                    value.serialize(&mut **self)
+                }
+            )*
+
+            fn end(self) -> Result<(), Self::Error> {
+                Ok(())
+            }
+        }
+    )*};
+}
+
+impl_compound! {
+    SerializeSeq { serialize_element(); }
+    SerializeTuple { serialize_element(); }
+    SerializeTupleStruct { serialize_field(); }
+    SerializeTupleVariant { serialize_field(); }
+    SerializeMap { serialize_key(); serialize_value(); }
+    SerializeStruct { serialize_field(_key: &'static str); }
+    SerializeStructVariant { serialize_field(_key: &'static str); }
+}
@@ -0,0 +1,10 @@
+//! Running futures without a runtime
+//!
+//! These helpers are enough to drive the async traits in this crate from
+//! synchronous code or in tests. None of them need an allocator.
+
+mod block_on;
+mod future;
+
+pub use self::block_on::block_on;
+pub use self::future::*;
@@ -0,0 +1,87 @@
+use core::{future::Future, pin::Pin};
+
+/// Runs a future to completion on the current thread.
+///
+/// When the `std` feature is enabled, the thread is parked while the future
+/// is pending and unparked when it is woken. Otherwise, the future is polled
+/// in a spin loop, so it should only be used with futures that become ready
+/// on their own, such as ones driven by interrupts.
+///
+/// # Examples
+///
+/// ```
+/// use coreplus::task::{block_on, yield_now};
+///
+/// let value = block_on(async {
+///     yield_now().await;
+///     42
+/// });
+/// assert_eq!(value, 42);
+/// ```
+pub fn block_on<F: Future>(mut future: F) -> F::Output {
+    // SAFETY: `future` is shadowed, so it can't be moved again.
+    let mut future = unsafe { Pin::new_unchecked(&mut future) };
+    imp::run(|cx| future.as_mut().poll(cx))
+}
+
+#[cfg(feature = "std")]
+mod imp {
+    use std::{
+        sync::Arc,
+        task::{Context, Poll, Wake, Waker},
+        thread::{self, Thread},
+    };
+
+    struct ThreadWaker(Thread);
+
+    impl Wake for ThreadWaker {
+        fn wake(self: Arc<Self>) {
+            self.0.unpark();
+        }
+
+        fn wake_by_ref(self: &Arc<Self>) {
+            self.0.unpark();
+        }
+    }
+
+    pub(super) fn run<T>(mut poll: impl FnMut(&mut Context<'_>) -> Poll<T>) -> T {
+        let waker = Waker::from(Arc::new(ThreadWaker(thread::current())));
+        let mut cx = Context::from_waker(&waker);
+        loop {
+            match poll(&mut cx) {
+                Poll::Ready(value) => return value,
+                // A wake-up that happened since the last poll leaves the
+                // token set, so this returns immediately.
+                Poll::Pending => thread::park(),
+            }
+        }
+    }
+}
+
+#[cfg(not(feature = "std"))]
+mod imp {
+    use core::{
+        hint, ptr,
+        task::{Context, Poll, RawWaker, RawWakerVTable, Waker},
+    };
+
+    const VTABLE: RawWakerVTable = RawWakerVTable::new(clone, noop, noop, noop);
+
+    fn clone(_: *const ()) -> RawWaker {
+        RawWaker::new(ptr::null(), &VTABLE)
+    }
+
+    fn noop(_: *const ()) {}
+
+    pub(super) fn run<T>(mut poll: impl FnMut(&mut Context<'_>) -> Poll<T>) -> T {
+        // SAFETY: the vtable functions do nothing, so any data pointer is fine.
+        let waker = unsafe { Waker::from_raw(clone(ptr::null())) };
+        let mut cx = Context::from_waker(&waker);
+        loop {
+            match poll(&mut cx) {
+                Poll::Ready(value) => return value,
+                Poll::Pending => hint::spin_loop(),
+            }
+        }
+    }
+}
@@ -0,0 +1,261 @@
+use core::{
+    fmt,
+    future::Future,
+    mem,
+    pin::Pin,
+    task::{Context, Poll},
+};
+
+/// A future that wraps a function returning [`Poll`].
+///
+/// This struct is created by [`poll_fn`].
+pub struct PollFn<F> {
+    f: F,
+}
+
+impl<F> fmt::Debug for PollFn<F> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("PollFn").finish()
+    }
+}
+
+/// Creates a future that calls `f` each time it is polled.
+///
+/// This is the easiest way to call the `poll_*` methods of the async traits
+/// from an `async` block.
+///
+/// # Examples
+///
+/// ```
+/// use core::pin::Pin;
+/// use coreplus::io::{repeat, AsyncRead};
+/// use coreplus::task::{block_on, poll_fn};
+///
+/// let mut reader = repeat(7);
+/// let mut buf = [0; 3];
+/// let n = block_on(poll_fn(|cx| Pin::new(&mut reader).poll_read(cx, &mut buf)));
+/// assert_eq!(n, Ok(3));
+/// assert_eq!(buf, [7; 3]);
+/// ```
+pub fn poll_fn<T, F>(f: F) -> PollFn<F>
+where
+    F: FnMut(&mut Context<'_>) -> Poll<T>,
+{
+    PollFn { f }
+}
+
+impl<F> Unpin for PollFn<F> {}
+
+impl<T, F> Future for PollFn<F>
+where
+    F: FnMut(&mut Context<'_>) -> Poll<T>,
+{
+    type Output = T;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<T> {
+        // `f` is never pinned, so `PollFn` is `Unpin` regardless of `F`.
+        (self.get_mut().f)(cx)
+    }
+}
+
+/// A future that is pending the first time it is polled.
+///
+/// This struct is created by [`yield_now`].
+#[derive(Debug)]
+#[must_use = "futures do nothing unless you `.await` or poll them"]
+pub struct YieldNow {
+    yielded: bool,
+}
+
+/// Yields once to whatever is driving the current task.
+///
+/// The task is woken straight away, so it is polled again as soon as the
+/// executor gets round to it.
+///
+/// # Examples
+///
+/// ```
+/// use coreplus::task::{block_on, join, yield_now};
+///
+/// let (a, b) = block_on(join(
+///     async {
+///         yield_now().await;
+///         "a"
+///     },
+///     async { "b" },
+/// ));
+/// assert_eq!((a, b), ("a", "b"));
+/// ```
+pub fn yield_now() -> YieldNow {
+    YieldNow { yielded: false }
+}
+
+impl Future for YieldNow {
+    type Output = ();
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<()> {
+        if self.yielded {
+            Poll::Ready(())
+        } else {
+            self.yielded = true;
+            cx.waker().wake_by_ref();
+            Poll::Pending
+        }
+    }
+}
+
+/// The output of [`select`]: whichever future finished first.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Either<A, B> {
+    /// The first future finished first.
+    Left(A),
+    /// The second future finished first.
+    Right(B),
+}
+
+/// A future that waits for the first of two futures to finish.
+///
+/// This struct is created by [`select`].
+#[derive(Debug)]
+#[must_use = "futures do nothing unless you `.await` or poll them"]
+pub struct Select<A, B> {
+    a: A,
+    b: B,
+}
+
+/// Waits for either of two futures to finish, dropping the other one.
+///
+/// `a` is always polled first, so it wins if both are ready at once.
+///
+/// # Examples
+///
+/// ```
+/// use coreplus::task::{block_on, select, yield_now, Either};
+///
+/// let winner = block_on(select(
+///     async {
+///         yield_now().await;
+///         1
+///     },
+///     async { "ready" },
+/// ));
+/// assert_eq!(winner, Either::Right("ready"));
+/// ```
+pub fn select<A: Future, B: Future>(a: A, b: B) -> Select<A, B> {
+    Select { a, b }
+}
+
+impl<A: Future, B: Future> Future for Select<A, B> {
+    type Output = Either<A::Output, B::Output>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        // SAFETY: both futures are structurally pinned and never moved out
+        // of a pinned `Select`.
+        let (a, b) = unsafe {
+            let this = self.get_unchecked_mut();
+            (
+                Pin::new_unchecked(&mut this.a),
+                Pin::new_unchecked(&mut this.b),
+            )
+        };
+
+        if let Poll::Ready(output) = a.poll(cx) {
+            return Poll::Ready(Either::Left(output));
+        }
+        if let Poll::Ready(output) = b.poll(cx) {
+            return Poll::Ready(Either::Right(output));
+        }
+        Poll::Pending
+    }
+}
+
+enum MaybeDone<F: Future> {
+    Future(F),
+    Done(F::Output),
+    Taken,
+}
+
+impl<F: Future> MaybeDone<F> {
+    /// Polls the future if it's still running. Returns `true` once it's done.
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> bool {
+        // SAFETY: the future is structurally pinned and only ever dropped in
+        // place by `Pin::set`. The output is not pinned.
+        let future = match unsafe { self.as_mut().get_unchecked_mut() } {
+            MaybeDone::Future(future) => unsafe { Pin::new_unchecked(future) },
+            MaybeDone::Done(_) => return true,
+            MaybeDone::Taken => panic!("`Join` polled after completion"),
+        };
+        match future.poll(cx) {
+            Poll::Ready(output) => {
+                self.set(MaybeDone::Done(output));
+                true
+            }
+            Poll::Pending => false,
+        }
+    }
+
+    fn take(self: Pin<&mut Self>) -> F::Output {
+        // SAFETY: this is only called once the future is done, and the output
+        // is not pinned, so moving it out is fine.
+        match mem::replace(unsafe { self.get_unchecked_mut() }, MaybeDone::Taken) {
+            MaybeDone::Done(output) => output,
+            _ => unreachable!(),
+        }
+    }
+}
+
+/// A future that waits for two futures to both finish.
+///
+/// This struct is created by [`join`].
+#[must_use = "futures do nothing unless you `.await` or poll them"]
+pub struct Join<A: Future, B: Future> {
+    a: MaybeDone<A>,
+    b: MaybeDone<B>,
+}
+
+impl<A: Future, B: Future> fmt::Debug for Join<A, B> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Join").finish()
+    }
+}
+
+/// Runs two futures concurrently and waits for both of them.
+///
+/// # Examples
+///
+/// ```
+/// use coreplus::task::{block_on, join};
+///
+/// let (a, b) = block_on(join(async { 1 }, async { 2 }));
+/// assert_eq!(a + b, 3);
+/// ```
+pub fn join<A: Future, B: Future>(a: A, b: B) -> Join<A, B> {
+    Join {
+        a: MaybeDone::Future(a),
+        b: MaybeDone::Future(b),
+    }
+}
+
+impl<A: Future, B: Future> Future for Join<A, B> {
+    type Output = (A::Output, B::Output);
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        // SAFETY: both halves are structurally pinned and never moved out of
+        // a pinned `Join`.
+        let (mut a, mut b) = unsafe {
+            let this = self.get_unchecked_mut();
+            (
+                Pin::new_unchecked(&mut this.a),
+                Pin::new_unchecked(&mut this.b),
+            )
+        };
+
+        let a_done = a.as_mut().poll(cx);
+        let b_done = b.as_mut().poll(cx);
+        if a_done && b_done {
+            Poll::Ready((a.take(), b.take()))
+        } else {
+            Poll::Pending
+        }
+    }
+}
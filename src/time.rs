@@ -0,0 +1,64 @@
+//! Time
+
+use core::{future::Future, time::Duration};
+
+#[cfg(feature = "alloc")]
+mod manual;
+#[cfg(feature = "std")]
+mod std_impl;
+mod timeout;
+
+#[cfg(feature = "alloc")]
+pub use self::manual::*;
+#[cfg(feature = "std")]
+pub use self::std_impl::*;
+pub use self::timeout::*;
+
+/// A measurement of a monotonically nondecreasing clock.
+pub trait Instant: Copy + Ord {
+    /// Returns the amount of time elapsed from `earlier` to `self`, or zero
+    /// if `earlier` is later than `self`.
+    fn saturating_duration_since(&self, earlier: Self) -> Duration;
+
+    /// Returns `self + duration`, or `None` if that can't be represented.
+    fn checked_add(&self, duration: Duration) -> Option<Self>;
+}
+
+/// A source of [`Instant`]s.
+///
+/// Implement this for your platform's monotonic clock. [`StdClock`] uses the
+/// standard library's, and [`ManualClock`] only moves when told to, for tests.
+pub trait Clock {
+    /// The type of instant this clock produces.
+    type Instant: Instant;
+
+    /// Returns the current time.
+    fn now(&self) -> Self::Instant;
+}
+
+/// A [`Clock`] that can sleep asynchronously.
+///
+/// Implement this for your executor or hardware timer.
+pub trait Timer: Clock {
+    /// The future returned by [`sleep_until`](Timer::sleep_until).
+    type Sleep: Future<Output = ()>;
+
+    /// Returns a future that completes once the clock reaches `deadline`.
+    fn sleep_until(&self, deadline: Self::Instant) -> Self::Sleep;
+}
+
+impl<C: Clock + ?Sized> Clock for &C {
+    type Instant = C::Instant;
+
+    fn now(&self) -> Self::Instant {
+        (**self).now()
+    }
+}
+
+impl<T: Timer + ?Sized> Timer for &T {
+    type Sleep = T::Sleep;
+
+    fn sleep_until(&self, deadline: Self::Instant) -> Self::Sleep {
+        (**self).sleep_until(deadline)
+    }
+}
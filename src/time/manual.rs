@@ -0,0 +1,136 @@
+use alloc::{rc::Rc, vec::Vec};
+use core::{
+    cell::{Cell, RefCell},
+    future::Future,
+    pin::Pin,
+    task::{Context, Poll, Waker},
+    time::Duration,
+};
+
+use crate::time::{Clock, Instant, Timer};
+
+/// An instant of a [`ManualClock`], measured from when the clock was created.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct ManualInstant(Duration);
+
+impl ManualInstant {
+    /// Returns the time between the clock's creation and this instant.
+    pub fn since_start(&self) -> Duration {
+        self.0
+    }
+}
+
+impl Instant for ManualInstant {
+    fn saturating_duration_since(&self, earlier: Self) -> Duration {
+        self.0.saturating_sub(earlier.0)
+    }
+
+    fn checked_add(&self, duration: Duration) -> Option<Self> {
+        self.0.checked_add(duration).map(ManualInstant)
+    }
+}
+
+#[derive(Debug, Default)]
+struct Shared {
+    now: Cell<Duration>,
+    wakers: RefCell<Vec<Waker>>,
+}
+
+/// A [`Clock`] and [`Timer`] that only moves forward when
+/// [`advance`](ManualClock::advance) is called.
+///
+/// This makes code that depends on time deterministic to test. Clones share
+/// the same time.
+///
+/// This type is available when the `alloc` feature is enabled.
+///
+/// # Examples
+///
+/// ```
+/// use core::time::Duration;
+/// use coreplus::task::{block_on, join};
+/// use coreplus::time::{Clock, Instant, ManualClock, Timer};
+///
+/// let clock = ManualClock::new();
+/// let deadline = clock.now().checked_add(Duration::from_secs(5)).unwrap();
+///
+/// block_on(join(clock.sleep_until(deadline), async {
+///     clock.advance(Duration::from_secs(5));
+/// }));
+/// assert_eq!(clock.now().since_start(), Duration::from_secs(5));
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct ManualClock {
+    shared: Rc<Shared>,
+}
+
+impl ManualClock {
+    /// Creates a clock at time zero.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Moves the clock forward by `duration`, waking any sleeps that may
+    /// have finished.
+    pub fn advance(&self, duration: Duration) {
+        let now = &self.shared.now;
+        now.set(now.get() + duration);
+
+        let wakers = self.shared.wakers.take();
+        for waker in wakers {
+            waker.wake();
+        }
+    }
+}
+
+impl Clock for ManualClock {
+    type Instant = ManualInstant;
+
+    fn now(&self) -> Self::Instant {
+        ManualInstant(self.shared.now.get())
+    }
+}
+
+impl Timer for ManualClock {
+    type Sleep = ManualSleep;
+
+    fn sleep_until(&self, deadline: Self::Instant) -> Self::Sleep {
+        ManualSleep {
+            clock: self.clone(),
+            deadline,
+        }
+    }
+}
+
+/// The future returned by [`ManualClock::sleep_until`](Timer::sleep_until).
+///
+/// This type is available when the `alloc` feature is enabled.
+#[derive(Debug)]
+#[must_use = "futures do nothing unless you `.await` or poll them"]
+pub struct ManualSleep {
+    clock: ManualClock,
+    deadline: ManualInstant,
+}
+
+impl ManualSleep {
+    /// Returns the instant at which this future completes.
+    pub fn deadline(&self) -> ManualInstant {
+        self.deadline
+    }
+}
+
+impl Future for ManualSleep {
+    type Output = ();
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<()> {
+        if self.clock.now() >= self.deadline {
+            return Poll::Ready(());
+        }
+
+        let mut wakers = self.clock.shared.wakers.borrow_mut();
+        if !wakers.iter().any(|w| w.will_wake(cx.waker())) {
+            wakers.push(cx.waker().clone());
+        }
+        Poll::Pending
+    }
+}
@@ -0,0 +1,176 @@
+use std::{
+    collections::BTreeMap,
+    future::Future,
+    pin::Pin,
+    sync::{Mutex, MutexGuard},
+    task::{Context, Poll, Waker},
+    thread::{self, Thread},
+    time::Duration,
+};
+
+use crate::time::{Clock, Instant, Timer};
+
+impl Instant for std::time::Instant {
+    fn saturating_duration_since(&self, earlier: Self) -> Duration {
+        std::time::Instant::saturating_duration_since(self, earlier)
+    }
+
+    fn checked_add(&self, duration: Duration) -> Option<Self> {
+        std::time::Instant::checked_add(self, duration)
+    }
+}
+
+/// Zero-sized struct that implements [`Clock`] and [`Timer`] with
+/// [`std::time::Instant`].
+///
+/// Sleeping doesn't need a runtime: pending [`StdSleep`]s are woken by a
+/// single timer thread, started the first time anything sleeps, which parks
+/// until the earliest deadline. An executor's own timer is still a better
+/// fit for hot paths.
+///
+/// This type is available when the `std` feature is enabled.
+///
+/// # Examples
+///
+/// ```
+/// use std::time::Duration;
+/// use coreplus::task::block_on;
+/// use coreplus::time::{Clock, StdClock, Timer};
+///
+/// let start = StdClock.now();
+/// block_on(StdClock.sleep_until(start + Duration::from_millis(10)));
+/// assert!(start.elapsed() >= Duration::from_millis(10));
+/// ```
+#[derive(Clone, Copy, Debug, Default)]
+pub struct StdClock;
+
+impl Clock for StdClock {
+    type Instant = std::time::Instant;
+
+    fn now(&self) -> Self::Instant {
+        std::time::Instant::now()
+    }
+}
+
+impl Timer for StdClock {
+    type Sleep = StdSleep;
+
+    fn sleep_until(&self, deadline: Self::Instant) -> Self::Sleep {
+        StdSleep { deadline, id: None }
+    }
+}
+
+/// The future returned by [`StdClock::sleep_until`](Timer::sleep_until).
+///
+/// This type is available when the `std` feature is enabled.
+#[derive(Debug)]
+#[must_use = "futures do nothing unless you `.await` or poll them"]
+pub struct StdSleep {
+    deadline: std::time::Instant,
+    /// The key of this sleep in the timer thread's queue, once it's there.
+    id: Option<u64>,
+}
+
+impl StdSleep {
+    /// Returns the instant at which this future completes.
+    pub fn deadline(&self) -> std::time::Instant {
+        self.deadline
+    }
+}
+
+impl Future for StdSleep {
+    type Output = ();
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<()> {
+        if std::time::Instant::now() >= self.deadline {
+            return Poll::Ready(());
+        }
+
+        let mut queue = lock_queue();
+        let id = match self.id {
+            Some(id) => id,
+            None => {
+                let id = queue.next_id;
+                queue.next_id += 1;
+                id
+            }
+        };
+        let key = (self.deadline, id);
+        match queue.sleeping.get_mut(&key) {
+            Some(waker) if waker.will_wake(cx.waker()) => {}
+            Some(waker) => *waker = cx.waker().clone(),
+            None => {
+                let earliest = match queue.sleeping.keys().next() {
+                    Some(&(first, _)) => self.deadline < first,
+                    None => true,
+                };
+                queue.sleeping.insert(key, cx.waker().clone());
+                let thread = queue.thread.get_or_insert_with(spawn_timer);
+                if earliest {
+                    thread.unpark();
+                }
+            }
+        }
+        self.id = Some(id);
+        Poll::Pending
+    }
+}
+
+impl Drop for StdSleep {
+    fn drop(&mut self) {
+        if let Some(id) = self.id {
+            lock_queue().sleeping.remove(&(self.deadline, id));
+        }
+    }
+}
+
+/// The sleeps the timer thread is waiting on, earliest first.
+struct Queue {
+    sleeping: BTreeMap<(std::time::Instant, u64), Waker>,
+    next_id: u64,
+    thread: Option<Thread>,
+}
+
+static QUEUE: Mutex<Queue> = Mutex::new(Queue {
+    sleeping: BTreeMap::new(),
+    next_id: 0,
+    thread: None,
+});
+
+fn lock_queue() -> MutexGuard<'static, Queue> {
+    QUEUE.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+fn spawn_timer() -> Thread {
+    thread::Builder::new()
+        .name("coreplus-timer".into())
+        .spawn(|| {
+            let mut expired = Vec::new();
+            loop {
+                let now = std::time::Instant::now();
+                let next = {
+                    let mut queue = lock_queue();
+                    while let Some(entry) = queue.sleeping.first_entry() {
+                        if entry.key().0 > now {
+                            break;
+                        }
+                        expired.push(entry.remove());
+                    }
+                    queue.sleeping.keys().next().map(|&(deadline, _)| deadline)
+                };
+
+                // Wake outside the lock, since waking can poll right away.
+                for waker in expired.drain(..) {
+                    waker.wake();
+                }
+                match next {
+                    // Parking may return early, so the loop checks again.
+                    Some(deadline) => thread::park_timeout(deadline - now),
+                    None => thread::park(),
+                }
+            }
+        })
+        .expect("failed to spawn the timer thread")
+        .thread()
+        .clone()
+}
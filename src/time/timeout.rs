@@ -0,0 +1,335 @@
+use core::{
+    fmt,
+    future::Future,
+    pin::Pin,
+    task::{Context, Poll},
+    time::Duration,
+};
+
+use crate::{
+    error::{impl_foreign_error, Error},
+    io::{AsyncRead, AsyncWrite, ReadBuf},
+    net::{AsyncRecv, AsyncRecvFrom, AsyncSend, AsyncSendTo, SocketAddr},
+    time::{Instant, Timer},
+};
+
+/// The error type for [`Timeout`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TimeoutError<E> {
+    /// The operation didn't finish before the deadline.
+    TimedOut,
+    /// The underlying object returned an error.
+    Other(E),
+}
+
+impl<E: fmt::Display> fmt::Display for TimeoutError<E> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TimeoutError::TimedOut => f.write_str("operation timed out"),
+            TimeoutError::Other(e) => e.fmt(f),
+        }
+    }
+}
+
+impl<E: Error> Error for TimeoutError<E> {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            TimeoutError::TimedOut => None,
+            TimeoutError::Other(e) => e.source(),
+        }
+    }
+}
+
+impl_foreign_error! {
+    impl<E> for TimeoutError<E>;
+    fn source(&self) {
+        match self {
+            TimeoutError::TimedOut => None,
+            TimeoutError::Other(e) => e.source(),
+        }
+    }
+}
+
+/// Fails operations on the wrapped object that take longer than a timeout.
+///
+/// The timeout applies to each operation separately: the clock starts the
+/// first time an operation returns [`Poll::Pending`], and stops when it
+/// finishes. Reads and receives are timed independently of writes and sends,
+/// so the two directions can be used at the same time.
+///
+/// # Examples
+///
+/// ```
+/// # #[cfg(feature = "alloc")] {
+/// use core::{pin::Pin, time::Duration};
+/// use coreplus::io::AsyncRead;
+/// use coreplus::task::{block_on, join, poll_fn};
+/// use coreplus::time::{ManualClock, Timeout, TimeoutError};
+/// # use core::task::{Context, Poll};
+/// # struct Silent;
+/// # impl AsyncRead for Silent {
+/// #     type Error = core::convert::Infallible;
+/// #     fn poll_read(
+/// #         self: Pin<&mut Self>,
+/// #         _cx: &mut Context<'_>,
+/// #         _buf: &mut [u8],
+/// #     ) -> Poll<Result<usize, Self::Error>> {
+/// #         Poll::Pending
+/// #     }
+/// # }
+///
+/// // `Silent` is a reader that never has any data.
+/// let clock = ManualClock::new();
+/// let mut reader = Timeout::new(Silent, clock.clone(), Duration::from_secs(5));
+///
+/// let mut buf = [0; 16];
+/// let (result, ()) = block_on(join(
+///     poll_fn(|cx| Pin::new(&mut reader).poll_read(cx, &mut buf)),
+///     async { clock.advance(Duration::from_secs(5)) },
+/// ));
+/// assert_eq!(result, Err(TimeoutError::TimedOut));
+/// # }
+/// ```
+pub struct Timeout<S, T: Timer> {
+    inner: S,
+    timer: T,
+    timeout: Duration,
+    read_sleep: Option<T::Sleep>,
+    write_sleep: Option<T::Sleep>,
+}
+
+impl<S, T: Timer> Timeout<S, T> {
+    /// Wraps `inner`, failing any operation that is pending for longer than
+    /// `timeout`.
+    pub fn new(inner: S, timer: T, timeout: Duration) -> Self {
+        Self {
+            inner,
+            timer,
+            timeout,
+            read_sleep: None,
+            write_sleep: None,
+        }
+    }
+
+    /// Returns the timeout.
+    pub fn timeout(&self) -> Duration {
+        self.timeout
+    }
+
+    /// Sets the timeout. Operations that are already pending keep their
+    /// original deadline.
+    pub fn set_timeout(&mut self, timeout: Duration) {
+        self.timeout = timeout;
+    }
+
+    /// Gets a reference to the underlying object.
+    pub fn get_ref(&self) -> &S {
+        &self.inner
+    }
+
+    /// Gets a mutable reference to the underlying object.
+    pub fn get_mut(&mut self) -> &mut S {
+        &mut self.inner
+    }
+
+    /// Gets a pinned mutable reference to the underlying object.
+    pub fn get_pin_mut(self: Pin<&mut Self>) -> Pin<&mut S> {
+        self.project().inner
+    }
+
+    /// Unwraps this `Timeout`, returning the underlying object.
+    pub fn into_inner(self) -> S {
+        self.inner
+    }
+
+    fn project(self: Pin<&mut Self>) -> TimeoutProj<'_, S, T> {
+        // SAFETY: `inner` and the sleep futures are structurally pinned and
+        // never moved out of a pinned `Timeout`. Nothing else is pinned.
+        unsafe {
+            let this = self.get_unchecked_mut();
+            TimeoutProj {
+                inner: Pin::new_unchecked(&mut this.inner),
+                timer: &this.timer,
+                timeout: this.timeout,
+                read_sleep: Pin::new_unchecked(&mut this.read_sleep),
+                write_sleep: Pin::new_unchecked(&mut this.write_sleep),
+            }
+        }
+    }
+}
+
+impl<S: fmt::Debug, T: Timer + fmt::Debug> fmt::Debug for Timeout<S, T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Timeout")
+            .field("inner", &self.inner)
+            .field("timer", &self.timer)
+            .field("timeout", &self.timeout)
+            .finish()
+    }
+}
+
+struct TimeoutProj<'a, S, T: Timer> {
+    inner: Pin<&'a mut S>,
+    timer: &'a T,
+    timeout: Duration,
+    read_sleep: Pin<&'a mut Option<T::Sleep>>,
+    write_sleep: Pin<&'a mut Option<T::Sleep>>,
+}
+
+/// Turns the result of polling an operation into a timed one, starting the
+/// clock the first time it is pending.
+fn poll_timeout<T: Timer, R, E>(
+    timer: &T,
+    timeout: Duration,
+    mut sleep: Pin<&mut Option<T::Sleep>>,
+    cx: &mut Context<'_>,
+    poll: Poll<Result<R, E>>,
+) -> Poll<Result<R, TimeoutError<E>>> {
+    if let Poll::Ready(result) = poll {
+        sleep.set(None);
+        return Poll::Ready(result.map_err(TimeoutError::Other));
+    }
+
+    if sleep.is_none() {
+        match timer.now().checked_add(timeout) {
+            Some(deadline) => sleep.set(Some(timer.sleep_until(deadline))),
+            // A deadline that can't be represented is never reached.
+            None => return Poll::Pending,
+        }
+    }
+
+    match sleep.as_mut().as_pin_mut().map(|s| s.poll(cx)) {
+        Some(Poll::Ready(())) => {
+            sleep.set(None);
+            Poll::Ready(Err(TimeoutError::TimedOut))
+        }
+        _ => Poll::Pending,
+    }
+}
+
+impl<S: AsyncRead, T: Timer> AsyncRead for Timeout<S, T> {
+    type Error = TimeoutError<S::Error>;
+
+    fn poll_read(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut [u8],
+    ) -> Poll<Result<usize, Self::Error>> {
+        let this = self.project();
+        let poll = this.inner.poll_read(cx, buf);
+        poll_timeout(this.timer, this.timeout, this.read_sleep, cx, poll)
+    }
+
+    fn poll_read_buf(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut ReadBuf<'_>,
+    ) -> Poll<Result<(), Self::Error>> {
+        let this = self.project();
+        let poll = this.inner.poll_read_buf(cx, buf);
+        poll_timeout(this.timer, this.timeout, this.read_sleep, cx, poll)
+    }
+}
+
+impl<S: AsyncWrite, T: Timer> AsyncWrite for Timeout<S, T> {
+    type Error = TimeoutError<S::Error>;
+
+    fn poll_write(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<Result<usize, Self::Error>> {
+        let this = self.project();
+        let poll = this.inner.poll_write(cx, buf);
+        poll_timeout(this.timer, this.timeout, this.write_sleep, cx, poll)
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        let this = self.project();
+        let poll = this.inner.poll_flush(cx);
+        poll_timeout(this.timer, this.timeout, this.write_sleep, cx, poll)
+    }
+
+    fn poll_close(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        let this = self.project();
+        let poll = this.inner.poll_close(cx);
+        poll_timeout(this.timer, this.timeout, this.write_sleep, cx, poll)
+    }
+}
+
+impl<S: AsyncSendTo, T: Timer> AsyncSendTo for Timeout<S, T> {
+    type Error = TimeoutError<S::Error>;
+
+    fn poll_send_to(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &[u8],
+        addr: SocketAddr,
+    ) -> Poll<Result<usize, Self::Error>> {
+        let this = self.project();
+        let poll = this.inner.poll_send_to(cx, buf, addr);
+        poll_timeout(this.timer, this.timeout, this.write_sleep, cx, poll)
+    }
+}
+
+impl<S: AsyncRecvFrom, T: Timer> AsyncRecvFrom for Timeout<S, T> {
+    type Error = TimeoutError<S::Error>;
+
+    fn poll_recv_from(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut [u8],
+    ) -> Poll<Result<(usize, SocketAddr), Self::Error>> {
+        let this = self.project();
+        let poll = this.inner.poll_recv_from(cx, buf);
+        poll_timeout(this.timer, this.timeout, this.read_sleep, cx, poll)
+    }
+
+    fn poll_recv_from_buf(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut ReadBuf<'_>,
+    ) -> Poll<Result<SocketAddr, Self::Error>> {
+        let this = self.project();
+        let poll = this.inner.poll_recv_from_buf(cx, buf);
+        poll_timeout(this.timer, this.timeout, this.read_sleep, cx, poll)
+    }
+}
+
+impl<S: AsyncSend, T: Timer> AsyncSend for Timeout<S, T> {
+    type Error = TimeoutError<S::Error>;
+
+    fn poll_send(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<Result<usize, Self::Error>> {
+        let this = self.project();
+        let poll = this.inner.poll_send(cx, buf);
+        poll_timeout(this.timer, this.timeout, this.write_sleep, cx, poll)
+    }
+}
+
+impl<S: AsyncRecv, T: Timer> AsyncRecv for Timeout<S, T> {
+    type Error = TimeoutError<S::Error>;
+
+    fn poll_recv(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut [u8],
+    ) -> Poll<Result<usize, Self::Error>> {
+        let this = self.project();
+        let poll = this.inner.poll_recv(cx, buf);
+        poll_timeout(this.timer, this.timeout, this.read_sleep, cx, poll)
+    }
+
+    fn poll_recv_buf(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut ReadBuf<'_>,
+    ) -> Poll<Result<(), Self::Error>> {
+        let this = self.project();
+        let poll = this.inner.poll_recv_buf(cx, buf);
+        poll_timeout(this.timer, this.timeout, this.read_sleep, cx, poll)
+    }
+}